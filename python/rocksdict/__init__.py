@@ -4,6 +4,7 @@ __doc__ = rocksdict.__doc__
 
 __all__ = ["Rdict",
            "WriteBatch",
+           "WriteBatchWithIndex",
            "SstFileWriter",
            "AccessType",
            "WriteOptions",
@@ -26,6 +27,7 @@ __all__ = ["Rdict",
            "Cache",
            "ChecksumType",
            "DBCompactionStyle",
+           "CompactionPri",
            "DBCompressionType",
            "DBRecoveryMode",
            "Env",
@@ -33,9 +35,16 @@ __all__ = ["Rdict",
            "CompactOptions",
            "BottommostLevelCompaction",
            "KeyEncodingType",
+           "RocksDBError",
            "DbClosedError",
+           "KeyNotFoundError",
            "WriteBufferManager",
-           "Checkpoint"]
+           "Checkpoint",
+           "BackupEngine",
+           "OptimisticTransactionDB",
+           "Transaction",
+           "PinnableSlice",
+           "WalIterator"]
 
 Rdict.__enter__ = lambda self: self
 Rdict.__exit__ = lambda self, exc_type, exc_val, exc_tb: self.close()
@@ -1,7 +1,7 @@
 from __future__ import annotations
 
 from types import TracebackType
-from typing import Any, Callable, Dict, Iterator, List, Optional, Tuple, Type, Union
+from typing import Any, Callable, Dict, Iterable, Iterator, List, Literal, Optional, Tuple, Type, Union
 
 __all__ = ["Rdict",
            "RdictIter",
@@ -21,6 +21,7 @@ __all__ = ["Rdict",
            "Cache",
            "ChecksumType",
            "DBCompactionStyle",
+           "CompactionPri",
            "DBCompressionType",
            "DBRecoveryMode",
            "Env",
@@ -34,7 +35,9 @@ __all__ = ["Rdict",
            "CompactOptions",
            "BottommostLevelCompaction",
            "KeyEncodingType",
+           "RocksDBError",
            "DbClosedError",
+           "KeyNotFoundError",
            "WriteBufferManager",
            "Checkpoint"]
 
@@ -71,6 +74,10 @@ class BlockBasedOptions:
     def set_block_restart_interval(self, interval: int) -> None: ...
     def set_block_size(self, size: int) -> None: ...
     def set_bloom_filter(self, bits_per_key: int, block_based: bool) -> None: ...
+    def set_ribbon_filter(self, bloom_equivalent_bits_per_key: float) -> None: ...
+    def set_ribbon_filter_with_bloom_fallback(self, bloom_equivalent_bits_per_key: float, bloom_before_level: int) -> None: ...
+    def set_optimize_filters_for_memory(self, v: bool) -> None: ...
+    def set_whole_key_filtering(self, v: bool) -> None: ...
     def set_cache_index_and_filter_blocks(self, v: bool) -> None: ...
     def set_data_block_hash_ratio(self, ratio: float) -> None: ...
     def set_data_block_index_type(self, index_type: DataBlockIndexType) -> None: ...
@@ -89,6 +96,7 @@ class Cache:
     def new_hyper_clock_cache(capacity: int, estimated_entry_charge: int) -> Cache: ...
     def get_pinned_usage(self) -> int: ...
     def get_usage(self) -> int: ...
+    def get_capacity(self) -> int: ...
     def set_capacity(self, capacity: int) -> None: ...
 
 class CuckooTableOptions:
@@ -107,6 +115,18 @@ class DBCompactionStyle:
     @staticmethod
     def universal() -> DBCompactionStyle: ...
 
+class CompactionPri:
+    @staticmethod
+    def by_compensated_size() -> CompactionPri: ...
+    @staticmethod
+    def oldest_largest_seq_first() -> CompactionPri: ...
+    @staticmethod
+    def oldest_smallest_seq_first() -> CompactionPri: ...
+    @staticmethod
+    def min_overlapping_ratio() -> CompactionPri: ...
+    @staticmethod
+    def round_robin() -> CompactionPri: ...
+
 class DBCompressionType:
     @staticmethod
     def bz2() -> DBCompressionType: ...
@@ -125,6 +145,10 @@ class DBCompressionType:
 
 class DBPath:
     def __init__(self, path: str, target_size: int) -> None: ...
+    @property
+    def path(self) -> str: ...
+    @property
+    def target_size(self) -> int: ...
 
 class DBRecoveryMode:
     @staticmethod
@@ -166,9 +190,9 @@ class FlushOptions:
 
 class MemtableFactory:
     @staticmethod
-    def hash_link_list() -> MemtableFactory: ...
+    def hash_link_list(bucket_count: int) -> MemtableFactory: ...
     @staticmethod
-    def hash_skip_list() -> MemtableFactory: ...
+    def hash_skip_list(bucket_count: int, height: int, branching_factor: int) -> MemtableFactory: ...
     @staticmethod
     def vector() -> MemtableFactory: ...
 
@@ -180,6 +204,8 @@ class Options:
     def create_missing_column_families(self, create_missing_cfs: bool) -> None: ...
     def enable_statistics(self) -> None: ...
     def get_statistics(self) -> Optional[str]: ...
+    def get_ticker_count(self, ticker_name: str) -> int: ...
+    def get_histogram_data(self, histogram_name: str) -> Dict[str, float]: ...
     def increase_parallelism(self, parallelism: int) -> None: ...
     def optimize_for_point_lookup(self, cache_size: int) -> None: ...
     def optimize_level_style_compaction(self, memtable_memory_budget: int) -> None: ...
@@ -196,6 +222,7 @@ class Options:
     def set_bytes_per_sync(self, nbytes: int) -> None: ...
     def set_compaction_readahead_size(self, compaction_readahead_size: int) -> None: ...
     def set_compaction_style(self, style: DBCompactionStyle) -> None: ...
+    def set_compaction_pri(self, pri: CompactionPri) -> None: ...
     def set_compression_options(self, w_bits: int, level: int, strategy: int, max_dict_bytes: int) -> None: ...
     def set_compression_per_level(self, level_types: List[DBCompressionType]) -> None: ...
     def set_compression_type(self, t: DBCompressionType) -> None: ...
@@ -205,9 +232,11 @@ class Options:
     def set_cuckoo_table_factory(self, factory: CuckooTableOptions) -> None: ...
     def set_db_log_dir(self, path: str) -> None: ...
     def set_db_paths(self, paths: List[DBPath]) -> None: ...
+    def get_db_paths(self) -> List[Tuple[str, int]]: ...
     def set_db_write_buffer_size(self, size: int) -> None: ...
     def set_delete_obsolete_files_period_micros(self, micros: int) -> None: ...
     def set_disable_auto_compactions(self, disable: bool) -> None: ...
+    def get_disable_auto_compactions(self) -> bool: ...
     def set_dump_malloc_stats(self, enabled: bool) -> None: ...
     def set_enable_pipelined_write(self, value: bool) -> None: ...
     def set_enable_write_thread_adaptive_yield(self, enabled: bool) -> None: ...
@@ -220,25 +249,40 @@ class Options:
     def set_is_fd_close_on_exec(self, enabled: bool) -> None: ...
     def set_keep_log_file_num(self, nfiles: int) -> None: ...
     def set_level_compaction_dynamic_level_bytes(self, v: bool) -> None: ...
+    def set_merge_operator_associative(self, name: str, merge_fn: Callable[[Any, Any, List[Any]], Any]) -> None: ...
+    def set_comparator(self, name: str, compare_fn: Callable[[bytes, bytes], int]) -> None: ...
+    def set_compaction_filter(self, name: str, filter_fn: Callable[[int, Any, Any], Union[str, Tuple[str, Any]]]) -> None: ...
+    def set_compaction_filter_factory(self, factory: Callable[[bool, bool], Callable[[int, Any, Any], Union[str, Tuple[str, Any]]]], name: str = "rocksdict_compaction_filter_factory") -> None: ...
+    def set_per_key_ttl_filter(self) -> None: ...
     def set_level_zero_file_num_compaction_trigger(self, n: int) -> None: ...
+    def get_level_zero_file_num_compaction_trigger(self) -> int: ...
     def set_level_zero_slowdown_writes_trigger(self, n_int: int) -> None: ...
+    def get_level_zero_slowdown_writes_trigger(self) -> int: ...
     def set_level_zero_stop_writes_trigger(self, n: int) -> None: ...
+    def get_level_zero_stop_writes_trigger(self) -> int: ...
     def set_log_file_time_to_roll(self, secs: int) -> None: ...
     def set_manifest_preallocation_size(self, size: int) -> None: ...
     def set_max_background_jobs(self, jobs: int) -> None: ...
+    def get_max_background_jobs(self) -> int: ...
     def set_max_bytes_for_level_base(self, size: int) -> None: ...
+    def get_max_bytes_for_level_base(self) -> int: ...
     def set_max_bytes_for_level_multiplier(self, mul: float) -> None: ...
+    def get_max_bytes_for_level_multiplier(self) -> float: ...
     def set_max_bytes_for_level_multiplier_additional(self, level_values: List[int]) -> None: ...
     def set_max_compaction_bytes(self, nbytes: int) -> None: ...
+    def get_max_compaction_bytes(self) -> int: ...
     def set_max_file_opening_threads(self, nthreads: int) -> None: ...
     def set_max_log_file_size(self, size: int) -> None: ...
     def set_max_manifest_file_size(self, size: int) -> None: ...
     def set_max_open_files(self, nfiles: int) -> None: ...
+    def get_max_open_files(self) -> int: ...
     def set_max_sequential_skip_in_iterations(self, num: int) -> None: ...
     def set_max_subcompactions(self, num: int) -> None: ...
+    def get_max_subcompactions(self) -> int: ...
     def set_max_successive_merges(self, num: int) -> None: ...
     def set_max_total_wal_size(self, size: int) -> None: ...
     def set_max_write_buffer_number(self, nbuf: int) -> None: ...
+    def get_max_write_buffer_number(self) -> int: ...
     def set_max_write_buffer_size_to_maintain(self, size: int) -> None: ...
     def set_memtable_factory(self, factory: MemtableFactory) -> None: ...
     def set_memtable_huge_page_size(self, size: int) -> None: ...
@@ -247,11 +291,21 @@ class Options:
     def set_min_level_to_compress(self, lvl: int) -> None: ...
     def set_min_write_buffer_number(self, nbuf: int) -> None: ...
     def set_min_write_buffer_number_to_merge(self, to_merge: int) -> None: ...
+    def get_min_write_buffer_number_to_merge(self) -> int: ...
+    def set_normalize_str_bytes_keys(self, normalize: bool) -> None: ...
+    def set_serializer(self, dumps: Callable[[Any], bytes], loads: Callable[[bytes], Any]) -> None: ...
     def set_num_levels(self, n: int) -> None: ...
+    def get_num_levels(self) -> int: ...
     def set_optimize_filters_for_hits(self, optimize_for_hits: bool) -> None: ...
     def set_paranoid_checks(self, enabled: bool) -> None: ...
     def set_plain_table_factory(self, options: PlainTableFactoryOptions) -> None: ...
     def set_prefix_extractor(self, prefix_extractor: SliceTransform) -> None: ...
+    def configure_prefix_lookup(self,
+                                prefix_len: int,
+                                bloom_bits: float = 10.0,
+                                table_options: Optional[BlockBasedOptions] = None) -> None: ...
+    def set_builtin_comparator(self, name: str) -> None: ...
+    def enable_user_timestamp(self) -> None: ...
     def set_ratelimiter(self, rate_bytes_per_sec: int, refill_period_us: int, fairness: int) -> None: ...
     def set_recycle_log_file_num(self, num: int) -> None: ...
     def set_report_bg_io_stats(self, enable: bool) -> None: ...
@@ -263,13 +317,16 @@ class Options:
     def set_stats_persist_period_sec(self, period: int) -> None: ...
     def set_table_cache_num_shard_bits(self, nbits: int) -> None: ...
     def set_target_file_size_base(self, size: int) -> None: ...
+    def get_target_file_size_base(self) -> int: ...
     def set_target_file_size_multiplier(self, multiplier: int) -> None: ...
+    def get_target_file_size_multiplier(self) -> int: ...
     def set_universal_compaction_options(self, uco: UniversalCompactOptions) -> None: ...
     def set_unordered_write(self, unordered: bool) -> None: ...
     def set_use_adaptive_mutex(self, enabled: bool) -> None: ...
     def set_use_direct_io_for_flush_and_compaction(self, enabled: bool) -> None: ...
     def set_use_direct_reads(self, enabled: bool) -> None: ...
     def set_use_fsync(self, useit: bool) -> None: ...
+    def get_use_fsync(self) -> bool: ...
     def set_wal_bytes_per_sync(self, nbytes: int) -> None: ...
     def set_wal_dir(self, path: str) -> None: ...
     def set_wal_recovery_mode(self, mode: DBRecoveryMode) -> None: ...
@@ -277,6 +334,7 @@ class Options:
     def set_wal_ttl_seconds(self, secs: int) -> None: ...
     def set_writable_file_max_buffer_size(self, nbytes: int) -> None: ...
     def set_write_buffer_size(self, size: int) -> None: ...
+    def get_write_buffer_size(self) -> int: ...
     def set_zstd_max_train_bytes(self, value: int) -> None: ...
     def set_enable_blob_files(self, val: bool) -> None: ...
     def set_min_blob_size(self, val: int) -> None: ...
@@ -324,8 +382,13 @@ class ReadOptions:
     def set_readahead_size(self, v: int) -> None: ...
     def set_tailing(self, v: bool) -> None: ...
     def set_total_order_seek(self, v: bool) -> None: ...
+    def set_auto_total_order_seek(self, v: bool) -> None: ...
     def set_verify_checksums(self, v: bool) -> None: ...
     def set_async_io(self, v: bool) -> None: ...
+    def set_auto_readahead_size(self, v: bool) -> None: ...
+    def set_snapshot(self, snapshot: Optional[Snapshot]) -> None: ...
+    def set_timestamp(self, ts: Optional[int]) -> None: ...
+    def set_iter_start_ts(self, ts: Optional[int]) -> None: ...
 
 class SliceTransform:
     @staticmethod
@@ -400,7 +463,8 @@ class IngestExternalFileOptions:
     def set_allow_blocking_flush(self, v: bool) -> None: ...
     def set_ingest_behind(self, v: bool) -> None: ...
 
-class ColumnFamily: ...
+class ColumnFamily:
+    def id(self) -> int: ...
 
 class AccessType:
     @staticmethod
@@ -438,6 +502,9 @@ class Rdict:
     def set_dumps(self, dumps: Callable[[Any], bytes]) -> None: ...
     def set_loads(self, dumps: Callable[[bytes], Any]) -> None: ...
     def set_read_options(self, read_opt: ReadOptions) -> None: ...
+    def set_drop_behavior(self, behavior: Literal["flush", "flush_wal", "none"]) -> None: ...
+    def set_schema(self, key_type: Optional[Union[type, Tuple[type, ...]]] = None,
+                   value_type: Optional[Union[type, Tuple[type, ...]]] = None) -> None: ...
     def set_write_options(self, write_opt: WriteOptions) -> None: ...
     def __contains__(self, key: Union[str, int, float, bytes, bool]) -> bool: ...
     def __delitem__(self, key: Union[str, int, float, bytes, bool]) -> None: ...
@@ -447,6 +514,24 @@ class Rdict:
             key: Union[str, int, float, bytes, bool, List[Union[str, int, float, bytes, bool]]],
             default: Any = None,
             read_opt: Optional[ReadOptions] = None) -> Any | None: ...
+    def multi_get_cf(self, pairs: List[Tuple[ColumnFamily, Union[str, int, float, bytes, bool]]],
+                     default: Any = None,
+                     read_opt: Optional[ReadOptions] = None) -> List[Any]: ...
+    def get_pinned(self, key: bytes, read_opt: Optional[ReadOptions] = None) -> Optional[PinnableSlice]: ...
+    def get_batch(self,
+                  keys: List[Union[str, int, float, bytes, bool]],
+                  read_opt: Optional[ReadOptions] = None,
+                  as_dict: bool = False,
+                  skip_missing: bool = False) -> Union[List[Any], Dict[Any, Any]]: ...
+    def get_with_flag(self,
+                      key: Union[str, int, float, bytes, bool],
+                      read_opt: Optional[ReadOptions] = None) -> Tuple[bool, Any]: ...
+    def getset(self,
+               key: Union[str, int, float, bytes, bool],
+               value: Any,
+               write_opt: Optional[WriteOptions] = None) -> Any: ...
+    def get_raw(self, key: bytes, read_opt: Optional[ReadOptions] = None) -> Optional[bytes]: ...
+    def put_raw(self, key: bytes, value: bytes, write_opt: Optional[WriteOptions] = None) -> None: ...
     def get_entity(self,
                    key: Union[str, int, float, bytes, bool, List[Union[str, int, float, bytes, bool]]],
                    default: Any = None,
@@ -455,12 +540,32 @@ class Rdict:
             key: Union[str, int, float, bytes, bool],
             value: Any,
             write_opt: Optional[WriteOptions] = None) -> None: ...
+    def put_with_ttl(self, key: Union[str, int, float, bytes, bool],
+                     value: Any, ttl_seconds: int,
+                     write_opt: Optional[WriteOptions] = None) -> None: ...
+    def put_with_ts(self, key: bytes, ts: int, value: bytes,
+                    write_opt: Optional[WriteOptions] = None) -> None: ...
+    def update_value(self,
+                     key: Union[str, int, float, bytes, bool],
+                     update_fn: Callable[[Any], Any],
+                     default: Any = None,
+                     write_opt: Optional[WriteOptions] = None) -> Any: ...
+    def merge(self,
+              key: Union[str, int, float, bytes, bool],
+              value: Any,
+              write_opt: Optional[WriteOptions] = None) -> None: ...
     def put_entity(self,
                    key: Union[str, int, float, bytes, bool],
                    names: List[Any],
                    values: List[Any],
                    write_opt: Optional[WriteOptions] = None) -> None: ...
+    def delete_batch(self, keys: Iterable[Union[str, int, float, bytes, bool]],
+                     write_opt: Optional[WriteOptions] = None) -> None: ...
     def delete(self, key: Union[str, int, float, bytes, bool], write_opt: Optional[WriteOptions] = None) -> None: ...
+    def delete_with_ts(self, key: bytes, ts: int, write_opt: Optional[WriteOptions] = None) -> None: ...
+    def enable_write_buffering(self, max_ops: int = 10000) -> None: ...
+    def disable_write_buffering(self) -> None: ...
+    def flush_writes(self) -> None: ...
     def key_may_exist(self,
                       key: Union[str, int, float, bytes, bool],
                       fetch: bool = False,
@@ -468,13 +573,20 @@ class Rdict:
     def iter(self, read_opt: Optional[ReadOptions] = None) -> RdictIter: ...
     def items(self, backwards: bool = False,
               from_key: Union[str, int, float, bytes, bool, None] = None,
-              read_opt: Optional[ReadOptions] = None) -> RdictItems: ...
+              read_opt: Optional[ReadOptions] = None,
+              to_key: Union[str, int, float, bytes, bool, None] = None) -> RdictItems: ...
+    def iter_prefix(self, prefix: Union[str, bytes, Tuple[Any, ...]],
+                    read_opt: Optional[ReadOptions] = None) -> RdictItems: ...
+    def range_numeric(self, lo: int, hi: int,
+                      read_opt: Optional[ReadOptions] = None) -> RdictItems: ...
     def keys(self, backwards: bool = False,
              from_key: Union[str, int, float, bytes, bool, None] = None,
-             read_opt: Optional[ReadOptions] = None) -> RdictKeys: ...
+             read_opt: Optional[ReadOptions] = None,
+             to_key: Union[str, int, float, bytes, bool, None] = None) -> RdictKeys: ...
     def values(self, backwards: bool = False,
                from_key: Union[str, int, float, bytes, bool, None] = None,
-               read_opt: Optional[ReadOptions] = None) -> RdictValues: ...
+               read_opt: Optional[ReadOptions] = None,
+               to_key: Union[str, int, float, bytes, bool, None] = None) -> RdictValues: ...
     def columns(self, backwards: bool = False,
                 from_key: Union[str, int, float, bytes, bool, None] = None,
                 read_opt: Optional[ReadOptions] = None) -> RdictColumns: ...
@@ -482,35 +594,79 @@ class Rdict:
                 from_key: Union[str, int, float, bytes, bool, None] = None,
                 read_opt: Optional[ReadOptions] = None) -> RdictEntities: ...
     def ingest_external_file(self, paths: List[str], opts: IngestExternalFileOptions = IngestExternalFileOptions()) -> None: ...
+    def bulk_load(self,
+                  items: Iterable[Tuple[Union[str, int, float, bytes, bool], Any]],
+                  tmp_dir: str,
+                  max_file_size: int = 256 * 1024 * 1024) -> None: ...
     def get_column_family(self, name: str) -> Rdict: ...
     def get_column_family_handle(self, name: str) -> ColumnFamily: ...
     def drop_column_family(self, name: str) -> None: ...
     def create_column_family(self, name: str, options: Options = Options()) -> Rdict: ...
-    def write(self, write_batch: WriteBatch, write_opt: Optional[WriteOptions] = None) -> None: ...
+    def cf_names(self) -> List[str]: ...
+    def column_families(self) -> Dict[str, Rdict]: ...
+    def update(self,
+               other: Union[Dict[Any, Any], Iterable[Tuple[Union[str, int, float, bytes, bool], Any]]],
+               write_opt: Optional[WriteOptions] = None) -> None: ...
+    def write_parallel(self,
+                       items: Union[Dict[Any, Any], Iterable[Tuple[Union[str, int, float, bytes, bool], Any]]],
+                       threads: Optional[int] = None,
+                       write_opt: Optional[WriteOptions] = None) -> None: ...
+    def write(self, write_batch: Union[WriteBatch, WriteBatchWithIndex], write_opt: Optional[WriteOptions] = None) -> None: ...
     def delete_range(self,
                      begin: Union[str, int, float, bytes, bool],
                      end: Union[str, int, float, bytes, bool],
                      write_opt: Optional[WriteOptions] = None) -> None: ...
+    def delete_file_in_range(self, begin: Union[str, int, float, bytes, bool],
+                             end: Union[str, int, float, bytes, bool]) -> None: ...
+    def get_approximate_sizes(self, ranges: List[Tuple[Union[str, int, float, bytes, bool],
+                                                      Union[str, int, float, bytes, bool]]]) -> List[int]: ...
+    def get_approximate_memtable_size(self, begin: Union[str, int, float, bytes, bool],
+                                      end: Union[str, int, float, bytes, bool]) -> int: ...
     def snapshot(self) -> Snapshot: ...
     def path(self) -> str: ...
     def set_options(self, options: Dict[str, str]) -> None: ...
     def property_value(self, name: str) -> Optional[str]: ...
     def property_int_value(self, name: str) -> Optional[int]: ...
+    def property_int_value_all_cf(self, name: str) -> Dict[str, Optional[int]]: ...
+    def properties(self, names: List[str]) -> Dict[str, Optional[str]]: ...
+    def compression_stats(self) -> Dict[int, float]: ...
+    def __len__(self) -> int: ...
+    def __bool__(self) -> bool: ...
+    def count_exact(self, read_opt: Optional[ReadOptions] = None) -> int: ...
+    def create_checkpoint(self, path: str, log_size_for_flush: int = 0) -> None: ...
     def latest_sequence_number(self) -> int: ...
+    def get_updates_since(self, sequence_number: int) -> WalIterator: ...
     def live_files(self) -> List[Dict[str, Any]]: ...
     def compact_range(self, begin: Union[str, int, float, bytes, bool, None],
                       end: Union[str, int, float, bytes, bool, None],
                       compact_opt: CompactOptions = CompactOptions()) -> None: ...
+    def compact(self, target_level: Optional[int] = None,
+                compact_opt: Optional[CompactOptions] = None) -> None: ...
+    def wait_for_compact(self, flush: bool = True, timeout: Optional[float] = None,
+                         abort_on_pause: bool = False) -> None: ...
+    def collapse_to_single_file(self) -> None: ...
+    def force_blob_gc(self) -> None: ...
+    def value_goes_to_blob(self, value: Any) -> bool: ...
+    def get_effective_options(self) -> Dict[str, Any]: ...
     def try_catch_up_with_primary(self) -> None: ...
     def cancel_all_background(self, wait: bool) -> None: ...
     def close(self) -> None: ...
+    def force_close(self) -> None: ...
     def __exit__(self, exc_type: Optional[Type[BaseException]], exc_val: Optional[BaseException], exc_tb: Optional[TracebackType]) -> None: ...
     def flush(self, wait: bool = True) -> None: ...
+    def flush_cfs(self, cf_names: List[str], wait: bool = True) -> None: ...
     def flush_wal(self, sync: bool = True) -> None: ...
+    def wal_size(self) -> int: ...
+    def latest_options_file(self) -> str: ...
+    def rotate_wal(self) -> None: ...
     @staticmethod
     def destroy(path: str, options: Options = Options()) -> None: ...
     @staticmethod
-    def repair(path: str, options: Options = Options()) -> None: ...
+    def repair(path: str, options: Options = Options()) -> Dict[str, Any]: ...
+    @staticmethod
+    def open_all_cfs(path: str,
+                     options: Optional[Options] = None,
+                     access_type: AccessType = AccessType.read_write()) -> Dict[str, Rdict]: ...
     @staticmethod
     def list_cf(path: str, options: Options = Options()) -> List[str]: ...
 
@@ -537,12 +693,12 @@ class RdictEntities(Iterator[Tuple[Union[str, int, float, bytes, bool], List[Tup
 class RdictIter:
     def valid(self) -> bool: ...
     def status(self) -> None: ...
-    def seek_to_first(self) -> None: ...
-    def seek_to_last(self) -> None: ...
-    def seek(self, key: Union[str, int, float, bytes, bool]) -> None: ...
-    def seek_for_prev(self, key: Union[str, int, float, bytes, bool]) -> None: ...
-    def next(self) -> None: ...
-    def prev(self) -> None: ...
+    def seek_to_first(self) -> bool: ...
+    def seek_to_last(self) -> bool: ...
+    def seek(self, key: Union[str, int, float, bytes, bool]) -> bool: ...
+    def seek_for_prev(self, key: Union[str, int, float, bytes, bool]) -> bool: ...
+    def next(self) -> bool: ...
+    def prev(self) -> bool: ...
     def key(self) -> Any: ...
     def value(self) -> Any: ...
     def columns(self) -> List[Tuple[Any, Any]]: ...
@@ -555,19 +711,24 @@ class SstFileWriter:
     def file_size(self) -> int: ...
     def __setitem__(self, key: Union[str, int, float, bytes, bool], value: Any) -> None: ...
     def __delitem__(self, key: Union[str, int, float, bytes, bool]) -> None: ...
+    def put(self, key: Union[str, int, float, bytes, bool], value: Any) -> None: ...
+    def delete(self, key: Union[str, int, float, bytes, bool]) -> None: ...
 
 class WriteBatch:
-    def __init__(self, raw_mode: bool = False) -> None: ...
+    def __init__(self, raw_mode: bool = False, normalize_str_bytes_keys: bool = False) -> None: ...
     def __len__(self) -> int: ...
     def __setitem__(self, key: Union[str, int, float, bytes, bool], value: Any) -> None: ...
     def __delitem__(self, key: Union[str, int, float, bytes, bool]) -> None: ...
     def set_dumps(self, dumps: Callable[[Any], bytes]) -> None: ...
+    def set_loads(self, loads: Callable[[bytes], Any]) -> None: ...
     def set_default_column_family(self, column_family: Optional[ColumnFamily]) -> None: ...
     def len(self) -> int: ...
     def size_in_bytes(self) -> int: ...
     def is_empty(self) -> bool: ...
     def put(self, key: Union[str, int, float, bytes, bool], value: Any,
             column_family: Optional[ColumnFamily] = None) -> None: ...
+    def merge(self, key: Union[str, int, float, bytes, bool], value: Any,
+              column_family: Optional[ColumnFamily] = None) -> None: ...
     def put_entity(self,
                    key: Union[str, int, float, bytes, bool],
                    names: List[Any],
@@ -578,10 +739,32 @@ class WriteBatch:
                      end: Union[str, int, float, bytes, bool],
                      column_family: Optional[ColumnFamily] = None) -> None: ...
     def clear(self) -> None: ...
+    def set_save_point(self) -> None: ...
+    def rollback_to_save_point(self) -> None: ...
+    def pop_save_point(self) -> None: ...
+    def operations(self) -> List[Tuple[str, int, Any, Any]]: ...
+    def iterate(self, handler: Any) -> None: ...
 
 
+class WriteBatchWithIndex:
+    def __init__(self, raw_mode: bool = False, normalize_str_bytes_keys: bool = False) -> None: ...
+    def set_dumps(self, dumps: Callable[[Any], bytes]) -> None: ...
+    def set_loads(self, loads: Callable[[bytes], Any]) -> None: ...
+    def __len__(self) -> int: ...
+    def __setitem__(self, key: Union[str, int, float, bytes, bool], value: Any) -> None: ...
+    def __delitem__(self, key: Union[str, int, float, bytes, bool]) -> None: ...
+    def put(self, key: Union[str, int, float, bytes, bool], value: Any) -> None: ...
+    def delete(self, key: Union[str, int, float, bytes, bool]) -> None: ...
+    def get_from_batch(self, key: Union[str, int, float, bytes, bool], default: Any = None) -> Any: ...
+    def get_from_batch_and_db(self, db: Rdict, key: Union[str, int, float, bytes, bool], default: Any = None,
+                              read_opt: Optional[ReadOptions] = None) -> Any: ...
+    def staged_entries(self) -> List[Tuple[str, Any, Any]]: ...
+    def clear(self) -> None: ...
+
 class Snapshot:
     def __getitem__(self, key: Union[str, int, float, bytes, bool]) -> Any: ...
+    def get_batch(self, keys: List[Union[str, int, float, bytes, bool]],
+                  default: Any = None) -> List[Any]: ...
     def iter(self, read_opt: Optional[ReadOptions] = None) -> RdictIter: ...
     def items(self, backwards: bool = False,
               from_key: Union[str, int, float, bytes, bool, None] = None,
@@ -611,7 +794,53 @@ class KeyEncodingType:
 
 class Checkpoint:
     def __init__(self, db: Rdict) -> None: ...
-    def create_checkpoint(self, path: str) -> None: ...
+    def create_checkpoint(self, path: str, log_size_for_flush: int = 0) -> None: ...
+
+class BackupEngine:
+    def __init__(self, backup_dir: str) -> None: ...
+    def create_new_backup(self, db: Rdict, flush_before_backup: bool = True) -> None: ...
+    def restore_from_latest_backup(self, db_dir: str, wal_dir: Optional[str] = None,
+                                   keep_log_files: bool = False) -> None: ...
+    def get_backup_info(self) -> List[Dict[str, int]]: ...
+    def purge_old_backups(self, num_backups_to_keep: int) -> None: ...
+
+class PinnableSlice:
+    def __len__(self) -> int: ...
+    def __buffer__(self, flags: int) -> memoryview: ...
+
+class WalIterator(Iterator[Tuple[int, WriteBatch]]):
+    def __iter__(self) -> WalIterator: ...
+    def __next__(self) -> Tuple[int, WriteBatch]: ...
+
+class OptimisticTransactionDB:
+    def __init__(self, path: str, options: Optional[Options] = None) -> None: ...
+    def transaction(self, set_snapshot: bool = False) -> Transaction: ...
+    def get(self, key: Union[str, int, float, bytes, bool], default: Any = None) -> Any: ...
+    def put(self, key: Union[str, int, float, bytes, bool], value: Any) -> None: ...
+    def delete(self, key: Union[str, int, float, bytes, bool]) -> None: ...
+    def __getitem__(self, key: Union[str, int, float, bytes, bool]) -> Any: ...
+    def __setitem__(self, key: Union[str, int, float, bytes, bool], value: Any) -> None: ...
+    def __delitem__(self, key: Union[str, int, float, bytes, bool]) -> None: ...
+    def close(self) -> None: ...
 
-class DbClosedError(Exception):
+class Transaction:
+    def get(self, key: Union[str, int, float, bytes, bool], default: Any = None) -> Any: ...
+    def get_for_update(self, key: Union[str, int, float, bytes, bool], default: Any = None) -> Any: ...
+    def put(self, key: Union[str, int, float, bytes, bool], value: Any) -> None: ...
+    def delete(self, key: Union[str, int, float, bytes, bool]) -> None: ...
+    def __getitem__(self, key: Union[str, int, float, bytes, bool]) -> Any: ...
+    def __setitem__(self, key: Union[str, int, float, bytes, bool], value: Any) -> None: ...
+    def __delitem__(self, key: Union[str, int, float, bytes, bool]) -> None: ...
+    def commit(self) -> None: ...
+    def rollback(self) -> None: ...
+    def set_savepoint(self) -> None: ...
+    def rollback_to_savepoint(self) -> None: ...
+
+class RocksDBError(Exception):
+    """Raised when RocksDB returns an error, e.g. an I/O error or a corruption."""
+
+class DbClosedError(RocksDBError):
     """Raised when accessing a closed database instance."""
+
+class KeyNotFoundError(KeyError):
+    """Raised when a key is not found. It is a subclass of `KeyError`."""
@@ -0,0 +1,173 @@
+use crate::exceptions::rocksdb_error;
+use crate::{Rdict, RocksDictConfig};
+use pyo3::{exceptions::PyException, prelude::*, types::PyDict};
+use rocksdb::backup::{BackupEngine, BackupEngineOptions, RestoreOptions};
+use rocksdb::Env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Database's backup engine.
+/// Used to create incremental backups of a DB, and to restore them.
+///
+/// Backups keep the `rocksdict` config of the DB (raw mode, prefix
+/// extractors, etc.), which is written back when restoring.
+///
+/// Example:
+///     ::
+///
+///         from rocksdict import Rdict, BackupEngine
+///
+///         db = Rdict("./db_path")
+///         db["key"] = "value"
+///         engine = BackupEngine("./backup_path")
+///         engine.create_new_backup(db)
+///         db.close()
+///         Rdict.destroy("./db_path")
+///
+///         engine.restore_from_latest_backup("./db_path")
+///         db = Rdict("./db_path")
+///         assert db["key"] == "value"
+#[pyclass(name = "BackupEngine")]
+pub(crate) struct BackupEnginePy {
+    inner: BackupEngine,
+    backup_dir: PathBuf,
+}
+
+#[pymethods]
+impl BackupEnginePy {
+    /// Opens a backup engine storing its backups in `backup_dir`.
+    ///
+    /// Args:
+    ///     backup_dir: directory of the backups, created if missing.
+    #[new]
+    #[pyo3(signature = (backup_dir))]
+    pub fn new(backup_dir: &str) -> PyResult<Self> {
+        let opts = BackupEngineOptions::new(backup_dir).map_err(rocksdb_error)?;
+        let env = Env::new().map_err(rocksdb_error)?;
+        let inner = BackupEngine::open(&opts, &env).map_err(rocksdb_error)?;
+        Ok(Self {
+            inner,
+            backup_dir: PathBuf::from(backup_dir),
+        })
+    }
+
+    /// Captures the state of the database in a new backup.
+    ///
+    /// Args:
+    ///     db: the database to back up.
+    ///     flush_before_backup: flush the memtables first, so that the
+    ///         backup does not depend on WAL files.
+    #[pyo3(signature = (db, flush_before_backup = true))]
+    pub fn create_new_backup(
+        &mut self,
+        db: &Rdict,
+        flush_before_backup: bool,
+        py: Python,
+    ) -> PyResult<()> {
+        let db_ref = db.get_db()?;
+        let inner = &mut self.inner;
+        py.allow_threads(|| inner.create_new_backup_flush(&**db_ref, flush_before_backup))
+            .map_err(rocksdb_error)?;
+        if let Some(backup_id) = self.latest_backup_id() {
+            db.config().save(self.config_path(backup_id))?;
+        }
+        Ok(())
+    }
+
+    /// Restores the latest backup into `db_dir`.
+    ///
+    /// The database must not be open.
+    ///
+    /// Args:
+    ///     db_dir: the directory of the restored database.
+    ///     wal_dir: the directory of the WAL files, `db_dir` by default.
+    ///     keep_log_files: keep the WAL files already in `wal_dir`,
+    ///         to recover writes made after the backup.
+    #[pyo3(signature = (db_dir, wal_dir = None, keep_log_files = false))]
+    pub fn restore_from_latest_backup(
+        &mut self,
+        db_dir: &str,
+        wal_dir: Option<&str>,
+        keep_log_files: bool,
+        py: Python,
+    ) -> PyResult<()> {
+        let backup_id = self
+            .latest_backup_id()
+            .ok_or_else(|| PyException::new_err("no backup to restore"))?;
+        let wal_dir = wal_dir.unwrap_or(db_dir);
+        let inner = &mut self.inner;
+        py.allow_threads(|| {
+            let mut restore_opts = RestoreOptions::default();
+            restore_opts.set_keep_log_files(keep_log_files);
+            inner.restore_from_latest_backup(db_dir, wal_dir, &restore_opts)
+        })
+        .map_err(rocksdb_error)?;
+        // backups created outside of rocksdict have no config
+        if let Ok(config) = RocksDictConfig::load(self.config_path(backup_id)) {
+            config.save_to_dir(db_dir)?;
+        }
+        Ok(())
+    }
+
+    /// Returns a list of the backups, as dicts with keys
+    /// `backup_id`, `timestamp`, `size` and `num_files`.
+    pub fn get_backup_info<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.inner
+            .get_backup_info()
+            .into_iter()
+            .map(|info| {
+                let result = PyDict::new(py);
+                result.set_item("backup_id", info.backup_id)?;
+                result.set_item("timestamp", info.timestamp)?;
+                result.set_item("size", info.size)?;
+                result.set_item("num_files", info.num_files)?;
+                Ok(result)
+            })
+            .collect()
+    }
+
+    /// Deletes all backups but the latest `num_backups_to_keep`.
+    pub fn purge_old_backups(&mut self, num_backups_to_keep: usize) -> PyResult<()> {
+        self.inner
+            .purge_old_backups(num_backups_to_keep)
+            .map_err(rocksdb_error)?;
+        let kept = self
+            .inner
+            .get_backup_info()
+            .iter()
+            .map(|info| self.config_path(info.backup_id))
+            .collect::<Vec<_>>();
+        for entry in fs::read_dir(&self.backup_dir)?.flatten() {
+            let path = entry.path();
+            if is_config_file(&path) && !kept.contains(&path) {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BackupEnginePy {
+    fn latest_backup_id(&self) -> Option<u32> {
+        self.inner
+            .get_backup_info()
+            .iter()
+            .map(|info| info.backup_id)
+            .max()
+    }
+
+    /// rocksdict config of a backup, next to the `private` and `shared` directories
+    fn config_path(&self, backup_id: u32) -> PathBuf {
+        self.backup_dir
+            .join(format!("rocksdict-config-{backup_id}.json"))
+    }
+}
+
+fn is_config_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("rocksdict-config-") && name.ends_with(".json"))
+}
+
+// the backup engine is thread-safe, and is only mutated through `&mut self`
+unsafe impl Sync for BackupEnginePy {}
@@ -5,6 +5,7 @@ use crate::{
     Rdict, RocksDictConfig,
 };
 use pyo3::{exceptions::PyException, prelude::*};
+use std::path::Path;
 
 /// Database's checkpoint object.
 /// Used to create checkpoints of the specified DB from time to time.
@@ -51,18 +52,28 @@ impl CheckpointPy {
     }
 
     /// Creates new physical DB checkpoint in directory specified by `path`.
-    #[pyo3(signature = (path))]
-    pub fn create_checkpoint(&self, path: &str) -> PyResult<()> {
+    ///
+    /// SST files are hard-linked when possible, so checkpoints are cheap.
+    ///
+    /// Args:
+    ///     path: directory of the checkpoint, which must not exist.
+    ///     log_size_for_flush: the memtables are flushed first if the total
+    ///         WAL size is at least this many bytes (always with the default 0),
+    ///         otherwise WAL files are copied into the checkpoint.
+    #[pyo3(signature = (path, log_size_for_flush = 0))]
+    pub fn create_checkpoint(&self, path: &str, log_size_for_flush: u64) -> PyResult<()> {
+        if Path::new(path).exists() {
+            return Err(PyException::new_err(format!(
+                "checkpoint directory `{path}` already exists"
+            )));
+        }
         let cpath = to_cpath(path)?;
 
-        /// Undocumented parameter for `ffi::rocksdb_checkpoint_create` function. Zero by default.
-        const LOG_SIZE_FOR_FLUSH: u64 = 0_u64;
-
         unsafe {
             ffi_try!(librocksdb_sys::rocksdb_checkpoint_create(
                 self.inner,
                 cpath.as_ptr(),
-                LOG_SIZE_FOR_FLUSH,
+                log_size_for_flush,
             ));
         }
 
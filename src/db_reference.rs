@@ -1,4 +1,5 @@
 use rocksdb::{DBWithThreadMode, MultiThreaded};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 /// The type of a reference to a [rocksdb::DB] that is passed around the library.
@@ -11,16 +12,29 @@ pub(crate) type DbReference = Arc<DBWithThreadMode<MultiThreaded>>;
 #[derive(Clone)]
 pub(crate) struct DbReferenceHolder {
     inner: Option<DbReference>,
+    /// Shared by all clones, set by [DbReferenceHolder::force_close].
+    force_closed: Arc<AtomicBool>,
 }
 
 impl DbReferenceHolder {
     pub fn new(db: DBWithThreadMode<MultiThreaded>) -> Self {
         Self {
             inner: Some(Arc::new(db)),
+            force_closed: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Returns `None` if this reference is closed, or if any clone was force closed.
     pub fn get(&self) -> Option<&DbReference> {
+        if self.force_closed.load(Ordering::Acquire) {
+            return None;
+        }
+        self.inner.as_ref()
+    }
+
+    /// Returns the reference even if the DB was force closed,
+    /// for releasing resources (e.g. snapshots) that belong to the DB.
+    pub fn get_unchecked(&self) -> Option<&DbReference> {
         self.inner.as_ref()
     }
 
@@ -29,6 +43,15 @@ impl DbReferenceHolder {
             db.cancel_all_background_work(true);
         }
     }
+
+    /// Invalidates all clones of this reference and cancels all background work,
+    /// even if other clones are still alive.
+    pub fn force_close(&mut self) {
+        self.force_closed.store(true, Ordering::Release);
+        if let Some(db) = self.inner.take() {
+            db.cancel_all_background_work(true);
+        }
+    }
 }
 
 impl Drop for DbReferenceHolder {
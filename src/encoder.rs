@@ -1,8 +1,14 @@
+use crate::exceptions::RocksDBError;
 use num_bigint::BigInt;
 use pyo3::exceptions::{PyException, PyKeyError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyBool, PyBytes, PyFloat, PyInt, PyString};
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{
+    timezone_utc, PyBool, PyBytes, PyDate, PyDateTime, PyDelta, PyDeltaAccess, PyFloat, PyInt,
+    PyString, PyTuple, PyType,
+};
 use std::borrow::Cow;
+use std::cmp::Ordering;
 
 pub(crate) enum ValueTypes<'a, 'b> {
     Bytes(&'a [u8]),
@@ -10,6 +16,17 @@ pub(crate) enum ValueTypes<'a, 'b> {
     Int(BigInt),
     Float(f64),
     Bool(bool),
+    /// timezone-aware datetime, as microseconds since the unix epoch
+    DateTime(i64),
+    /// date, as days since 0001-01-01 (`date.toordinal()`)
+    Date(u32),
+    /// timedelta, as normalized `(days, seconds, microseconds)`
+    TimeDelta(i32, i32, i32),
+    /// uuid.UUID, as its 128-bit integer
+    Uuid(u128),
+    /// decimal.Decimal, as its exact string as a value,
+    /// and in an order-preserving form as a key
+    Decimal(&'a Bound<'b, PyAny>),
     Any(&'a Bound<'b, PyAny>),
 }
 
@@ -22,11 +39,20 @@ pub(crate) fn encoding_byte(v_type: &ValueTypes) -> u8 {
         ValueTypes::Float(_) => 4,
         ValueTypes::Bool(_) => 5,
         ValueTypes::Any(_) => 6,
+        ValueTypes::DateTime(_) => 8,
+        ValueTypes::Date(_) => 9,
+        ValueTypes::TimeDelta(..) => 10,
+        ValueTypes::Uuid(_) => 11,
+        ValueTypes::Decimal(_) => 12,
     }
 }
 
 #[inline(always)]
-pub(crate) fn encode_key<'a>(key: &'a Bound<PyAny>, raw_mode: bool) -> PyResult<Cow<'a, [u8]>> {
+pub(crate) fn encode_key<'a>(
+    key: &'a Bound<PyAny>,
+    raw_mode: bool,
+    normalize_str_bytes_keys: bool,
+) -> PyResult<Cow<'a, [u8]>> {
     if raw_mode {
         return if let Ok(value) = key.downcast::<PyBytes>() {
             Ok(Cow::Borrowed(value.as_bytes()))
@@ -34,11 +60,24 @@ pub(crate) fn encode_key<'a>(key: &'a Bound<PyAny>, raw_mode: bool) -> PyResult<
             Err(PyKeyError::new_err("raw mode only support bytes"))
         };
     }
+    if let Ok(tuple) = key.downcast::<PyTuple>() {
+        return Ok(Cow::Owned(encode_tuple_key(
+            tuple,
+            normalize_str_bytes_keys,
+        )?));
+    }
     let bytes = py_to_value_types(key)?;
-    let type_encoding = encoding_byte(&bytes);
+    let type_encoding = match bytes {
+        // encode str keys as their utf-8 bytes
+        ValueTypes::String(_) if normalize_str_bytes_keys => 1,
+        ValueTypes::Decimal(_) => DECIMAL_KEY,
+        _ => encoding_byte(&bytes),
+    };
     let owned_bytes = match bytes {
         ValueTypes::Bytes(value) => Ok(concat_type_encoding(type_encoding, value)),
         ValueTypes::String(value) => Ok(concat_type_encoding(type_encoding, value.as_bytes())),
+        // not order-preserving as bytes, the "rocksdict" comparator
+        // (`compare_keys`) orders int keys numerically
         ValueTypes::Int(value) => Ok(concat_type_encoding(
             type_encoding,
             &value.to_signed_bytes_be()[..],
@@ -51,13 +90,142 @@ pub(crate) fn encode_key<'a>(key: &'a Bound<PyAny>, raw_mode: bool) -> PyResult<
             type_encoding,
             if value { &[1u8] } else { &[0u8] },
         )),
+        ValueTypes::DateTime(_) | ValueTypes::Date(_) | ValueTypes::TimeDelta(..) => {
+            Ok(concat_type_encoding(type_encoding, &time_payload(&bytes)))
+        }
+        ValueTypes::Uuid(value) => Ok(concat_type_encoding(type_encoding, &value.to_be_bytes())),
+        ValueTypes::Decimal(value) => Ok(concat_type_encoding(
+            type_encoding,
+            &decimal_key_payload(value)?,
+        )),
         ValueTypes::Any(_) => Err(PyException::new_err(
-            "Only support `string`, `int`, `float`, `bool`, and `bytes` as keys",
+            "Only support `string`, `int`, `float`, `bool`, `bytes`, timezone-aware `datetime`, \
+             `date`, `timedelta`, `UUID`, `Decimal`, and `tuple` of them as keys",
         )),
     }?;
     Ok(Cow::Owned(owned_bytes))
 }
 
+///
+/// Encode a tuple key (type byte 7).
+///
+/// Each component is encoded as a key (with its own type byte),
+/// with every `0x00` byte escaped as `0x00 0xFF`,
+/// and terminated by `0x00 0x00`.
+/// Components thus never collide across boundaries
+/// (`("a", "b")` vs `("ab",)`), and all tuples starting
+/// with the same components share the same byte prefix.
+///
+fn encode_tuple_key(tuple: &Bound<PyTuple>, normalize_str_bytes_keys: bool) -> PyResult<Vec<u8>> {
+    let mut output = vec![7u8];
+    for item in tuple.iter() {
+        for &byte in encode_key(&item, false, normalize_str_bytes_keys)?.iter() {
+            output.push(byte);
+            if byte == 0 {
+                output.push(0xFF);
+            }
+        }
+        output.extend_from_slice(&[0, 0]);
+    }
+    Ok(output)
+}
+
+/// Split the payload of a tuple key (without type byte) into unescaped components.
+///
+/// Returns `None` if the payload is not a valid tuple encoding.
+fn split_tuple_key(payload: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let mut components = Vec::new();
+    let mut current = Vec::new();
+    let mut i = 0;
+    while i < payload.len() {
+        match (payload[i], payload.get(i + 1)) {
+            (0, Some(0xFF)) => current.push(0),
+            (0, Some(0)) => components.push(std::mem::take(&mut current)),
+            (0, _) => return None,
+            (byte, _) => {
+                current.push(byte);
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+    if current.is_empty() {
+        Some(components)
+    } else {
+        None
+    }
+}
+
+/// The ordering of the "rocksdict" comparator used in non-raw mode.
+///
+/// Integers are compared numerically, tuples component by component
+/// (a tuple is less than the tuples it is a prefix of),
+/// and all other keys bytewise.
+pub(crate) fn compare_keys(v1: &[u8], v2: &[u8]) -> Ordering {
+    match (v1.first(), v2.first()) {
+        (Some(3), Some(3)) => {
+            BigInt::from_signed_bytes_be(&v1[1..]).cmp(&BigInt::from_signed_bytes_be(&v2[1..]))
+        }
+        (Some(7), Some(7)) => match (split_tuple_key(&v1[1..]), split_tuple_key(&v2[1..])) {
+            (Some(c1), Some(c2)) => {
+                for (a, b) in c1.iter().zip(c2.iter()) {
+                    match compare_keys(a, b) {
+                        Ordering::Equal => continue,
+                        ordering => return ordering,
+                    }
+                }
+                c1.len().cmp(&c2.len())
+            }
+            _ => v1.cmp(v2),
+        },
+        _ => v1.cmp(v2),
+    }
+}
+
+/// Size of the user-defined timestamps of `Options.enable_user_timestamp`.
+pub(crate) const U64_TS_SIZE: usize = 8;
+
+/// Timestamps are encoded little-endian, as in RocksDB's
+/// `BytewiseComparatorWithU64Ts`.
+pub(crate) fn encode_u64_ts(ts: u64) -> [u8; U64_TS_SIZE] {
+    ts.to_le_bytes()
+}
+
+fn decode_u64_ts(ts: &[u8]) -> u64 {
+    let mut bytes = [0u8; U64_TS_SIZE];
+    bytes.copy_from_slice(&ts[..U64_TS_SIZE]);
+    u64::from_le_bytes(bytes)
+}
+
+fn strip_u64_ts(key: &[u8], has_ts: bool) -> &[u8] {
+    if has_ts {
+        &key[..key.len() - U64_TS_SIZE]
+    } else {
+        key
+    }
+}
+
+pub(crate) fn compare_u64_ts(ts1: &[u8], ts2: &[u8]) -> Ordering {
+    decode_u64_ts(ts1).cmp(&decode_u64_ts(ts2))
+}
+
+pub(crate) fn compare_keys_without_u64_ts(
+    v1: &[u8],
+    v1_has_ts: bool,
+    v2: &[u8],
+    v2_has_ts: bool,
+) -> Ordering {
+    strip_u64_ts(v1, v1_has_ts).cmp(strip_u64_ts(v2, v2_has_ts))
+}
+
+/// Keys with a timestamp are ordered bytewise, then from the newest
+/// to the oldest timestamp.
+pub(crate) fn compare_keys_with_u64_ts(v1: &[u8], v2: &[u8]) -> Ordering {
+    compare_keys_without_u64_ts(v1, true, v2, true)
+        .then_with(|| compare_u64_ts(&v2[v2.len() - U64_TS_SIZE..], &v1[v1.len() - U64_TS_SIZE..]))
+}
+
 ///
 /// Convert string, int, float, bytes to byte encodings.
 ///
@@ -90,9 +258,18 @@ pub(crate) fn encode_value<'a>(
             ValueTypes::Bool(value) => {
                 concat_type_encoding(type_encoding, if value { &[1u8] } else { &[0u8] })
             }
+            ValueTypes::DateTime(_) | ValueTypes::Date(_) | ValueTypes::TimeDelta(..) => {
+                concat_type_encoding(type_encoding, &time_payload(&bytes))
+            }
+            ValueTypes::Uuid(value) => concat_type_encoding(type_encoding, &value.to_be_bytes()),
+            ValueTypes::Decimal(value) => {
+                concat_type_encoding(type_encoding, value.str()?.to_str()?.as_bytes())
+            }
             ValueTypes::Any(value) => {
                 let py = value.py();
-                let pickle_bytes = dumps.call1(py, (value,))?;
+                let pickle_bytes = dumps.call1(py, (value,)).map_err(|e| {
+                    PyValueError::new_err(format!("cannot serialize {}: {e}", value.get_type()))
+                })?;
                 let bytes: &[u8] = pickle_bytes.downcast_bound::<PyBytes>(py)?.as_bytes();
                 concat_type_encoding(type_encoding, bytes)
             }
@@ -118,9 +295,170 @@ fn py_to_value_types<'a, 'b>(value: &'a Bound<'b, PyAny>) -> PyResult<ValueTypes
     if let Ok(value) = value.downcast::<PyFloat>() {
         return Ok(ValueTypes::Float(value.value()));
     }
+    // exact types only: subclasses (e.g. `pandas.Timestamp`) are pickled
+    // so that they round-trip unchanged
+    if let Ok(datetime) = value.downcast_exact::<PyDateTime>() {
+        // naive datetimes are pickled, as they have no well-defined instant
+        if datetime.call_method0("utcoffset")?.is_none() {
+            return Ok(ValueTypes::Any(value));
+        }
+        let delta = datetime.call_method1("__sub__", (unix_epoch(value.py())?,))?;
+        let delta = delta.downcast::<PyDelta>()?;
+        return Ok(ValueTypes::DateTime(
+            delta.get_days() as i64 * MICROS_PER_DAY
+                + delta.get_seconds() as i64 * MICROS_PER_SECOND
+                + delta.get_microseconds() as i64,
+        ));
+    }
+    if let Ok(date) = value.downcast_exact::<PyDate>() {
+        return Ok(ValueTypes::Date(date.call_method0("toordinal")?.extract()?));
+    }
+    if let Ok(delta) = value.downcast_exact::<PyDelta>() {
+        return Ok(ValueTypes::TimeDelta(
+            delta.get_days(),
+            delta.get_seconds(),
+            delta.get_microseconds(),
+        ));
+    }
+    let py = value.py();
+    if value.get_type().is(uuid_type(py)?) {
+        return Ok(ValueTypes::Uuid(value.getattr("int")?.extract()?));
+    }
+    if value.get_type().is(decimal_type(py)?) {
+        return Ok(ValueTypes::Decimal(value));
+    }
     Ok(ValueTypes::Any(value))
 }
 
+static UUID_TYPE: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static DECIMAL_TYPE: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+fn uuid_type(py: Python) -> PyResult<&Bound<PyType>> {
+    UUID_TYPE.import(py, "uuid", "UUID")
+}
+
+fn decimal_type(py: Python) -> PyResult<&Bound<PyType>> {
+    DECIMAL_TYPE.import(py, "decimal", "Decimal")
+}
+
+/// type byte of `Decimal` keys, `Decimal` values being stored
+/// as their exact string (type byte 12)
+pub(crate) const DECIMAL_KEY: u8 = 14;
+
+///
+/// Order-preserving payload of a `Decimal` key.
+///
+/// A class byte (negative infinity, negative, zero, positive, infinity),
+/// then for finite non-zero numbers `0.d1...dn * 10^e` with `d1` and `dn` non-zero,
+/// the exponent `e` (big-endian, with the sign bit flipped) and the digits,
+/// all inverted and terminated by `10` for negative numbers.
+/// Equal numbers, such as `Decimal("1.0")` and `Decimal("1.00")`,
+/// thus have the same encoding.
+///
+fn decimal_key_payload(value: &Bound<PyAny>) -> PyResult<Vec<u8>> {
+    if value.call_method0("is_nan")?.is_truthy()? {
+        return Err(PyValueError::new_err("Decimal NaN cannot be used as a key"));
+    }
+    let (sign, digits, exponent): (u8, Vec<u8>, Bound<PyAny>) =
+        value.call_method0("as_tuple")?.extract()?;
+    let negative = sign == 1;
+    if value.call_method0("is_infinite")?.is_truthy()? {
+        return Ok(vec![if negative { 0 } else { 4 }]);
+    }
+    let (first, last) = match (
+        digits.iter().position(|&d| d != 0),
+        digits.iter().rposition(|&d| d != 0),
+    ) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Ok(vec![2]),
+    };
+    let exponent = exponent.extract::<i64>()? + (digits.len() - first) as i64;
+    let exponent = (exponent as u64) ^ (1 << 63);
+    let mut payload = Vec::with_capacity(last - first + 10);
+    if negative {
+        payload.push(1);
+        payload.extend_from_slice(&(!exponent).to_be_bytes());
+        payload.extend(digits[first..=last].iter().map(|d| 9 - d));
+        payload.push(10);
+    } else {
+        payload.push(3);
+        payload.extend_from_slice(&exponent.to_be_bytes());
+        payload.extend_from_slice(&digits[first..=last]);
+    }
+    Ok(payload)
+}
+
+fn decode_decimal_key<'py>(py: Python<'py>, payload: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+    let corrupted = || RocksDBError::new_err("corrupted value: invalid Decimal key");
+    let (negative, exponent, digits) = match payload {
+        [0] => return decimal_type(py)?.call1(("-Infinity",)),
+        [2] => return decimal_type(py)?.call1((0,)),
+        [4] => return decimal_type(py)?.call1(("Infinity",)),
+        [1, rest @ .., 10] if rest.len() > 8 => {
+            let exponent: [u8; 8] = fixed_payload(&rest[..8])?;
+            (true, !u64::from_be_bytes(exponent), &rest[8..])
+        }
+        [3, rest @ ..] if rest.len() > 8 => {
+            let exponent: [u8; 8] = fixed_payload(&rest[..8])?;
+            (false, u64::from_be_bytes(exponent), &rest[8..])
+        }
+        _ => return Err(corrupted()),
+    };
+    if digits.iter().any(|&d| d > 9) {
+        return Err(corrupted());
+    }
+    let digits = digits
+        .iter()
+        .map(|&d| if negative { 9 - d } else { d })
+        .collect::<Vec<_>>();
+    let exponent = ((exponent ^ (1 << 63)) as i64) - digits.len() as i64;
+    let digits = PyTuple::new(py, digits)?;
+    decimal_type(py)?.call1(((negative as u8, digits, exponent),))
+}
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const MICROS_PER_DAY: i64 = 86_400 * MICROS_PER_SECOND;
+
+fn unix_epoch(py: Python) -> PyResult<Bound<PyDateTime>> {
+    PyDateTime::new(py, 1970, 1, 1, 0, 0, 0, 0, Some(&timezone_utc(py)))
+}
+
+///
+/// Big-endian payloads of datetime, date and timedelta,
+/// with the sign bit of signed integers flipped,
+/// so that their bytewise ordering is chronological.
+///
+fn time_payload(value: &ValueTypes) -> Vec<u8> {
+    match value {
+        ValueTypes::DateTime(micros) => ((*micros as u64) ^ (1 << 63)).to_be_bytes().to_vec(),
+        ValueTypes::Date(ordinal) => ordinal.to_be_bytes().to_vec(),
+        ValueTypes::TimeDelta(days, seconds, micros) => {
+            let mut payload = Vec::with_capacity(12);
+            payload.extend_from_slice(&((*days as u32) ^ (1 << 31)).to_be_bytes());
+            payload.extend_from_slice(&(*seconds as u32).to_be_bytes());
+            payload.extend_from_slice(&(*micros as u32).to_be_bytes());
+            payload
+        }
+        _ => unreachable!("not a time value"),
+    }
+}
+
+/// The fixed-size payload of a value, which must be exactly `N` bytes long.
+#[inline(always)]
+fn fixed_payload<const N: usize>(bytes: &[u8]) -> PyResult<[u8; N]> {
+    bytes.try_into().map_err(|_| {
+        RocksDBError::new_err(format!(
+            "corrupted value: expected a payload of {N} bytes, got {}",
+            bytes.len()
+        ))
+    })
+}
+
+#[inline(always)]
+fn be_u32(bytes: &[u8]) -> PyResult<u32> {
+    Ok(u32::from_be_bytes(fixed_payload(bytes)?))
+}
+
 /// this function is used for decoding value from bytes
 #[inline(always)]
 pub(crate) fn decode_value<'py>(
@@ -150,7 +488,7 @@ pub(crate) fn decode_value<'py>(
                 Ok(big_int.into_pyobject(py)?.into_any())
             }
             4 => {
-                let float: f64 = f64::from_be_bytes(bytes[1..].try_into().unwrap());
+                let float: f64 = f64::from_be_bytes(fixed_payload(&bytes[1..])?);
                 Ok(float.into_pyobject(py)?.into_any())
             }
             5 => Ok(PyBool::new(py, bytes[1] != 0).to_owned().into_any()),
@@ -158,11 +496,81 @@ pub(crate) fn decode_value<'py>(
                 .call1(py, (PyBytes::new(py, &bytes[1..]),))?
                 .bind(py)
                 .to_owned()),
+            7 => {
+                let components = split_tuple_key(&bytes[1..])
+                    .ok_or_else(|| PyException::new_err("invalid tuple encoding"))?;
+                let items = components
+                    .iter()
+                    .map(|c| decode_value(py, c, loads, false))
+                    .collect::<PyResult<Vec<_>>>()?;
+                Ok(PyTuple::new(py, items)?.into_any())
+            }
+            8 => {
+                let micros = (u64::from_be_bytes(fixed_payload(&bytes[1..])?) ^ (1 << 63)) as i64;
+                let delta = PyDelta::new(
+                    py,
+                    micros.div_euclid(MICROS_PER_DAY) as i32,
+                    (micros.rem_euclid(MICROS_PER_DAY) / MICROS_PER_SECOND) as i32,
+                    micros.rem_euclid(MICROS_PER_SECOND) as i32,
+                    false,
+                )?;
+                unix_epoch(py)?.call_method1("__add__", (delta,))
+            }
+            9 => py
+                .get_type::<PyDate>()
+                .call_method1("fromordinal", (be_u32(&bytes[1..])?,)),
+            10 => {
+                let payload: [u8; 12] = fixed_payload(&bytes[1..])?;
+                Ok(PyDelta::new(
+                    py,
+                    (be_u32(&payload[0..4])? ^ (1 << 31)) as i32,
+                    be_u32(&payload[4..8])? as i32,
+                    be_u32(&payload[8..12])? as i32,
+                    false,
+                )?
+                .into_any())
+            }
+            11 => uuid_type(py)?.call1((py.None(), PyBytes::new(py, &bytes[1..]))),
+            12 => {
+                let string = std::str::from_utf8(&bytes[1..])
+                    .map_err(|_| PyException::new_err("utf-8 decoding error"))?;
+                decimal_type(py)?.call1((string,))
+            }
+            DECIMAL_KEY => decode_decimal_key(py, &bytes[1..]),
+            EXPIRING_VALUE => match bytes.get(9..) {
+                Some(value) => decode_value(py, value, loads, false),
+                None => Err(RocksDBError::new_err(
+                    "corrupted value: expiring value without expiry time",
+                )),
+            },
             _ => Err(PyException::new_err("Unknown value type")),
         },
     }
 }
 
+/// type byte of a value written by `Rdict.put_with_ttl`,
+/// followed by its expiry time and by the encoded value
+pub(crate) const EXPIRING_VALUE: u8 = 13;
+
+/// Wraps an encoded value with its expiry time, in seconds since the unix epoch.
+pub(crate) fn encode_expiring_value(expire_at: u64, value: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(value.len() + 9);
+    output.push(EXPIRING_VALUE);
+    output.extend_from_slice(&expire_at.to_be_bytes());
+    output.extend_from_slice(value);
+    output
+}
+
+/// The expiry time of a value written by `Rdict.put_with_ttl`.
+pub(crate) fn value_expire_at(bytes: &[u8]) -> Option<u64> {
+    match bytes.first() {
+        Some(&EXPIRING_VALUE) if bytes.len() >= 9 => {
+            Some(u64::from_be_bytes(bytes[1..9].try_into().unwrap()))
+        }
+        _ => None,
+    }
+}
+
 #[inline(always)]
 fn concat_type_encoding(encoding: u8, payload: &[u8]) -> Vec<u8> {
     let mut output = Vec::with_capacity(payload.len() + 1);
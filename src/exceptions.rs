@@ -1,9 +1,29 @@
 use pyo3::create_exception;
-use pyo3::exceptions::PyException;
+use pyo3::exceptions::{PyException, PyKeyError};
+use pyo3::PyErr;
 
 create_exception!(
     rocksdict,
-    DbClosedError,
+    RocksDBError,
     PyException,
+    "Raised when RocksDB returns an error, e.g. an I/O error or a corruption."
+);
+
+create_exception!(
+    rocksdict,
+    DbClosedError,
+    RocksDBError,
     "Raised when accessing a closed database instance."
 );
+
+create_exception!(
+    rocksdict,
+    KeyNotFoundError,
+    PyKeyError,
+    "Raised when a key is not found. It is a subclass of `KeyError`."
+);
+
+/// Converts a RocksDB error into a `RocksDBError`.
+pub(crate) fn rocksdb_error(e: rocksdb::Error) -> PyErr {
+    RocksDBError::new_err(e.into_string())
+}
@@ -1,11 +1,10 @@
 use crate::db_reference::DbReferenceHolder;
 use crate::encoder::{decode_value, encode_key};
-use crate::exceptions::DbClosedError;
+use crate::exceptions::{DbClosedError, RocksDBError};
 use crate::util::error_message;
 use crate::{ReadOpt, ReadOptionsPy};
 use core::slice;
 use libc::{c_char, c_uchar, size_t};
-use pyo3::exceptions::PyException;
 use pyo3::prelude::*;
 use pyo3::types::{PyList, PyTuple};
 use rocksdb::{AsColumnFamilyRef, Iterable as _, UnboundColumnFamily};
@@ -29,6 +28,11 @@ pub(crate) struct RdictIter {
     pub(crate) loads: PyObject,
 
     pub(crate) raw_mode: bool,
+
+    pub(crate) normalize_str_bytes_keys: bool,
+
+    /// if set, the iterator is invalidated at the first key without this prefix
+    pub(crate) prefix: Option<Vec<u8>>,
 }
 
 #[pyclass]
@@ -68,10 +72,33 @@ impl RdictIter {
         readopts: ReadOptionsPy,
         pickle_loads: &PyObject,
         raw_mode: bool,
+        normalize_str_bytes_keys: bool,
         py: Python,
     ) -> PyResult<Self> {
-        let readopts = readopts.to_read_opt(raw_mode, py)?;
+        if let Some(db) = db.get() {
+            readopts.check_snapshot(db, py)?;
+        }
+        let readopts = readopts.to_read_opt(raw_mode, normalize_str_bytes_keys, py)?;
+        Self::from_read_opt(
+            db,
+            cf,
+            readopts,
+            pickle_loads,
+            raw_mode,
+            normalize_str_bytes_keys,
+        )
+    }
 
+    /// Creates an iterator from read options already converted
+    /// (e.g. with a snapshot set on them).
+    pub(crate) fn from_read_opt(
+        db: &DbReferenceHolder,
+        cf: &Option<Arc<UnboundColumnFamily>>,
+        readopts: ReadOpt,
+        pickle_loads: &PyObject,
+        raw_mode: bool,
+        normalize_str_bytes_keys: bool,
+    ) -> PyResult<Self> {
         let db_inner = db
             .get()
             .ok_or_else(|| DbClosedError::new_err("DB instance already closed"))?
@@ -92,8 +119,34 @@ impl RdictIter {
             readopts,
             loads: pickle_loads.clone(),
             raw_mode,
+            normalize_str_bytes_keys,
+            prefix: None,
         })
     }
+
+    /// Runs `f` on the C iterator with the GIL released, since moving the
+    /// iterator may read blocks from disk. `f` must not touch Python objects.
+    fn without_gil(
+        &self,
+        py: Python,
+        f: impl FnOnce(*mut librocksdb_sys::rocksdb_iterator_t) + Send,
+    ) {
+        let inner = self.inner.lock().unwrap();
+        // raw pointers are not `Send`, the lock guarantees exclusive access
+        let iter = *inner as usize;
+        py.allow_threads(|| f(iter as *mut librocksdb_sys::rocksdb_iterator_t));
+    }
+
+    /// Whether the current key starts with `prefix`.
+    fn has_prefix(&self, prefix: &[u8]) -> bool {
+        unsafe {
+            let mut key_len: size_t = 0;
+            let key_ptr =
+                librocksdb_sys::rocksdb_iter_key(*self.inner.lock().unwrap(), &mut key_len)
+                    as *const c_uchar;
+            slice::from_raw_parts(key_ptr, key_len).starts_with(prefix)
+        }
+    }
 }
 
 #[pymethods]
@@ -106,7 +159,11 @@ impl RdictIter {
     /// return an error when `valid` is `true`.
     #[inline]
     pub fn valid(&self) -> bool {
-        unsafe { librocksdb_sys::rocksdb_iter_valid(*self.inner.lock().unwrap()) != 0 }
+        let valid = unsafe { librocksdb_sys::rocksdb_iter_valid(*self.inner.lock().unwrap()) != 0 };
+        match &self.prefix {
+            Some(prefix) => valid && self.has_prefix(prefix),
+            None => valid,
+        }
     }
 
     /// Returns an error `Result` if the iterator has encountered an error
@@ -120,7 +177,7 @@ impl RdictIter {
             librocksdb_sys::rocksdb_iter_get_error(*self.inner.lock().unwrap(), &mut err);
         }
         if !err.is_null() {
-            Err(PyException::new_err(error_message(err)))
+            Err(RocksDBError::new_err(error_message(err)))
         } else {
             Ok(())
         }
@@ -128,6 +185,9 @@ impl RdictIter {
 
     /// Seeks to the first key in the database.
     ///
+    /// Returns:
+    ///     whether the iterator is valid, as `valid()`.
+    ///
     /// Example:
     ///     ::
     ///
@@ -150,14 +210,18 @@ impl RdictIter {
     ///
     ///         del iter, db
     ///         Rdict.destroy(path, Options())
-    pub fn seek_to_first(&mut self) {
-        unsafe {
-            librocksdb_sys::rocksdb_iter_seek_to_first(*self.inner.lock().unwrap());
-        }
+    pub fn seek_to_first(&mut self, py: Python) -> bool {
+        self.without_gil(py, |iter| unsafe {
+            librocksdb_sys::rocksdb_iter_seek_to_first(iter);
+        });
+        self.valid()
     }
 
     /// Seeks to the last key in the database.
     ///
+    /// Returns:
+    ///     whether the iterator is valid, as `valid()`.
+    ///
     /// Example:
     ///     ::
     ///
@@ -180,10 +244,11 @@ impl RdictIter {
     ///
     ///         del iter, db
     ///         Rdict.destroy(path, Options())
-    pub fn seek_to_last(&mut self) {
-        unsafe {
-            librocksdb_sys::rocksdb_iter_seek_to_last(*self.inner.lock().unwrap());
-        }
+    pub fn seek_to_last(&mut self, py: Python) -> bool {
+        self.without_gil(py, |iter| unsafe {
+            librocksdb_sys::rocksdb_iter_seek_to_last(iter);
+        });
+        self.valid()
     }
 
     /// Seeks to the specified key or the first key that lexicographically follows it.
@@ -191,6 +256,9 @@ impl RdictIter {
     /// This method will attempt to seek to the specified key. If that key does not exist, it will
     /// find and seek to the key that lexicographically follows it instead.
     ///
+    /// Returns:
+    ///     whether the iterator is valid, as `valid()`.
+    ///
     /// Example:
     ///     ::
     ///
@@ -206,16 +274,16 @@ impl RdictIter {
     ///
     ///         del iter, db
     ///         Rdict.destroy(path, Options())
-    pub fn seek(&mut self, key: &Bound<PyAny>) -> PyResult<()> {
-        let key = encode_key(key, self.raw_mode)?;
-        unsafe {
+    pub fn seek(&mut self, key: &Bound<PyAny>) -> PyResult<bool> {
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
+        self.without_gil(key.py(), |iter| unsafe {
             librocksdb_sys::rocksdb_iter_seek(
-                *self.inner.lock().unwrap(),
+                iter,
                 key.as_ptr() as *const c_char,
                 key.len() as size_t,
             );
-        }
-        Ok(())
+        });
+        Ok(self.valid())
     }
 
     /// Seeks to the specified key, or the first key that lexicographically precedes it.
@@ -224,6 +292,9 @@ impl RdictIter {
     /// The difference with ``.seek()`` is that if the specified key do not exist, this method will
     /// seek to key that lexicographically precedes it instead.
     ///
+    /// Returns:
+    ///     whether the iterator is valid, as `valid()`.
+    ///
     /// Example:
     ///     ::
     ///
@@ -239,30 +310,32 @@ impl RdictIter {
     ///
     ///         del iter, db
     ///         Rdict.destroy(path, Options())
-    pub fn seek_for_prev(&mut self, key: &Bound<PyAny>) -> PyResult<()> {
-        let key = encode_key(key, self.raw_mode)?;
-        unsafe {
+    pub fn seek_for_prev(&mut self, key: &Bound<PyAny>) -> PyResult<bool> {
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
+        self.without_gil(key.py(), |iter| unsafe {
             librocksdb_sys::rocksdb_iter_seek_for_prev(
-                *self.inner.lock().unwrap(),
+                iter,
                 key.as_ptr() as *const c_char,
                 key.len() as size_t,
             );
-        }
-        Ok(())
+        });
+        Ok(self.valid())
     }
 
-    /// Seeks to the next key.
-    pub fn next(&mut self) {
-        unsafe {
-            librocksdb_sys::rocksdb_iter_next(*self.inner.lock().unwrap());
-        }
+    /// Seeks to the next key, and returns whether the iterator is valid.
+    pub fn next(&mut self, py: Python) -> bool {
+        self.without_gil(py, |iter| unsafe {
+            librocksdb_sys::rocksdb_iter_next(iter);
+        });
+        self.valid()
     }
 
-    /// Seeks to the previous key.
-    pub fn prev(&mut self) {
-        unsafe {
-            librocksdb_sys::rocksdb_iter_prev(*self.inner.lock().unwrap());
-        }
+    /// Seeks to the previous key, and returns whether the iterator is valid.
+    pub fn prev(&mut self, py: Python) -> bool {
+        self.without_gil(py, |iter| unsafe {
+            librocksdb_sys::rocksdb_iter_prev(iter);
+        });
+        self.valid()
     }
 
     /// Returns the current key.
@@ -355,12 +428,14 @@ macro_rules! impl_iter_single {
                 if slf.inner.valid() {
                     let $field = slf.inner.$field(py)?;
                     if slf.backwards {
-                        slf.inner.prev();
+                        slf.inner.prev(py);
                     } else {
-                        slf.inner.next();
+                        slf.inner.next(py);
                     }
                     Ok(Some($field))
                 } else {
+                    // an invalid iterator is either exhausted or failed
+                    slf.inner.status()?;
                     Ok(None)
                 }
             }
@@ -371,6 +446,7 @@ macro_rules! impl_iter_single {
                 inner: RdictIter,
                 backwards: bool,
                 from_key: Option<&Bound<PyAny>>,
+                py: Python,
             ) -> PyResult<Self> {
                 let mut inner = inner;
                 if let Some(from_key) = from_key {
@@ -381,9 +457,9 @@ macro_rules! impl_iter_single {
                     }
                 } else {
                     if backwards {
-                        inner.seek_to_last();
+                        inner.seek_to_last(py);
                     } else {
-                        inner.seek_to_first();
+                        inner.seek_to_first(py);
                     }
                 }
                 Ok(Self { inner, backwards })
@@ -404,19 +480,21 @@ macro_rules! impl_iter {
                 if slf.inner.valid() {
                     $(let $field = slf.inner.$field(py)?;)*
                     if slf.backwards {
-                        slf.inner.prev();
+                        slf.inner.prev(py);
                     } else {
-                        slf.inner.next();
+                        slf.inner.next(py);
                     }
                     Ok(Some(($($field),*).into_pyobject(py)?.into_any()))
                 } else {
+                    // an invalid iterator is either exhausted or failed
+                    slf.inner.status()?;
                     Ok(None)
                 }
             }
         }
 
         impl $iter_name {
-            pub(crate) fn new(inner: RdictIter, backwards: bool, from_key: Option<&Bound<PyAny>>) -> PyResult<Self> {
+            pub(crate) fn new(inner: RdictIter, backwards: bool, from_key: Option<&Bound<PyAny>>, py: Python) -> PyResult<Self> {
                 let mut inner = inner;
                 if let Some(from_key) = from_key {
                     if backwards {
@@ -426,9 +504,9 @@ macro_rules! impl_iter {
                     }
                 } else {
                     if backwards {
-                        inner.seek_to_last();
+                        inner.seek_to_last(py);
                     } else {
-                        inner.seek_to_first();
+                        inner.seek_to_first(py);
                     }
                 }
                 Ok(Self {
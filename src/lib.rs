@@ -1,14 +1,19 @@
+mod backup;
 mod checkpoints;
 mod db_reference;
 mod encoder;
 mod exceptions;
 mod iter;
 mod options;
+mod pinnable_slice;
 mod rdict;
 mod snapshot;
 mod sst_file_writer;
+mod transaction;
 mod util;
+mod wal_iterator;
 mod write_batch;
+mod write_batch_with_index;
 
 use crate::exceptions::*;
 use crate::iter::*;
@@ -17,8 +22,13 @@ use crate::rdict::*;
 use crate::snapshot::Snapshot;
 use crate::sst_file_writer::*;
 use crate::write_batch::*;
+use backup::BackupEnginePy;
 use checkpoints::CheckpointPy;
+use pinnable_slice::PinnableSlicePy;
 use pyo3::prelude::*;
+use transaction::{OptimisticTransactionDBPy, TransactionPy};
+use wal_iterator::WalIteratorPy;
+use write_batch_with_index::WriteBatchWithIndexPy;
 
 /// ## Abstract
 ///
@@ -125,6 +135,7 @@ fn rocksdict(py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_class::<ReadOptionsPy>()?;
     m.add_class::<DBCompressionTypePy>()?;
     m.add_class::<DBCompactionStylePy>()?;
+    m.add_class::<CompactionPriPy>()?;
     m.add_class::<DBRecoveryModePy>()?;
     m.add_class::<UniversalCompactOptionsPy>()?;
     m.add_class::<UniversalCompactionStopStylePy>()?;
@@ -139,6 +150,7 @@ fn rocksdict(py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_class::<IngestExternalFileOptionsPy>()?;
     m.add_class::<SstFileWriterPy>()?;
     m.add_class::<WriteBatchPy>()?;
+    m.add_class::<WriteBatchWithIndexPy>()?;
     m.add_class::<ColumnFamilyPy>()?;
     m.add_class::<AccessType>()?;
     m.add_class::<Snapshot>()?;
@@ -148,8 +160,15 @@ fn rocksdict(py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_class::<KeyEncodingTypePy>()?;
     m.add_class::<WriteBufferManagerPy>()?;
     m.add_class::<CheckpointPy>()?;
+    m.add_class::<BackupEnginePy>()?;
+    m.add_class::<OptimisticTransactionDBPy>()?;
+    m.add_class::<TransactionPy>()?;
+    m.add_class::<PinnableSlicePy>()?;
+    m.add_class::<WalIteratorPy>()?;
 
+    m.add("RocksDBError", py.get_type::<RocksDBError>())?;
     m.add("DbClosedError", py.get_type::<DbClosedError>())?;
+    m.add("KeyNotFoundError", py.get_type::<KeyNotFoundError>())?;
 
     Ok(())
 }
@@ -1,22 +1,39 @@
-use crate::encoder::encode_key;
-use crate::rdict::{RocksDictConfig, ROCKSDICT_CONFIG_FILE};
+use crate::db_reference::DbReference;
+use crate::encoder::{
+    compare_keys, compare_keys_with_u64_ts, compare_keys_without_u64_ts, compare_u64_ts,
+    decode_value, encode_key, encode_u64_ts, encode_value, value_expire_at, U64_TS_SIZE,
+};
+use crate::exceptions::rocksdb_error;
+use crate::rdict::{RocksDictConfig, SerializerConfig, ROCKSDICT_CONFIG_FILE};
+use crate::snapshot::{set_snapshot, Snapshot};
+use crate::util::unix_now;
 use libc::{c_char, c_uchar, size_t};
-use num_bigint::BigInt;
-use pyo3::exceptions::PyException;
+use pyo3::exceptions::{PyException, PyTypeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList, PyTuple};
+use pyo3::types::{PyBytes, PyDict, PyList, PyTuple};
+use rocksdb::compaction_filter::CompactionFilter;
+use rocksdb::compaction_filter_factory::{CompactionFilterContext, CompactionFilterFactory};
+use rocksdb::statistics::{Histogram, Ticker};
 use rocksdb::*;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::ffi::c_double;
+use std::ffi::{c_double, CStr, CString};
 use std::os::raw::{c_int, c_uint};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{self, AtomicUsize};
+use std::sync::{Arc, Mutex};
 
 /// Database-wide options around performance and behavior.
 ///
 /// Please read the official tuning [guide](https://github.com/facebook/rocksdb/wiki/RocksDB-Tuning-Guide)
 /// and most importantly, measure performance under realistic workloads with realistic hardware.
 ///
+/// Notes:
+///     The `get_*` methods return the values set on this `Options` object.
+///     Use `Rdict.get_effective_options` to read the values in effect for
+///     an open database, as saved in its latest OPTIONS file.
+///
 /// Example:
 ///     ::
 ///
@@ -49,12 +66,85 @@ use std::path::{Path, PathBuf};
 ///         it will only allow bytes as key-value pairs, and is compatible
 ///         with other RockDB database).
 ///
+/// The first exception raised by a Python comparator,
+/// which is raised by the next call to the Rdict.
+pub(crate) type ComparatorError = Arc<Mutex<Option<PyErr>>>;
+
 #[pyclass(name = "Options")]
 #[derive(Clone)]
 pub(crate) struct OptionsPy {
     pub(crate) inner_opt: Options,
     pub(crate) raw_mode: bool,
     pub(crate) prefix_extractor: Option<SliceTransformType>,
+    pub(crate) comparator: Option<BuiltinComparator>,
+    // set by `set_comparator`, which keys need not be in bytewise order
+    pub(crate) python_comparator: Option<ComparatorError>,
+    pub(crate) hash_memtable: bool,
+    pub(crate) normalize_str_bytes_keys: bool,
+    pub(crate) serializer: Option<SerializerPy>,
+    // set once a merge operator or a compaction filter uses the serializer
+    pub(crate) serializer_in_use: bool,
+    pub(crate) db_paths: Vec<(String, u64)>,
+}
+
+/// Custom `dumps` and `loads` functions, with the names to import them by.
+#[derive(Clone)]
+pub(crate) struct SerializerPy {
+    pub(crate) dumps: PyObject,
+    pub(crate) loads: PyObject,
+    pub(crate) config: SerializerConfig,
+}
+
+impl SerializerPy {
+    /// Import the `dumps` and `loads` functions saved in a rocksdict config.
+    pub(crate) fn import(py: Python, config: SerializerConfig) -> PyResult<Self> {
+        Ok(SerializerPy {
+            dumps: import_by_name(py, &config.dumps)?,
+            loads: import_by_name(py, &config.loads)?,
+            config,
+        })
+    }
+
+    /// The `(loads, dumps)` functions of an optional serializer,
+    /// defaulting to `pickle`.
+    pub(crate) fn loads_dumps(
+        serializer: Option<&SerializerPy>,
+        py: Python,
+    ) -> PyResult<(PyObject, PyObject)> {
+        match serializer {
+            Some(s) => Ok((s.loads.clone_ref(py), s.dumps.clone_ref(py))),
+            None => {
+                let pickle = PyModule::import(py, "pickle")?;
+                Ok((
+                    pickle.getattr("loads")?.unbind(),
+                    pickle.getattr("dumps")?.unbind(),
+                ))
+            }
+        }
+    }
+}
+
+/// `module:qualname` of a function, so that it can be imported again
+fn qualified_name(f: &Bound<PyAny>) -> PyResult<String> {
+    let module: String = f.getattr("__module__")?.extract()?;
+    let qualname: String = f.getattr("__qualname__")?.extract()?;
+    if qualname.contains('<') {
+        return Err(PyValueError::new_err(format!(
+            "`{qualname}` cannot be imported by name, use a module-level function"
+        )));
+    }
+    Ok(format!("{module}:{qualname}"))
+}
+
+fn import_by_name(py: Python, name: &str) -> PyResult<PyObject> {
+    let (module, qualname) = name
+        .split_once(':')
+        .ok_or_else(|| PyValueError::new_err(format!("invalid function name `{name}`")))?;
+    let mut function = PyModule::import(py, module)?.into_any();
+    for attr in qualname.split('.') {
+        function = function.getattr(attr)?;
+    }
+    Ok(function.unbind())
 }
 
 /// Optionally disable WAL or sync for this write.
@@ -138,10 +228,17 @@ pub(crate) struct WriteOptionsPy {
 
 /// Optionally wait for the memtable flush to be performed.
 ///
+/// Notes:
+///     RocksDB's `allow_write_stall` flush option is not available
+///     in its C API, so it cannot be set here: flushes always use
+///     RocksDB's default (`false`), i.e. a flush waits for ongoing
+///     write stalls to clear before starting, instead of possibly
+///     causing a write stall itself.
+///
 /// Example:
 ///     ::
 ///
-///         from rocksdb import Rdict, Options, FlushOptions
+///         from rocksdict import Rdict, Options, FlushOptions
 ///
 ///         path = "_path_for_rocksdb_storageY2"
 ///         db = Rdict(path, Options())
@@ -162,10 +259,10 @@ pub(crate) struct FlushOptionsPy {
     /// Example:
     ///     ::
     ///
-    ///         from rocksdb import FlushOptions
+    ///         from rocksdict import FlushOptions
     ///
-    ///         let options = FlushOptions()
-    ///         options.set_wait(False)
+    ///         options = FlushOptions()
+    ///         options.wait = False
     #[pyo3(get, set)]
     pub(crate) wait: bool,
 }
@@ -191,9 +288,28 @@ pub(crate) struct ReadOptionsPy {
     tailing: bool,
     pin_data: bool,
     async_io: bool,
+    auto_readahead_size: bool,
+    auto_total_order_seek: bool,
+    snapshot: Option<Py<Snapshot>>,
+    timestamp: Option<u64>,
+    iter_start_ts: Option<u64>,
 }
 
-pub(crate) struct ReadOpt(pub(crate) *mut librocksdb_sys::rocksdb_readoptions_t);
+/// Raw read options.
+///
+/// The C read options only point to the iterate bounds and timestamps,
+/// so they are owned here, along with the snapshot set on them (if any),
+/// to outlive the iterators using these options.
+pub(crate) struct ReadOpt(
+    pub(crate) *mut librocksdb_sys::rocksdb_readoptions_t,
+    ReadOptData,
+);
+
+#[derive(Default)]
+pub(crate) struct ReadOptData {
+    buffers: Vec<Vec<u8>>,
+    snapshot: Option<Py<Snapshot>>,
+}
 
 /// Defines the underlying memtable implementation.
 /// See official [wiki](https://github.com/facebook/rocksdb/wiki/MemTable) for more information.
@@ -266,9 +382,43 @@ pub(crate) struct PlainTableFactoryOptionsPy {
     store_index_in_file: bool,
 }
 
+/// Block cache, row cache or blob cache.
+///
+/// Notes:
+///     The cache does not count its hits and misses. Enable statistics
+///     with `Options.enable_statistics` and read the
+///     `rocksdb.block.cache.hit` and `rocksdb.block.cache.miss` tickers
+///     (or `rocksdb.row.cache.hit` and `rocksdb.row.cache.miss`)
+///     with `Options.get_ticker_count` to compute the hit ratio.
+///
+///     A compressed secondary cache tier is not available: the RocksDB
+///     C API, which rocksdict is built on, has no binding for
+///     `NewCompressedSecondaryCache`. The compressed block cache it
+///     supersedes (`block_cache_compressed`) was removed in RocksDB 8.0.
+///
+/// Example:
+///     ::
+///
+///         from rocksdict import Rdict, Options, BlockBasedOptions, Cache
+///
+///         cache = Cache(64 * 1024 * 1024)
+///         table_options = BlockBasedOptions()
+///         table_options.set_block_cache(cache)
+///         opt = Options()
+///         opt.enable_statistics()
+///         opt.set_block_based_table_factory(table_options)
+///         db = Rdict("./db", opt)
+///
+///         hit = opt.get_ticker_count("rocksdb.block.cache.hit")
+///         miss = opt.get_ticker_count("rocksdb.block.cache.miss")
+///         print(hit / max(hit + miss, 1), cache.get_usage(), cache.get_capacity())
 #[pyclass(name = "Cache")]
 #[derive(Clone)]
-pub(crate) struct CachePy(Cache);
+pub(crate) struct CachePy(
+    Cache,
+    // rust-rocksdb cannot read the capacity back, so it is tracked here
+    Arc<AtomicUsize>,
+);
 
 #[pyclass(name = "BlockBasedIndexType")]
 pub(crate) struct BlockBasedIndexTypePy(BlockBasedIndexType);
@@ -279,13 +429,51 @@ pub(crate) struct DataBlockIndexTypePy(DataBlockIndexType);
 #[pyclass(name = "SliceTransform")]
 pub(crate) struct SliceTransformPy(SliceTransformType);
 
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
 pub enum SliceTransformType {
     Fixed(size_t),
     MaxLen(usize),
     Noop,
 }
 
+impl std::fmt::Display for SliceTransformType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SliceTransformType::Fixed(len) => write!(f, "fixed_prefix({len})"),
+            SliceTransformType::MaxLen(len) => write!(f, "max_len_prefix({len})"),
+            SliceTransformType::Noop => write!(f, "noop"),
+        }
+    }
+}
+
+/// Comparators of `Options.set_builtin_comparator`, implemented in Rust.
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub enum BuiltinComparator {
+    Bytewise,
+    ReverseBytewise,
+}
+
+impl BuiltinComparator {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "bytewise" => Ok(BuiltinComparator::Bytewise),
+            "reverse_bytewise" => Ok(BuiltinComparator::ReverseBytewise),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown comparator `{name}`, expected `bytewise` or `reverse_bytewise`"
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for BuiltinComparator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuiltinComparator::Bytewise => write!(f, "bytewise"),
+            BuiltinComparator::ReverseBytewise => write!(f, "reverse_bytewise"),
+        }
+    }
+}
+
 #[pyclass(name = "DBPath")]
 pub(crate) struct DBPathPy {
     path: PathBuf,
@@ -334,6 +522,53 @@ pub(crate) struct DBCompressionTypePy(DBCompressionType);
 #[pyclass(name = "DBCompactionStyle")]
 pub(crate) struct DBCompactionStylePy(DBCompactionStyle);
 
+/// The order in which files are picked for compaction in level compaction.
+///
+/// The options are:
+/// - by_compensated_size: slightly prioritize larger files by size
+///   compensated by #deletes
+/// - oldest_largest_seq_first: first compact files whose data's latest
+///   update time is oldest. Try this if you only update some hot keys
+///   in small ranges.
+/// - oldest_smallest_seq_first: first compact files whose range hasn't
+///   been compacted to the next level for the longest. If your updates
+///   are random across the key space, write amplification is slightly
+///   better with this option.
+/// - min_overlapping_ratio: first compact files whose ratio between
+///   overlapping size in next level and its size is the smallest.
+///   It in many cases can optimize write amplification. This is the default.
+/// - round_robin: cycle through the files of each level, starting after
+///   the files compacted before.
+///
+/// See [rocksdb post](https://github.com/facebook/rocksdb/blob/f20d12adc85ece3e75fb238872959c702c0e5535/docs/_posts/2016-01-29-compaction_pri.markdown) for more details.
+///
+/// Example:
+///     ::
+///
+///         opt = Options()
+///         opt.set_compaction_pri(CompactionPri.oldest_smallest_seq_first())
+///
+#[pyclass(name = "CompactionPri")]
+pub(crate) struct CompactionPriPy(CompactionPri);
+
+impl Clone for CompactionPriPy {
+    fn clone(&self) -> Self {
+        match self.0 {
+            CompactionPri::ByCompensatedSize => CompactionPriPy(CompactionPri::ByCompensatedSize),
+            CompactionPri::OldestLargestSeqFirst => {
+                CompactionPriPy(CompactionPri::OldestLargestSeqFirst)
+            }
+            CompactionPri::OldestSmallestSeqFirst => {
+                CompactionPriPy(CompactionPri::OldestSmallestSeqFirst)
+            }
+            CompactionPri::MinOverlappingRatio => {
+                CompactionPriPy(CompactionPri::MinOverlappingRatio)
+            }
+            CompactionPri::RoundRobin => CompactionPriPy(CompactionPri::RoundRobin),
+        }
+    }
+}
+
 /// <https://github.com/facebook/rocksdb/wiki/Write-Buffer-Manager>
 /// Write buffer manager helps users control the total memory used
 /// by memtables across multiple column families and/or DB instances.
@@ -365,6 +600,25 @@ pub(crate) struct DBCompactionStylePy(DBCompactionStyle);
 /// allow_stall: If set true, it will enable stalling of all writers when
 /// memory usage exceeds buffer_size (soft limit).
 ///             It will wait for flush to complete and memory usage to drop down
+///
+/// Notes:
+///     Share one manager between the options of several databases
+///     to bound the total memory of their memtables.
+///
+/// Example:
+///     ::
+///
+///         from rocksdict import Rdict, Options, WriteBufferManager
+///
+///         manager = WriteBufferManager(64 * 1024 * 1024, False)
+///         opt = Options()
+///         opt.set_write_buffer_manager(manager)
+///         db1 = Rdict("./db1", opt)
+///         db2 = Rdict("./db2", opt)
+///         db1["a"] = "a" * 1024
+///         db2["b"] = "b" * 1024
+///         # memory of the memtables of both databases
+///         print(manager.get_usage())
 #[pyclass(name = "WriteBufferManager")]
 pub(crate) struct WriteBufferManagerPy(WriteBufferManager);
 
@@ -507,7 +761,15 @@ pub(crate) struct IngestExternalFileOptionsPy(pub(crate) IngestExternalFileOptio
 pub(crate) struct BottommostLevelCompactionPy(BottommostLevelCompaction);
 
 #[pyclass(name = "CompactOptions")]
-pub(crate) struct CompactOptionsPy(pub(crate) CompactOptions);
+pub(crate) struct CompactOptionsPy {
+    pub(crate) inner: CompactOptions,
+    // CompactOptions cannot be read back, so the settings are
+    // recorded to be able to copy them.
+    exclusive_manual_compaction: Option<bool>,
+    bottommost_level_compaction: Option<BottommostLevelCompactionPy>,
+    change_level: Option<bool>,
+    target_level: Option<c_int>,
+}
 
 unsafe impl Send for CompactOptionsPy {}
 unsafe impl Sync for CompactOptionsPy {}
@@ -534,6 +796,21 @@ impl OptionsPy {
         Ok(())
     }
 
+    /// Uses the names of RocksDB's built-in comparators,
+    /// so that other RocksDB tools can open the database.
+    fn set_builtin_comparator_inner(options: &mut Options, comparator: BuiltinComparator) {
+        match comparator {
+            BuiltinComparator::Bytewise => options.set_comparator(
+                "leveldb.BytewiseComparator",
+                Box::new(|a: &[u8], b: &[u8]| a.cmp(b)),
+            ),
+            BuiltinComparator::ReverseBytewise => options.set_comparator(
+                "rocksdb.ReverseBytewiseComparator",
+                Box::new(|a: &[u8], b: &[u8]| b.cmp(a)),
+            ),
+        }
+    }
+
     /// load latest options from OPTIONS files and config files
     pub fn load_latest_inner(
         path: &str,
@@ -545,17 +822,24 @@ impl OptionsPy {
         config_path.push(ROCKSDICT_CONFIG_FILE);
         let rocksdict_config = RocksDictConfig::load(config_path).unwrap_or_default();
         let raw_mode = rocksdict_config.raw_mode;
+        let normalize_str_bytes_keys = rocksdict_config.normalize_str_bytes_keys;
         let slice_transforms = rocksdict_config.prefix_extractors;
+        let comparators = rocksdict_config.comparators;
         let load_result = Options::load_latest(path, env.0, ignore_unknown_options, cache.0);
         let (options, column_families) = match load_result {
             Ok(d) => d,
-            Err(e) => return Err(PyException::new_err(e.to_string())),
+            Err(e) => return Err(rocksdb_error(e)),
         };
-        let options = OptionsPy::compose_options_py(
+        let mut options = OptionsPy::compose_options_py(
             options,
             raw_mode,
             slice_transforms.get(DEFAULT_COLUMN_FAMILY_NAME).cloned(),
+            comparators.get(DEFAULT_COLUMN_FAMILY_NAME).copied(),
         )?;
+        options.normalize_str_bytes_keys = normalize_str_bytes_keys;
+        if let Some(config) = rocksdict_config.serializer {
+            options.serializer = Some(Python::with_gil(|py| SerializerPy::import(py, config))?);
+        }
         let column_families: PyResult<HashMap<_, _>> = column_families
             .into_iter()
             .map(|c| {
@@ -563,9 +847,14 @@ impl OptionsPy {
                     c.options,
                     raw_mode,
                     slice_transforms.get(&c.name).cloned(),
+                    comparators.get(&c.name).copied(),
                 );
                 match opt {
-                    Ok(opt) => Ok((c.name, opt)),
+                    Ok(mut opt) => {
+                        opt.normalize_str_bytes_keys = normalize_str_bytes_keys;
+                        opt.serializer = options.serializer.clone();
+                        Ok((c.name, opt))
+                    }
                     Err(e) => Err(e),
                 }
             })
@@ -573,16 +862,23 @@ impl OptionsPy {
         Ok((options, column_families?))
     }
 
-    /// convert `Options` into `OptionsPy` based on `raw_mode` and `prefix_extractor`
+    /// convert `Options` into `OptionsPy` based on `raw_mode`, `prefix_extractor`
+    /// and `comparator`
     fn compose_options_py(
         opt: Options,
         raw_mode: bool,
         prefix_extractor: Option<SliceTransformType>,
+        comparator: Option<BuiltinComparator>,
     ) -> PyResult<OptionsPy> {
         let mut opt = opt;
+        // the rocksdict comparator is always used in non-raw mode
+        let comparator = comparator.filter(|_| raw_mode);
         if !raw_mode {
             OptionsPy::set_rocksdict_comparator(&mut opt);
         }
+        if let Some(comparator) = comparator {
+            OptionsPy::set_builtin_comparator_inner(&mut opt, comparator);
+        }
         if let Some(slice_transform) = &prefix_extractor {
             OptionsPy::set_prefix_extractor_inner(&mut opt, slice_transform)?
         }
@@ -590,22 +886,30 @@ impl OptionsPy {
             inner_opt: opt,
             raw_mode,
             prefix_extractor,
+            comparator,
+            python_comparator: None,
+            hash_memtable: false,
+            normalize_str_bytes_keys: false,
+            serializer: None,
+            serializer_in_use: false,
+            db_paths: Vec::new(),
         };
         Ok(options)
     }
 
+    /// hash-based memtables cannot be opened without a prefix extractor
+    pub(crate) fn check_memtable_factory(&self, cf_name: &str) -> PyResult<()> {
+        if self.hash_memtable && self.prefix_extractor.is_none() {
+            return Err(PyException::new_err(format!(
+                "column family `{cf_name}` uses a hash-based memtable factory, \
+                 which requires a prefix extractor, call `Options.set_prefix_extractor`"
+            )));
+        }
+        Ok(())
+    }
+
     fn set_rocksdict_comparator(opt: &mut Options) {
-        opt.set_comparator(
-            "rocksdict",
-            Box::new(|v1, v2| {
-                if let (Some(3), Some(3)) = (v1.first(), v2.first()) {
-                    BigInt::from_signed_bytes_be(&v1[1..])
-                        .cmp(&BigInt::from_signed_bytes_be(&v2[1..]))
-                } else {
-                    v1.cmp(v2)
-                }
-            }),
-        );
+        opt.set_comparator("rocksdict", Box::new(compare_keys));
     }
 }
 
@@ -624,19 +928,124 @@ impl OptionsPy {
             inner_opt: opt,
             raw_mode,
             prefix_extractor: None,
+            comparator: None,
+            python_comparator: None,
+            hash_memtable: false,
+            normalize_str_bytes_keys: false,
+            serializer: None,
+            serializer_in_use: false,
+            db_paths: Vec::new(),
+        }
+    }
+
+    /// Store `str` keys as their UTF-8 bytes, so that `"abc"` and `b"abc"`
+    /// are the same key, as in Redis-like stores.
+    ///
+    /// Notes:
+    ///     This is lossy: `str` keys are read back as `bytes`
+    ///     (e.g. from `db.keys()`), including `str` components of
+    ///     tuple keys. The setting is saved with the database and
+    ///     restored when it is reopened, and should not be changed
+    ///     for an existing database, since keys already written as
+    ///     `str` would no longer be found. `WriteBatch` must be created
+    ///     with the same `normalize_str_bytes_keys` argument.
+    ///     Has no effect in raw mode, where keys are always bytes.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options
+    ///
+    ///         opt = Options()
+    ///         opt.set_normalize_str_bytes_keys(True)
+    ///         db = Rdict("./test_dir", opt)
+    ///         db["abc"] = 1
+    ///         assert db[b"abc"] == 1
+    ///         assert list(db.keys()) == [b"abc"]
+    pub fn set_normalize_str_bytes_keys(&mut self, normalize: bool) {
+        self.normalize_str_bytes_keys = normalize
+    }
+
+    /// Use custom `dumps` and `loads` functions instead of `pickle`
+    /// for values (and keys) that are not `int`, `float`, `bool`,
+    /// `str`, or `bytes`, e.g. for databases shared with other languages.
+    ///
+    /// Notes:
+    ///     `dumps` must return `bytes`, and `loads` takes `bytes`.
+    ///     Both must be module-level functions: their names are saved
+    ///     with the database, and they are imported again when it is
+    ///     reopened without options. Opening the database with a
+    ///     different serializer raises an exception.
+    ///     `SstFileWriter` created with these options uses the same
+    ///     serializer, while `WriteBatch` and `WriteBatchWithIndex` require
+    ///     `set_dumps` and `set_loads` (`Rdict.write` rejects batches with
+    ///     another `dumps` function). Merge operators and compaction filters
+    ///     use the serializer set when they are set, so this must be called first.
+    ///     Has no effect in raw mode, where values are always bytes.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options
+    ///         import json
+    ///
+    ///         def json_dumps(obj):
+    ///             return json.dumps(obj).encode()
+    ///
+    ///         def json_loads(data):
+    ///             return json.loads(data)
+    ///
+    ///         opt = Options()
+    ///         opt.set_serializer(json_dumps, json_loads)
+    ///         db = Rdict("./test_dir", opt)
+    ///         db["list"] = [1, 2, 3]
+    ///
+    /// Args:
+    ///     dumps: function serializing an object into bytes.
+    ///     loads: function deserializing bytes into an object.
+    pub fn set_serializer(&mut self, dumps: &Bound<PyAny>, loads: &Bound<PyAny>) -> PyResult<()> {
+        if self.serializer_in_use {
+            return Err(PyException::new_err(
+                "set_serializer must be called before setting merge operators \
+                 or compaction filters",
+            ));
         }
+        self.serializer = Some(SerializerPy {
+            dumps: dumps.clone().unbind(),
+            loads: loads.clone().unbind(),
+            config: SerializerConfig {
+                dumps: qualified_name(dumps)?,
+                loads: qualified_name(loads)?,
+            },
+        });
+        Ok(())
     }
 
     /// Load latest options from the rocksdb path
     ///
     /// Returns a tuple, where the first item is `Options`
     /// and the second item is a `Dict` of column families.
+    ///
+    /// Notes:
+    ///     The options are read from the latest OPTIONS file
+    ///     (see `Rdict.latest_options_file`), which RocksDB writes
+    ///     whenever the database is opened. Settings that are not
+    ///     saved in it, such as `set_db_paths` or a custom comparator,
+    ///     must be set again.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Options, Rdict
+    ///
+    ///         opts, cf_opts = Options.load_latest("./test_dir")
+    ///         db = Rdict("./test_dir", opts, column_families=cf_opts)
     #[staticmethod]
     #[pyo3(signature = (
         path,
         env = EnvPy::default().unwrap(),
         ignore_unknown_options = false,
-        cache = CachePy::new_lru_cache(8 * 1024 * 1204)
+        cache = CachePy::new_lru_cache(8 * 1024 * 1024)
     ))]
     pub fn load_latest<'py>(
         path: &str,
@@ -781,23 +1190,53 @@ impl OptionsPy {
     ///     opt.set_db_paths([flash_path, hard_drive])
     pub fn set_db_paths(&mut self, paths: &Bound<PyList>) -> PyResult<()> {
         let mut db_paths = Vec::with_capacity(paths.len());
+        let mut configured = Vec::with_capacity(paths.len());
         for p in paths.iter() {
             let path: &Bound<DBPathPy> = p.downcast()?;
-            db_paths.push(
-                match DBPath::new(&path.borrow().path, path.borrow().target_size) {
-                    Ok(p) => p,
-                    Err(e) => return Err(PyException::new_err(e.into_string())),
-                },
-            );
+            let path = path.borrow();
+            db_paths.push(match DBPath::new(&path.path, path.target_size) {
+                Ok(p) => p,
+                Err(e) => return Err(rocksdb_error(e)),
+            });
+            configured.push((path.path(), path.target_size));
         }
         self.inner_opt.set_db_paths(&db_paths);
+        self.db_paths = configured;
         Ok(())
     }
 
+    /// Get the paths set by `set_db_paths`.
+    ///
+    /// Notes:
+    ///     The paths are not saved in the OPTIONS file, so options loaded
+    ///     by `Options.load_latest` always return an empty list.
+    ///
+    /// Returns:
+    ///     A list of `(path, target_size)` tuples, in the order they were set.
+    pub fn get_db_paths(&self) -> Vec<(String, u64)> {
+        self.db_paths.clone()
+    }
+
     /// Use the specified object to interact with the environment,
     /// e.g. to read/write files, schedule background work, etc. In the near
     /// future, support for doing storage operations such as read/write files
     /// through env will be deprecated in favor of file_system.
+    ///
+    /// The same `Env` can be shared by many databases in one process,
+    /// so that they share its bounded background thread pools.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options, Env
+    ///
+    ///         env = Env()
+    ///         env.set_low_priority_background_threads(2)
+    ///         env.set_high_priority_background_threads(1)
+    ///
+    ///         opt = Options()
+    ///         opt.set_env(env)
+    ///         dbs = [Rdict(f"./test_dir_{i}", opt) for i in range(10)]
     pub fn set_env(&mut self, env: &EnvPy) {
         self.inner_opt.set_env(&env.0)
     }
@@ -965,9 +1404,64 @@ impl OptionsPy {
         self.inner_opt.set_level_compaction_dynamic_level_bytes(v)
     }
 
-    // pub fn set_merge_operator_associative<F: MergeFn + Clone>(&mut self, name: &str, full_merge_fn: F) {
-    //     self.inner_opt.set_merge_operator_associative(name, full_merge_fn)
-    // }
+    /// Sets an associative merge operator implemented by a Python function.
+    ///
+    /// The function is called as `merge_fn(key, existing_value, operands)`,
+    /// where `existing_value` is None if there is no existing value,
+    /// and `operands` is the list of merge operands in the order they were
+    /// written. It must return the merged value. Keys and values are decoded
+    /// and encoded the same way as in `Rdict` (only `bytes` in raw mode),
+    /// and other python objects are serialized with the serializer
+    /// of `set_serializer` (`pickle` by default).
+    ///
+    /// Notes:
+    ///     The function is called under the GIL, from the thread doing
+    ///     the read or from RocksDB background threads during compaction.
+    ///     Exceptions raised inside it are reported with
+    ///     `sys.unraisablehook`, and fail the merge with a RocksDB error.
+    ///     The same merge operator must be set every time the database
+    ///     is opened.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options
+    ///
+    ///         def add(key, existing_value, operands):
+    ///             return (existing_value or 0) + sum(operands)
+    ///
+    ///         opt = Options()
+    ///         opt.set_merge_operator_associative("add", add)
+    ///         db = Rdict("./test_dir", opt)
+    ///         db.merge("counter", 1)
+    ///         db.merge("counter", 2)
+    ///         assert db["counter"] == 3
+    ///
+    /// Args:
+    ///     name: name of the merge operator.
+    ///     merge_fn: the merge function.
+    pub fn set_merge_operator_associative(
+        &mut self,
+        name: &str,
+        merge_fn: PyObject,
+        py: Python,
+    ) -> PyResult<()> {
+        let (loads, dumps) = SerializerPy::loads_dumps(self.serializer.as_ref(), py)?;
+        self.serializer_in_use = true;
+        let merge_fn = Arc::new(PyMergeFn {
+            merge_fn,
+            loads,
+            dumps,
+            raw_mode: self.raw_mode,
+        });
+        self.inner_opt.set_merge_operator_associative(
+            name,
+            move |key: &[u8], existing_value: Option<&[u8]>, operands: &MergeOperands| {
+                merge_fn.call(key, existing_value, operands)
+            },
+        );
+        Ok(())
+    }
 
     // pub fn set_merge_operator<F: MergeFn, PF: MergeFn>(&mut self, name: &str, full_merge_fn: F, partial_merge_fn: PF,) {
     //     self.inner_opt.set_merge_operator(name, full_merge_fn, partial_merge_fn,)
@@ -977,17 +1471,226 @@ impl OptionsPy {
     //     self.0.add_merge_operator(name, merge_fn)
     // }
 
-    // pub fn set_compaction_filter<F>(&mut self, name: &str, filter_fn: F) {
-    //     self.inner_opt.set_compaction_filter(name, filter_fn)
-    // }
+    /// Sets a compaction filter implemented by a Python function,
+    /// which can remove or change key-value pairs during compaction.
+    ///
+    /// The function is called as `filter_fn(level, key, value)` with the
+    /// decoded key and value (only `bytes` in raw mode), and must return
+    /// `"keep"`, `"remove"`, or `("change", new_value)`.
+    ///
+    /// Notes:
+    ///     The filter only runs when the data is compacted, not on reads,
+    ///     so removed or changed values remain visible until the
+    ///     compaction of the files containing them (use `compact_range`
+    ///     to force it). The function is called under the GIL from RocksDB
+    ///     background threads. If it raises, the exception is reported
+    ///     with `sys.unraisablehook` and the key-value pair is kept.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options
+    ///         import time
+    ///
+    ///         def drop_expired(level, key, value):
+    ///             return "remove" if value["expire_at"] < time.time() else "keep"
+    ///
+    ///         opt = Options()
+    ///         opt.set_compaction_filter("drop_expired", drop_expired)
+    ///         db = Rdict("./test_dir", opt)
+    ///
+    /// Args:
+    ///     name: name of the compaction filter.
+    ///     filter_fn: the filter function.
+    pub fn set_compaction_filter(
+        &mut self,
+        name: &str,
+        filter_fn: PyObject,
+        py: Python,
+    ) -> PyResult<()> {
+        let (loads, dumps) = SerializerPy::loads_dumps(self.serializer.as_ref(), py)?;
+        self.serializer_in_use = true;
+        let mut filter_fn = PyCompactionFilterFn {
+            filter_fn,
+            loads,
+            dumps,
+            raw_mode: self.raw_mode,
+            new_value: Vec::new(),
+        };
+        self.inner_opt
+            .set_compaction_filter(name, move |level: u32, key: &[u8], value: &[u8]| {
+                filter_fn.call(level, key, value)
+            });
+        Ok(())
+    }
 
-    // pub fn set_compaction_filter_factory<F>(&mut self, factory: F) {
-    //     self.inner_opt.set_compaction_filter_factory(factory)
-    // }
+    /// Sets a compaction filter factory implemented in Python,
+    /// for compaction filters that need per-compaction state.
+    ///
+    /// At the start of every compaction job, the factory is called as
+    /// `factory(is_full_compaction, is_manual_compaction)` and must return
+    /// a fresh filter: a callable with the same signature and return values
+    /// as the function of `set_compaction_filter`. The filter is then used
+    /// for all the key-value pairs of that compaction job only.
+    ///
+    /// Notes:
+    ///     Filters of concurrent compaction jobs may exist at the same time,
+    ///     but each filter is only used by the thread of its own job.
+    ///     If the factory raises, the exception is reported with
+    ///     `sys.unraisablehook` and all the key-value pairs of the
+    ///     compaction job are kept.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options
+    ///
+    ///         class KeepFirstVersions:
+    ///             def __init__(self, is_full, is_manual):
+    ///                 self.seen = set()
+    ///
+    ///             def __call__(self, level, key, value):
+    ///                 prefix = key.split(":")[0]
+    ///                 if prefix in self.seen:
+    ///                     return "remove"
+    ///                 self.seen.add(prefix)
+    ///                 return "keep"
+    ///
+    ///         opt = Options()
+    ///         opt.set_compaction_filter_factory(KeepFirstVersions)
+    ///         db = Rdict("./test_dir", opt)
+    ///
+    /// Args:
+    ///     factory: called once per compaction job to create a filter.
+    ///     name: name of the compaction filter factory.
+    #[pyo3(signature = (factory, name = "rocksdict_compaction_filter_factory"))]
+    pub fn set_compaction_filter_factory(
+        &mut self,
+        factory: PyObject,
+        name: &str,
+        py: Python,
+    ) -> PyResult<()> {
+        let (loads, dumps) = SerializerPy::loads_dumps(self.serializer.as_ref(), py)?;
+        self.serializer_in_use = true;
+        let name = CString::new(name).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.inner_opt
+            .set_compaction_filter_factory(PyCompactionFilterFactory {
+                factory,
+                loads,
+                dumps,
+                raw_mode: self.raw_mode,
+                name,
+            });
+        Ok(())
+    }
 
-    // pub fn set_comparator(&mut self, name: &str, compare_fn: CompareFn) {
-    //     self.inner_opt.set_comparator(name, compare_fn)
-    // }
+    /// Sets a compaction filter removing the values written by
+    /// `Rdict.put_with_ttl` once they have expired.
+    ///
+    /// Notes:
+    ///     Expired values are only removed when the data is compacted,
+    ///     so they may still be read until the compaction of the files
+    ///     containing them (use `compact_range` to force it). This
+    ///     replaces the filter of `set_compaction_filter`, and is not
+    ///     supported in raw mode.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options
+    ///
+    ///         opt = Options()
+    ///         opt.set_per_key_ttl_filter()
+    ///         db = Rdict("./test_dir", opt)
+    ///         db.put_with_ttl("session", "token", 3600)
+    pub fn set_per_key_ttl_filter(&mut self) -> PyResult<()> {
+        if self.raw_mode {
+            return Err(PyValueError::new_err(
+                "per-key TTL is not supported in raw mode",
+            ));
+        }
+        self.inner_opt.set_compaction_filter(
+            "rocksdict_per_key_ttl",
+            |_level: u32, _key: &[u8], value: &[u8]| match value_expire_at(value) {
+                Some(expire_at) if expire_at <= unix_now() => CompactionDecision::Remove,
+                _ => CompactionDecision::Keep,
+            },
+        );
+        Ok(())
+    }
+
+    /// Sets a custom comparator implemented by a Python function,
+    /// which defines the ordering of keys.
+    ///
+    /// The function is called as `compare_fn(a, b)` with the encoded keys
+    /// as `bytes`, and must return a negative number, zero, or a positive
+    /// number if `a` is less than, equal to, or greater than `b`.
+    /// In non-raw mode, the first byte of each key encodes the type of
+    /// the key, and this comparator replaces the default one which orders
+    /// `int` keys numerically.
+    ///
+    /// Notes:
+    ///     The function is called under the GIL for every key comparison,
+    ///     including from RocksDB background threads, so it is slow and
+    ///     must never raise. It is checked once when it is set. If it
+    ///     raises or does not return an `int` later, the keys are compared
+    ///     bytewise instead, and the first exception is raised by the next
+    ///     call to the Rdict. The ordering of the database is undefined
+    ///     after such a failure.
+    ///     The comparator name is persisted, and a database must always be
+    ///     opened with a comparator of the same name and ordering.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options
+    ///
+    ///         def reverse_cmp(a, b):
+    ///             return (a < b) - (a > b)
+    ///
+    ///         opt = Options(raw_mode=True)
+    ///         opt.set_comparator("reverse", reverse_cmp)
+    ///         db = Rdict("./test_dir", opt)
+    ///
+    /// Args:
+    ///     name: name of the comparator.
+    ///     compare_fn: the compare function.
+    pub fn set_comparator(&mut self, name: &str, compare_fn: PyObject, py: Python) -> PyResult<()> {
+        let checked = compare_fn
+            .call1(py, (PyBytes::new(py, b"a"), PyBytes::new(py, b"b")))
+            .map_err(|e| {
+                PyTypeError::new_err(format!("the compare function raised when checked: {e}"))
+            })?;
+        if checked.extract::<i64>(py).is_err() {
+            return Err(PyTypeError::new_err(
+                "the compare function must return an int",
+            ));
+        }
+        let error: ComparatorError = Arc::new(Mutex::new(None));
+        let first_error = error.clone();
+        self.inner_opt.set_comparator(
+            name,
+            Box::new(move |a: &[u8], b: &[u8]| {
+                Python::with_gil(|py| {
+                    let result = compare_fn
+                        .call1(py, (PyBytes::new(py, a), PyBytes::new(py, b)))
+                        .and_then(|r| r.extract::<i64>(py));
+                    match result {
+                        Ok(r) => r.cmp(&0),
+                        Err(e) => {
+                            // RocksDB cannot handle the error, keep the first one
+                            // for the next Rdict call
+                            first_error.lock().unwrap().get_or_insert(e);
+                            a.cmp(b)
+                        }
+                    }
+                })
+            }),
+        );
+        self.comparator = None;
+        self.python_comparator = Some(error);
+        Ok(())
+    }
 
     pub fn set_prefix_extractor(&mut self, prefix_extractor: &SliceTransformPy) -> PyResult<()> {
         let transform = match &prefix_extractor.0 {
@@ -1007,6 +1710,148 @@ impl OptionsPy {
         Ok(())
     }
 
+    /// Configure the options for prefix lookups in one call.
+    ///
+    /// This sets a fixed prefix extractor of `prefix_len` bytes,
+    /// a memtable prefix bloom filter (with whole key filtering, so
+    /// point lookups also benefit from it), a block-based bloom filter
+    /// and a hash index.
+    ///
+    /// Notes:
+    ///     The table options replace the block-based table factory,
+    ///     so pass your own `table_options` to keep settings such as
+    ///     the block cache: the filter and the index are set on them.
+    ///     In non-raw mode, `prefix_len` counts the bytes of the encoded
+    ///     keys, which start with a type byte.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options
+    ///
+    ///         opt = Options(raw_mode=True)
+    ///         opt.configure_prefix_lookup(4)
+    ///         db = Rdict("./db", opt)
+    ///         db[b"user0001"] = b"a"
+    ///         db[b"user0002"] = b"b"
+    ///         for k, v in db.iter_prefix(b"user"):
+    ///             print(k, v)
+    ///
+    /// Args:
+    ///     prefix_len (int): the length of the prefixes in bytes.
+    ///     bloom_bits (float): the bits per key of the bloom filters.
+    ///     table_options (BlockBasedOptions): the block-based table options
+    ///         to configure and use, new ones by default.
+    #[pyo3(signature = (prefix_len, bloom_bits = 10.0, table_options = None))]
+    pub fn configure_prefix_lookup(
+        &mut self,
+        prefix_len: usize,
+        bloom_bits: c_double,
+        table_options: Option<PyRefMut<BlockBasedOptionsPy>>,
+    ) -> PyResult<()> {
+        if prefix_len == 0 {
+            return Err(PyValueError::new_err("prefix_len must be at least 1"));
+        }
+        self.set_prefix_extractor(&SliceTransformPy(SliceTransformType::Fixed(prefix_len)))?;
+        self.set_memtable_prefix_bloom_ratio(0.1);
+        self.set_memtable_whole_key_filtering(true);
+        let mut configure = |table_options: &mut BlockBasedOptionsPy| {
+            table_options.set_bloom_filter(bloom_bits, false);
+            table_options
+                .0
+                .set_index_type(BlockBasedIndexType::HashSearch);
+            self.inner_opt
+                .set_block_based_table_factory(&table_options.0);
+        };
+        match table_options {
+            Some(mut table_options) => configure(&mut table_options),
+            None => configure(&mut BlockBasedOptionsPy::default()),
+        }
+        Ok(())
+    }
+
+    /// Order the keys with a comparator implemented in Rust, which is much
+    /// faster than a comparator written in Python.
+    ///
+    /// Available comparators:
+    ///  - `"bytewise"`: the default, keys are ordered bytewise.
+    ///  - `"reverse_bytewise"`: keys are ordered bytewise, from the largest
+    ///    to the smallest, e.g. to iterate forward over keys starting with
+    ///    big-endian timestamps from the newest to the oldest.
+    ///
+    /// Notes:
+    ///     This is only supported in raw mode, since non-raw mode uses
+    ///     its own comparator. The comparator is saved with the database
+    ///     and restored when it is reopened without options. RocksDB
+    ///     refuses to open a column family with another comparator.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options
+    ///
+    ///         opt = Options(raw_mode=True)
+    ///         opt.set_builtin_comparator("reverse_bytewise")
+    ///         db = Rdict("./test_dir", opt)
+    ///         db[b"a"] = b"1"
+    ///         db[b"b"] = b"2"
+    ///         assert list(db.keys()) == [b"b", b"a"]
+    ///
+    /// Args:
+    ///     name: `"bytewise"` or `"reverse_bytewise"`.
+    pub fn set_builtin_comparator(&mut self, name: &str) -> PyResult<()> {
+        if !self.raw_mode {
+            return Err(PyValueError::new_err(
+                "builtin comparators are only supported in raw mode",
+            ));
+        }
+        let comparator = BuiltinComparator::parse(name)?;
+        OptionsPy::set_builtin_comparator_inner(&mut self.inner_opt, comparator);
+        self.comparator = Some(comparator);
+        self.python_comparator = None;
+        Ok(())
+    }
+
+    /// Enable user-defined timestamps, to keep several versions of each
+    /// key, each written at an integer timestamp (an unsigned 64-bit int).
+    ///
+    /// Keys are ordered bytewise, with the same name and order as
+    /// RocksDB's `BytewiseComparatorWithU64Ts`.
+    ///
+    /// Notes:
+    ///     This is only supported in raw mode, and must be set every time
+    ///     the column family is opened. Writes must use
+    ///     `Rdict.put_with_ts` and `Rdict.delete_with_ts`, and reads must
+    ///     set `ReadOptions.set_timestamp`.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options, ReadOptions
+    ///
+    ///         opt = Options(raw_mode=True)
+    ///         opt.enable_user_timestamp()
+    ///         db = Rdict("./test_dir", opt)
+    ///         db.put_with_ts(b"key", 1, b"value")
+    ///         read_opt = ReadOptions(raw_mode=True)
+    ///         read_opt.set_timestamp(1)
+    ///         assert db.get(b"key", read_opt=read_opt) == b"value"
+    pub fn enable_user_timestamp(&mut self) -> PyResult<()> {
+        if !self.raw_mode {
+            return Err(PyValueError::new_err(
+                "user-defined timestamps are only supported in raw mode",
+            ));
+        }
+        self.inner_opt.set_comparator_with_ts(
+            "leveldb.BytewiseComparator.u64ts",
+            U64_TS_SIZE,
+            Box::new(compare_keys_with_u64_ts),
+            Box::new(compare_u64_ts),
+            Box::new(compare_keys_without_u64_ts),
+        );
+        Ok(())
+    }
+
     // pub fn add_comparator(&mut self, name: &str, compare_fn: CompareFn) {
     //     self.0.add_comparator(name, compare_fn)
     // }
@@ -1055,6 +1900,11 @@ impl OptionsPy {
         self.inner_opt.set_max_open_files(nfiles)
     }
 
+    /// Returns the value of the `max_open_files` option.
+    pub fn get_max_open_files(&self) -> c_int {
+        unsafe { librocksdb_sys::rocksdb_options_get_max_open_files(self.inner_opt.inner()) }
+    }
+
     /// If max_open_files is -1, DB will open all files on DB::Open(). You can
     /// use this option to increase the number of threads used to open the files.
     /// Default: 16
@@ -1072,6 +1922,11 @@ impl OptionsPy {
         self.inner_opt.set_use_fsync(useit)
     }
 
+    /// Returns the value of the `use_fsync` option.
+    pub fn get_use_fsync(&self) -> bool {
+        self.inner_opt.get_use_fsync()
+    }
+
     /// Specifies the absolute info LOG dir.
     ///
     /// If it is empty, the log files will be in the same dir as data.
@@ -1206,6 +2061,13 @@ impl OptionsPy {
         self.inner_opt.set_target_file_size_multiplier(multiplier)
     }
 
+    /// Returns the value of the `target_file_size_multiplier` option.
+    pub fn get_target_file_size_multiplier(&self) -> i32 {
+        unsafe {
+            librocksdb_sys::rocksdb_options_get_target_file_size_multiplier(self.inner_opt.inner())
+        }
+    }
+
     /// Sets the minimum number of write buffers that will be merged together
     /// before writing to storage.  If set to `1`, then
     /// all write buffers are flushed to L0 as individual files and this increases
@@ -1232,6 +2094,13 @@ impl OptionsPy {
         self.inner_opt.set_max_write_buffer_number(nbuf)
     }
 
+    /// Returns the value of the `max_write_buffer_number` option.
+    pub fn get_max_write_buffer_number(&self) -> c_int {
+        unsafe {
+            librocksdb_sys::rocksdb_options_get_max_write_buffer_number(self.inner_opt.inner())
+        }
+    }
+
     /// Sets the amount of data to build up in memory (backed by an unsorted log
     /// on disk) before converting to a sorted on-disk file.
     ///
@@ -1252,6 +2121,11 @@ impl OptionsPy {
         self.inner_opt.set_write_buffer_size(size)
     }
 
+    /// Returns the value of the `write_buffer_size` option.
+    pub fn get_write_buffer_size(&self) -> usize {
+        unsafe { librocksdb_sys::rocksdb_options_get_write_buffer_size(self.inner_opt.inner()) }
+    }
+
     /// Amount of data to build up in memtables across all column
     /// families before writing to disk.
     ///
@@ -1282,11 +2156,27 @@ impl OptionsPy {
         self.inner_opt.set_max_bytes_for_level_base(size)
     }
 
+    /// Returns the value of the `max_bytes_for_level_base` option.
+    pub fn get_max_bytes_for_level_base(&self) -> u64 {
+        unsafe {
+            librocksdb_sys::rocksdb_options_get_max_bytes_for_level_base(self.inner_opt.inner())
+        }
+    }
+
     /// Default: `10`
     pub fn set_max_bytes_for_level_multiplier(&mut self, mul: f64) {
         self.inner_opt.set_max_bytes_for_level_multiplier(mul)
     }
 
+    /// Returns the value of the `max_bytes_for_level_multiplier` option.
+    pub fn get_max_bytes_for_level_multiplier(&self) -> f64 {
+        unsafe {
+            librocksdb_sys::rocksdb_options_get_max_bytes_for_level_multiplier(
+                self.inner_opt.inner(),
+            )
+        }
+    }
+
     /// The manifest file is rolled over on reaching this limit.
     /// The older manifest file be deleted.
     /// The default value is MAX_INT so that roll-over does not take place.
@@ -1310,6 +2200,11 @@ impl OptionsPy {
         self.inner_opt.set_target_file_size_base(size)
     }
 
+    /// Returns the value of the `target_file_size_base` option.
+    pub fn get_target_file_size_base(&self) -> u64 {
+        unsafe { librocksdb_sys::rocksdb_options_get_target_file_size_base(self.inner_opt.inner()) }
+    }
+
     /// Sets the minimum number of write buffers that will be merged together
     /// before writing to storage.  If set to `1`, then
     /// all write buffers are flushed to L0 as individual files and this increases
@@ -1324,6 +2219,15 @@ impl OptionsPy {
             .set_min_write_buffer_number_to_merge(to_merge)
     }
 
+    /// Returns the value of the `min_write_buffer_number_to_merge` option.
+    pub fn get_min_write_buffer_number_to_merge(&self) -> c_int {
+        unsafe {
+            librocksdb_sys::rocksdb_options_get_min_write_buffer_number_to_merge(
+                self.inner_opt.inner(),
+            )
+        }
+    }
+
     /// Sets the number of files to trigger level-0 compaction. A value < `0` means that
     /// level-0 compaction will not be triggered by number of files at all.
     ///
@@ -1334,6 +2238,15 @@ impl OptionsPy {
         self.inner_opt.set_level_zero_file_num_compaction_trigger(n)
     }
 
+    /// Returns the value of the `level0_file_num_compaction_trigger` option.
+    pub fn get_level_zero_file_num_compaction_trigger(&self) -> c_int {
+        unsafe {
+            librocksdb_sys::rocksdb_options_get_level0_file_num_compaction_trigger(
+                self.inner_opt.inner(),
+            )
+        }
+    }
+
     /// Sets the soft limit on number of level-0 files. We start slowing down writes at this
     /// point. A value < `0` means that no writing slow down will be triggered by
     /// number of files in level-0.
@@ -1345,6 +2258,15 @@ impl OptionsPy {
         self.inner_opt.set_level_zero_slowdown_writes_trigger(n)
     }
 
+    /// Returns the value of the `level0_slowdown_writes_trigger` option.
+    pub fn get_level_zero_slowdown_writes_trigger(&self) -> c_int {
+        unsafe {
+            librocksdb_sys::rocksdb_options_get_level0_slowdown_writes_trigger(
+                self.inner_opt.inner(),
+            )
+        }
+    }
+
     /// Sets the maximum number of level-0 files.  We stop writes at this point.
     ///
     /// Default: `24`
@@ -1354,6 +2276,13 @@ impl OptionsPy {
         self.inner_opt.set_level_zero_stop_writes_trigger(n)
     }
 
+    /// Returns the value of the `level0_stop_writes_trigger` option.
+    pub fn get_level_zero_stop_writes_trigger(&self) -> c_int {
+        unsafe {
+            librocksdb_sys::rocksdb_options_get_level0_stop_writes_trigger(self.inner_opt.inner())
+        }
+    }
+
     /// Sets the compaction style.
     ///
     /// Default: DBCompactionStyle.level()
@@ -1361,12 +2290,40 @@ impl OptionsPy {
         self.inner_opt.set_compaction_style(style.0)
     }
 
+    /// Sets the order in which files are picked for compaction
+    /// in level compaction.
+    ///
+    /// Default: CompactionPri.min_overlapping_ratio()
+    pub fn set_compaction_pri(&mut self, pri: CompactionPriPy) {
+        self.inner_opt.set_compaction_pri(pri.0)
+    }
+
     /// Sets the options needed to support Universal Style compactions.
+    ///
+    /// Notes:
+    ///     This does not change the compaction style, also call
+    ///     `set_compaction_style(DBCompactionStyle.universal())`.
     pub fn set_universal_compaction_options(&mut self, uco: &UniversalCompactOptionsPy) {
         self.inner_opt.set_universal_compaction_options(&uco.into())
     }
 
     /// Sets the options for FIFO compaction style.
+    ///
+    /// Notes:
+    ///     This does not change the compaction style, also call
+    ///     `set_compaction_style(DBCompactionStyle.fifo())`.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Options, DBCompactionStyle, FifoCompactOptions
+    ///
+    ///         opt = Options()
+    ///         fifo = FifoCompactOptions()
+    ///         # keep at most 1GB of SST files
+    ///         fifo.max_table_files_size = 1024 * 1024 * 1024
+    ///         opt.set_compaction_style(DBCompactionStyle.fifo())
+    ///         opt.set_fifo_compaction_options(fifo)
     pub fn set_fifo_compaction_options(&mut self, fco: &FifoCompactOptionsPy) {
         self.inner_opt.set_fifo_compaction_options(&fco.into())
     }
@@ -1408,6 +2365,11 @@ impl OptionsPy {
         self.inner_opt.set_max_subcompactions(num)
     }
 
+    /// Returns the value of the `max_subcompactions` option.
+    pub fn get_max_subcompactions(&self) -> u32 {
+        unsafe { librocksdb_sys::rocksdb_options_get_max_subcompactions(self.inner_opt.inner()) }
+    }
+
     /// Sets maximum number of concurrent background jobs
     /// (compactions and flushes).
     ///
@@ -1418,6 +2380,11 @@ impl OptionsPy {
         self.inner_opt.set_max_background_jobs(jobs)
     }
 
+    /// Returns the value of the `max_background_jobs` option.
+    pub fn get_max_background_jobs(&self) -> c_int {
+        unsafe { librocksdb_sys::rocksdb_options_get_max_background_jobs(self.inner_opt.inner()) }
+    }
+
     /// Disables automatic compactions. Manual compactions can still
     /// be issued on this column family
     ///
@@ -1428,6 +2395,14 @@ impl OptionsPy {
         self.inner_opt.set_disable_auto_compactions(disable)
     }
 
+    /// Returns the value of the `disable_auto_compactions` option.
+    pub fn get_disable_auto_compactions(&self) -> bool {
+        unsafe {
+            librocksdb_sys::rocksdb_options_get_disable_auto_compactions(self.inner_opt.inner())
+                != 0
+        }
+    }
+
     /// SetMemtableHugePageSize sets the page size for huge page for
     /// arena used by the memtable.
     /// If <=0, it won't allocate from huge page but from malloc.
@@ -1573,7 +2548,7 @@ impl OptionsPy {
     /// Example:
     ///     ::
     ///
-    ///         from rocksdict import Options, MemtableFactory
+    ///         from rocksdict import Options, MemtableFactory, SliceTransform
     ///         opts = Options()
     ///         factory = MemtableFactory.hash_skip_list(bucket_count=1_000_000,
     ///                                                  height=4,
@@ -1581,7 +2556,13 @@ impl OptionsPy {
     ///
     ///         opts.set_allow_concurrent_memtable_write(false)
     ///         opts.set_memtable_factory(factory)
+    ///         opts.set_prefix_extractor(SliceTransform.create_fixed_prefix(4))
+    ///
+    /// Notes:
+    ///     `hash_skip_list` and `hash_link_list` require a prefix extractor,
+    ///     `Rdict` raises an exception at open if none is set.
     pub fn set_memtable_factory(&mut self, factory: &MemtableFactoryPy) {
+        self.hash_memtable = !matches!(factory.0, MemtableFactory::Vector);
         self.inner_opt.set_memtable_factory(match factory.0 {
             MemtableFactory::Vector => MemtableFactory::Vector,
             MemtableFactory::HashSkipList {
@@ -1684,10 +2665,61 @@ impl OptionsPy {
         self.inner_opt.enable_statistics()
     }
 
+    /// Return the statistics collected since `enable_statistics`
+    /// as a string, or None if statistics are not enabled.
+    ///
+    /// Notes:
+    ///     The counters are cumulative, and the RocksDB C API has no way
+    ///     to reset them. To measure a window, keep the previous result
+    ///     and subtract it from the current one.
     pub fn get_statistics(&self) -> Option<String> {
         self.inner_opt.get_statistics()
     }
 
+    /// Return the value of a statistics ticker (a counter).
+    ///
+    /// Args:
+    ///     ticker_name: the name of the ticker, as in `get_statistics`
+    ///         (e.g. `"rocksdb.block.cache.hit"` or `"rocksdb.bytes.written"`),
+    ///         or in upper case (e.g. `"BLOCK_CACHE_HIT"` or `"BYTES_WRITTEN"`).
+    ///
+    /// Returns:
+    ///     the count, 0 if statistics are not enabled.
+    pub fn get_ticker_count(&self, ticker_name: &str) -> PyResult<u64> {
+        let ticker = parse_statistics_name(ticker_name, Ticker::iter(), "ticker")?;
+        Ok(self.inner_opt.get_ticker_count(ticker))
+    }
+
+    /// Return the data of a statistics histogram.
+    ///
+    /// Args:
+    ///     histogram_name: the name of the histogram, as in `get_statistics`
+    ///         (e.g. `"rocksdb.db.get.micros"`), or in upper case
+    ///         (e.g. `"DB_GET"`).
+    ///
+    /// Returns:
+    ///     a dict with keys `median`, `average`, `p95`, `p99`, `max`, `min`,
+    ///     `sum`, `count` and `std_dev`.
+    pub fn get_histogram_data<'py>(
+        &self,
+        histogram_name: &str,
+        py: Python<'py>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let histogram = parse_statistics_name(histogram_name, Histogram::iter(), "histogram")?;
+        let data = self.inner_opt.get_histogram_data(histogram);
+        let result = PyDict::new(py);
+        result.set_item("median", data.median())?;
+        result.set_item("average", data.average())?;
+        result.set_item("p95", data.p95())?;
+        result.set_item("p99", data.p99())?;
+        result.set_item("max", data.max())?;
+        result.set_item("min", data.min())?;
+        result.set_item("sum", data.sum())?;
+        result.set_item("count", data.count())?;
+        result.set_item("std_dev", data.std_dev())?;
+        Ok(result)
+    }
+
     /// If not zero, dump `rocksdb.stats` to LOG every `stats_dump_period_sec`.
     ///
     /// Default: `600` (10 mins)
@@ -1711,9 +2743,9 @@ impl OptionsPy {
         self.inner_opt.set_advise_random_on_open(advise)
     }
 
-    // pub fn set_access_hint_on_compaction_start(&mut self, pattern: AccessHint) {
-    //     self.inner_opt.set_access_hint_on_compaction_start(pattern)
-    // }
+    // `access_hint_on_compaction_start` was removed in RocksDB 9.0
+    // (compaction inputs are read with the default access pattern),
+    // so `set_access_hint_on_compaction_start` cannot be bound.
 
     /// Enable/disable adaptive mutex, which spins in the user space before resorting to kernel.
     ///
@@ -1731,6 +2763,11 @@ impl OptionsPy {
         self.inner_opt.set_num_levels(n)
     }
 
+    /// Returns the value of the `num_levels` option.
+    pub fn get_num_levels(&self) -> c_int {
+        unsafe { librocksdb_sys::rocksdb_options_get_num_levels(self.inner_opt.inner()) }
+    }
+
     /// When a `prefix_extractor` is defined through `opts.set_prefix_extractor` this
     /// creates a prefix bloom filter for each memtable with the size of
     /// `write_buffer_size * memtable_prefix_bloom_ratio` (capped at 0.25).
@@ -1751,6 +2788,11 @@ impl OptionsPy {
         self.inner_opt.set_max_compaction_bytes(nbytes)
     }
 
+    /// Returns the value of the `max_compaction_bytes` option.
+    pub fn get_max_compaction_bytes(&self) -> u64 {
+        unsafe { librocksdb_sys::rocksdb_options_get_max_compaction_bytes(self.inner_opt.inner()) }
+    }
+
     /// Specifies the absolute path of the directory the
     /// write-ahead log (WAL) should be written to.
     ///
@@ -2178,6 +3220,11 @@ impl ReadOptionsPy {
             tailing: false,
             pin_data: false,
             async_io: false,
+            auto_readahead_size: true,
+            auto_total_order_seek: false,
+            snapshot: None,
+            timestamp: None,
+            iter_start_ts: None,
         })
     }
 
@@ -2198,7 +3245,7 @@ impl ReadOptionsPy {
 
     /// Sets the lower bound for an iterator.
     pub fn set_iterate_lower_bound(&mut self, key: Bound<PyAny>) -> PyResult<()> {
-        self.iterate_upper_bound = key.into_any().unbind();
+        self.iterate_lower_bound = key.into_any().unbind();
         Ok(())
     }
 
@@ -2225,6 +3272,19 @@ impl ReadOptionsPy {
         self.total_order_seek = v
     }
 
+    /// Automatically use total order seek when an iterate bound is set.
+    ///
+    /// With a prefix extractor configured, seeks are prefix seeks by default,
+    /// so an iterator with `iterate_lower_bound` / `iterate_upper_bound`
+    /// stops at the end of the first prefix instead of scanning the
+    /// whole range. With this option, setting any iterate bound also enables
+    /// `total_order_seek`, so bounded range scans return every key in range.
+    ///
+    /// Default: false
+    pub fn set_auto_total_order_seek(&mut self, v: bool) {
+        self.auto_total_order_seek = v
+    }
+
     /// Sets a threshold for the number of keys that can be skipped
     /// before failing an iterator seek as incomplete. The default value of 0 should be used to
     /// never fail a request as incomplete, even on skipping too many keys.
@@ -2299,22 +3359,115 @@ impl ReadOptionsPy {
     pub fn set_async_io(&mut self, v: bool) {
         self.async_io = v
     }
+
+    /// Automatically tune the readahead size during forward scans.
+    /// It only takes effect when `set_iterate_upper_bound` is set,
+    /// and is disabled for the rest of the iteration after a backward
+    /// move of the iterator.
+    ///
+    /// Default: true
+    pub fn set_auto_readahead_size(&mut self, v: bool) {
+        self.auto_readahead_size = v
+    }
+
+    /// Read the database as of `snapshot`, instead of its latest state.
+    /// Pass `None` to unset it.
+    ///
+    /// Notes:
+    ///     The snapshot is kept alive as long as these read options, and
+    ///     as long as the iterators created with them. It must be taken
+    ///     from the database these options are used with.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, ReadOptions
+    ///
+    ///         db = Rdict("./test_dir")
+    ///         db["a"] = 1
+    ///         read_opt = ReadOptions()
+    ///         read_opt.set_snapshot(db.snapshot())
+    ///         db["a"] = 2
+    ///         assert db.get("a", read_opt=read_opt) == 1
+    ///         assert list(db.values(read_opt=read_opt)) == [1]
+    #[pyo3(signature = (snapshot))]
+    pub fn set_snapshot(&mut self, snapshot: Option<Py<Snapshot>>) {
+        self.snapshot = snapshot
+    }
+
+    /// Read the latest versions visible at timestamp `ts`, for column
+    /// families with user-defined timestamps (see
+    /// `Options.enable_user_timestamp`). Pass `None` to unset it.
+    ///
+    /// Reads of such column families must set a timestamp, use
+    /// `2 ** 64 - 1` to read the latest versions.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options, ReadOptions
+    ///
+    ///         opt = Options(raw_mode=True)
+    ///         opt.enable_user_timestamp()
+    ///         db = Rdict("./test_dir", opt)
+    ///         db.put_with_ts(b"key", 1, b"old")
+    ///         db.put_with_ts(b"key", 2, b"new")
+    ///         read_opt = ReadOptions(raw_mode=True)
+    ///         read_opt.set_timestamp(1)
+    ///         assert db.get(b"key", read_opt=read_opt) == b"old"
+    #[pyo3(signature = (ts))]
+    pub fn set_timestamp(&mut self, ts: Option<u64>) {
+        self.timestamp = ts
+    }
+
+    /// Make iterators return all the versions with a timestamp between
+    /// `ts` and the timestamp of `set_timestamp` (both included),
+    /// from the newest to the oldest version of each key, instead of
+    /// only the latest one. Pass `None` to unset it.
+    ///
+    /// Notes:
+    ///     The iterator keys are then RocksDB internal keys: the key,
+    ///     followed by its 8-byte little-endian timestamp, and 8 bytes
+    ///     of sequence number and value type.
+    #[pyo3(signature = (ts))]
+    pub fn set_iter_start_ts(&mut self, ts: Option<u64>) {
+        self.iter_start_ts = ts
+    }
 }
 
 impl ReadOptionsPy {
-    pub(crate) fn to_read_options(&self, raw_mode: bool, py: Python) -> PyResult<ReadOptions> {
+    fn total_order_seek_enabled(&self, py: Python) -> bool {
+        self.total_order_seek
+            || (self.auto_total_order_seek
+                && !(self.iterate_lower_bound.is_none(py) && self.iterate_upper_bound.is_none(py)))
+    }
+
+    pub(crate) fn to_read_options(
+        &self,
+        raw_mode: bool,
+        normalize_str_bytes_keys: bool,
+        py: Python,
+    ) -> PyResult<ReadOptions> {
         let mut opt = ReadOptions::default();
         opt.fill_cache(self.fill_cache);
         if !self.iterate_lower_bound.is_none(py) {
-            let lower_bound = encode_key(self.iterate_lower_bound.bind(py), raw_mode)?;
+            let lower_bound = encode_key(
+                self.iterate_lower_bound.bind(py),
+                raw_mode,
+                normalize_str_bytes_keys,
+            )?;
             opt.set_iterate_lower_bound(lower_bound);
         }
         if !self.iterate_upper_bound.is_none(py) {
-            let upper_bound = encode_key(self.iterate_upper_bound.bind(py), raw_mode)?;
+            let upper_bound = encode_key(
+                self.iterate_upper_bound.bind(py),
+                raw_mode,
+                normalize_str_bytes_keys,
+            )?;
             opt.set_iterate_upper_bound(upper_bound);
         }
         opt.set_prefix_same_as_start(self.prefix_same_as_start);
-        opt.set_total_order_seek(self.total_order_seek);
+        opt.set_total_order_seek(self.total_order_seek_enabled(py));
         opt.set_max_skippable_internal_keys(self.max_skippable_internal_keys);
         opt.set_background_purge_on_iterator_cleanup(self.background_purge_on_iterator_cleanup);
         opt.set_ignore_range_deletions(self.ignore_range_deletions);
@@ -2323,13 +3476,42 @@ impl ReadOptionsPy {
         opt.set_tailing(self.tailing);
         opt.set_pin_data(self.pin_data);
         opt.set_async_io(self.async_io);
+        opt.set_auto_readahead_size(self.auto_readahead_size);
+        if let Some(snapshot) = &self.snapshot {
+            unsafe {
+                set_snapshot(opt.inner(), snapshot.borrow(py).inner);
+            }
+        }
+        if let Some(ts) = self.timestamp {
+            opt.set_timestamp(encode_u64_ts(ts));
+        }
+        if let Some(ts) = self.iter_start_ts {
+            opt.set_iter_start_ts(encode_u64_ts(ts));
+        }
         Ok(opt)
     }
 
-    pub(crate) fn to_read_opt(&self, raw_mode: bool, py: Python) -> PyResult<ReadOpt> {
-        let opt = unsafe { ReadOpt(librocksdb_sys::rocksdb_readoptions_create()) };
+    pub(crate) fn to_read_opt(
+        &self,
+        raw_mode: bool,
+        normalize_str_bytes_keys: bool,
+        py: Python,
+    ) -> PyResult<ReadOpt> {
+        let mut opt = unsafe {
+            ReadOpt(
+                librocksdb_sys::rocksdb_readoptions_create(),
+                ReadOptData {
+                    buffers: Vec::new(),
+                    snapshot: self.snapshot.as_ref().map(|s| s.clone_ref(py)),
+                },
+            )
+        };
         if !self.iterate_lower_bound.is_none(py) {
-            let lower_bound = encode_key(self.iterate_lower_bound.bind(py), raw_mode)?;
+            let lower_bound = encode_key(
+                self.iterate_lower_bound.bind(py),
+                raw_mode,
+                normalize_str_bytes_keys,
+            )?;
 
             unsafe {
                 librocksdb_sys::rocksdb_readoptions_set_iterate_lower_bound(
@@ -2338,9 +3520,14 @@ impl ReadOptionsPy {
                     lower_bound.len() as size_t,
                 );
             }
+            opt.1.buffers.push(lower_bound.into_owned());
         }
         if !self.iterate_upper_bound.is_none(py) {
-            let upper_bound = encode_key(self.iterate_upper_bound.bind(py), raw_mode)?;
+            let upper_bound = encode_key(
+                self.iterate_upper_bound.bind(py),
+                raw_mode,
+                normalize_str_bytes_keys,
+            )?;
 
             unsafe {
                 librocksdb_sys::rocksdb_readoptions_set_iterate_upper_bound(
@@ -2349,6 +3536,7 @@ impl ReadOptionsPy {
                     upper_bound.len() as size_t,
                 );
             }
+            opt.1.buffers.push(upper_bound.into_owned());
         }
         unsafe {
             librocksdb_sys::rocksdb_readoptions_set_fill_cache(opt.0, self.fill_cache as c_uchar);
@@ -2358,7 +3546,7 @@ impl ReadOptionsPy {
             );
             librocksdb_sys::rocksdb_readoptions_set_total_order_seek(
                 opt.0,
-                self.total_order_seek as c_uchar,
+                self.total_order_seek_enabled(py) as c_uchar,
             );
             librocksdb_sys::rocksdb_readoptions_set_max_skippable_internal_keys(
                 opt.0,
@@ -2382,9 +3570,57 @@ impl ReadOptionsPy {
             );
             librocksdb_sys::rocksdb_readoptions_set_tailing(opt.0, self.tailing as c_uchar);
             librocksdb_sys::rocksdb_readoptions_set_pin_data(opt.0, self.pin_data as c_uchar);
+            librocksdb_sys::rocksdb_readoptions_set_async_io(opt.0, self.async_io as c_uchar);
+            librocksdb_sys::rocksdb_readoptions_set_auto_readahead_size(
+                opt.0,
+                self.auto_readahead_size as c_uchar,
+            );
+            if let Some(snapshot) = &opt.1.snapshot {
+                set_snapshot(opt.0, snapshot.borrow(py).inner);
+            }
+        }
+        if let Some(ts) = self.timestamp {
+            let ts = encode_u64_ts(ts).to_vec();
+            unsafe {
+                librocksdb_sys::rocksdb_readoptions_set_timestamp(
+                    opt.0,
+                    ts.as_ptr() as *const c_char,
+                    ts.len() as size_t,
+                );
+            }
+            opt.1.buffers.push(ts);
+        }
+        if let Some(ts) = self.iter_start_ts {
+            let ts = encode_u64_ts(ts).to_vec();
+            unsafe {
+                librocksdb_sys::rocksdb_readoptions_set_iter_start_ts(
+                    opt.0,
+                    ts.as_ptr() as *const c_char,
+                    ts.len() as size_t,
+                );
+            }
+            opt.1.buffers.push(ts);
         }
         Ok(opt)
     }
+
+    /// The snapshot must be released by the database it was taken from.
+    pub(crate) fn check_snapshot(&self, db: &DbReference, py: Python) -> PyResult<()> {
+        match &self.snapshot {
+            Some(snapshot)
+                if !snapshot
+                    .borrow(py)
+                    .db
+                    .get_unchecked()
+                    .is_some_and(|snapshot_db| Arc::ptr_eq(snapshot_db, db)) =>
+            {
+                Err(PyValueError::new_err(
+                    "the snapshot of these read options is not from this database",
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 unsafe impl Send for ReadOpt {}
@@ -2474,6 +3710,59 @@ impl BlockBasedOptionsPy {
         self.0.set_bloom_filter(bits_per_key, block_based)
     }
 
+    /// Sets a [Ribbon filter](http://rocksdb.org/blog/2021/12/29/ribbon-filter.html)
+    /// policy to reduce disk reads.
+    ///
+    /// Ribbon filters use about 30% less memory than a bloom filter with the
+    /// same false positive rate, in exchange for slightly more CPU usage.
+    ///
+    /// Args:
+    ///     bloom_equivalent_bits_per_key (float): the bits per key of the
+    ///         bloom filter with the same false positive rate.
+    pub fn set_ribbon_filter(&mut self, bloom_equivalent_bits_per_key: c_double) {
+        self.0.set_ribbon_filter(bloom_equivalent_bits_per_key)
+    }
+
+    /// Sets a hybrid Ribbon filter policy: bloom filters are used for the
+    /// levels before `bloom_before_level`, and Ribbon filters for the others.
+    ///
+    /// This saves memory on the large last levels, while keeping the lower
+    /// CPU usage of bloom filters for the short-lived files of the first levels.
+    ///
+    /// Args:
+    ///     bloom_equivalent_bits_per_key (float): the bits per key of the
+    ///         bloom filter with the same false positive rate.
+    ///     bloom_before_level (int): the first level using a Ribbon filter.
+    ///         Use -1 to always use bloom filters, and 0 to use Ribbon filters
+    ///         except for flushes.
+    pub fn set_ribbon_filter_with_bloom_fallback(
+        &mut self,
+        bloom_equivalent_bits_per_key: c_double,
+        bloom_before_level: c_int,
+    ) {
+        self.0
+            .set_hybrid_ribbon_filter(bloom_equivalent_bits_per_key, bloom_before_level)
+    }
+
+    /// If true, generate Bloom/Ribbon filters that minimize memory internal
+    /// fragmentation.
+    /// See official [wiki](
+    /// https://github.com/facebook/rocksdb/wiki/RocksDB-Bloom-Filter#reducing-internal-fragmentation)
+    /// for more information.
+    ///
+    /// Defaults to false.
+    pub fn set_optimize_filters_for_memory(&mut self, v: bool) {
+        self.0.set_optimize_filters_for_memory(v)
+    }
+
+    /// If false, place only prefixes in the filter, not whole keys.
+    /// Use `Options.set_memtable_whole_key_filtering` for the memtable filter.
+    ///
+    /// Defaults to true.
+    pub fn set_whole_key_filtering(&mut self, v: bool) {
+        self.0.set_whole_key_filtering(v)
+    }
+
     pub fn set_cache_index_and_filter_blocks(&mut self, v: bool) {
         self.0.set_cache_index_and_filter_blocks(v)
     }
@@ -2690,7 +3979,10 @@ impl CachePy {
     /// Create a lru cache with capacity
     #[new]
     pub fn new_lru_cache(capacity: size_t) -> CachePy {
-        CachePy(Cache::new_lru_cache(capacity))
+        CachePy(
+            Cache::new_lru_cache(capacity),
+            Arc::new(AtomicUsize::new(capacity)),
+        )
     }
 
     /// Creates a HyperClockCache with capacity in bytes.
@@ -2715,10 +4007,10 @@ impl CachePy {
     /// errs towards this side.
     #[staticmethod]
     pub fn new_hyper_clock_cache(capacity: size_t, estimated_entry_charge: size_t) -> CachePy {
-        CachePy(Cache::new_hyper_clock_cache(
-            capacity,
-            estimated_entry_charge,
-        ))
+        CachePy(
+            Cache::new_hyper_clock_cache(capacity, estimated_entry_charge),
+            Arc::new(AtomicUsize::new(capacity)),
+        )
     }
 
     /// Returns the Cache memory usage
@@ -2733,7 +4025,13 @@ impl CachePy {
 
     /// Sets cache capacity
     pub fn set_capacity(&mut self, capacity: size_t) {
-        self.0.set_capacity(capacity)
+        self.0.set_capacity(capacity);
+        self.1.store(capacity, atomic::Ordering::Relaxed);
+    }
+
+    /// Returns the cache capacity in bytes
+    pub fn get_capacity(&self) -> usize {
+        self.1.load(atomic::Ordering::Relaxed)
     }
 }
 
@@ -2810,6 +4108,18 @@ impl DBPathPy {
             target_size,
         }
     }
+
+    /// The directory of this path.
+    #[getter]
+    pub fn path(&self) -> String {
+        self.path.to_string_lossy().into_owned()
+    }
+
+    /// The target size in bytes of the data placed in this path.
+    #[getter]
+    pub fn target_size(&self) -> u64 {
+        self.target_size
+    }
 }
 
 #[pymethods]
@@ -2868,6 +4178,34 @@ impl DBCompactionStylePy {
     }
 }
 
+#[pymethods]
+impl CompactionPriPy {
+    #[staticmethod]
+    pub fn by_compensated_size() -> Self {
+        CompactionPriPy(CompactionPri::ByCompensatedSize)
+    }
+
+    #[staticmethod]
+    pub fn oldest_largest_seq_first() -> Self {
+        CompactionPriPy(CompactionPri::OldestLargestSeqFirst)
+    }
+
+    #[staticmethod]
+    pub fn oldest_smallest_seq_first() -> Self {
+        CompactionPriPy(CompactionPri::OldestSmallestSeqFirst)
+    }
+
+    #[staticmethod]
+    pub fn min_overlapping_ratio() -> Self {
+        CompactionPriPy(CompactionPri::MinOverlappingRatio)
+    }
+
+    #[staticmethod]
+    pub fn round_robin() -> Self {
+        CompactionPriPy(CompactionPri::RoundRobin)
+    }
+}
+
 #[pymethods]
 impl ChecksumTypePy {
     #[staticmethod]
@@ -2926,7 +4264,7 @@ impl EnvPy {
     pub fn default() -> PyResult<Self> {
         match Env::new() {
             Ok(env) => Ok(EnvPy(env)),
-            Err(e) => Err(PyException::new_err(e.into_string())),
+            Err(e) => Err(rocksdb_error(e)),
         }
     }
 
@@ -2936,7 +4274,7 @@ impl EnvPy {
     pub fn mem_env() -> PyResult<Self> {
         match Env::mem_env() {
             Ok(env) => Ok(EnvPy(env)),
-            Err(e) => Err(PyException::new_err(e.into_string())),
+            Err(e) => Err(rocksdb_error(e)),
         }
     }
 
@@ -3126,7 +4464,13 @@ impl BottommostLevelCompactionPy {
 impl CompactOptionsPy {
     #[new]
     pub fn default() -> Self {
-        CompactOptionsPy(CompactOptions::default())
+        CompactOptionsPy {
+            inner: CompactOptions::default(),
+            exclusive_manual_compaction: None,
+            bottommost_level_compaction: None,
+            change_level: None,
+            target_level: None,
+        }
     }
 
     /// If more than one thread calls manual compaction,
@@ -3135,24 +4479,54 @@ impl CompactOptionsPy {
     /// is set to true, the call will disable scheduling of automatic compaction jobs
     /// and wait for existing automatic compaction jobs to finish.
     pub fn set_exclusive_manual_compaction(&mut self, v: bool) {
-        self.0.set_exclusive_manual_compaction(v)
+        self.exclusive_manual_compaction = Some(v);
+        self.inner.set_exclusive_manual_compaction(v)
     }
 
     /// Sets bottommost level compaction.
+    ///
+    /// By default (`BottommostLevelCompaction.if_have_compaction_filter()`),
+    /// the files already in the bottommost level are not rewritten unless
+    /// a compaction filter is set, so they keep their compression and
+    /// their deleted or overwritten entries. Use
+    /// `BottommostLevelCompaction.force()` to rewrite them.
     pub fn set_bottommost_level_compaction(&mut self, lvl: BottommostLevelCompactionPy) {
-        self.0.set_bottommost_level_compaction(lvl.0)
+        self.inner.set_bottommost_level_compaction(lvl.0);
+        self.bottommost_level_compaction = Some(lvl);
     }
 
     /// If true, compacted files will be moved to the minimum level capable
     /// of holding the data or given level (specified non-negative target_level).
     pub fn set_change_level(&mut self, v: bool) {
-        self.0.set_change_level(v)
+        self.change_level = Some(v);
+        self.inner.set_change_level(v)
     }
 
     /// If change_level is true and target_level have non-negative value, compacted
     /// files will be moved to target_level.
     pub fn set_target_level(&mut self, lvl: c_int) {
-        self.0.set_target_level(lvl)
+        self.target_level = Some(lvl);
+        self.inner.set_target_level(lvl)
+    }
+}
+
+impl CompactOptionsPy {
+    /// Returns a new CompactOptions with the same settings.
+    pub(crate) fn copy(&self) -> Self {
+        let mut opt = CompactOptionsPy::default();
+        if let Some(v) = self.exclusive_manual_compaction {
+            opt.set_exclusive_manual_compaction(v);
+        }
+        if let Some(lvl) = &self.bottommost_level_compaction {
+            opt.set_bottommost_level_compaction(lvl.clone());
+        }
+        if let Some(v) = self.change_level {
+            opt.set_change_level(v);
+        }
+        if let Some(lvl) = self.target_level {
+            opt.set_target_level(lvl);
+        }
+        opt
     }
 }
 
@@ -3213,6 +4587,172 @@ impl WriteBufferManagerPy {
     }
 }
 
+/// A Python function used as merge operator.
+struct PyMergeFn {
+    merge_fn: PyObject,
+    loads: PyObject,
+    dumps: PyObject,
+    raw_mode: bool,
+}
+
+impl PyMergeFn {
+    fn call(
+        &self,
+        key: &[u8],
+        existing_value: Option<&[u8]>,
+        operands: &MergeOperands,
+    ) -> Option<Vec<u8>> {
+        Python::with_gil(
+            |py| match self.call_inner(py, key, existing_value, operands) {
+                Ok(merged) => Some(merged),
+                Err(e) => {
+                    e.write_unraisable(py, Some(self.merge_fn.bind(py)));
+                    None
+                }
+            },
+        )
+    }
+
+    fn call_inner(
+        &self,
+        py: Python,
+        key: &[u8],
+        existing_value: Option<&[u8]>,
+        operands: &MergeOperands,
+    ) -> PyResult<Vec<u8>> {
+        let key = decode_value(py, key, &self.loads, self.raw_mode)?;
+        let existing_value = match existing_value {
+            None => py.None().into_bound(py),
+            Some(value) => decode_value(py, value, &self.loads, self.raw_mode)?,
+        };
+        let operands_list = PyList::empty(py);
+        for operand in operands {
+            operands_list.append(decode_value(py, operand, &self.loads, self.raw_mode)?)?;
+        }
+        let merged = self
+            .merge_fn
+            .call1(py, (key, existing_value, operands_list))?;
+        Ok(encode_value(merged.bind(py), &self.dumps, self.raw_mode)?.into_owned())
+    }
+}
+
+/// A Python function used as compaction filter.
+struct PyCompactionFilterFn {
+    filter_fn: PyObject,
+    loads: PyObject,
+    dumps: PyObject,
+    raw_mode: bool,
+    /// buffer of the last changed value, RocksDB copies it right after the callback
+    new_value: Vec<u8>,
+}
+
+impl PyCompactionFilterFn {
+    fn call(&mut self, level: u32, key: &[u8], value: &[u8]) -> CompactionDecision {
+        Python::with_gil(|py| match self.call_inner(py, level, key, value) {
+            Ok(decision) => decision,
+            Err(e) => {
+                e.write_unraisable(py, Some(self.filter_fn.bind(py)));
+                CompactionDecision::Keep
+            }
+        })
+    }
+
+    fn call_inner(
+        &mut self,
+        py: Python,
+        level: u32,
+        key: &[u8],
+        value: &[u8],
+    ) -> PyResult<CompactionDecision> {
+        let key = decode_value(py, key, &self.loads, self.raw_mode)?;
+        let value = decode_value(py, value, &self.loads, self.raw_mode)?;
+        let decision = self.filter_fn.call1(py, (level, key, value))?;
+        let decision = decision.bind(py);
+        if let Ok(decision) = decision.extract::<&str>() {
+            return match decision {
+                "keep" => Ok(CompactionDecision::Keep),
+                "remove" => Ok(CompactionDecision::Remove),
+                _ => Err(PyException::new_err(format!(
+                    "unknown compaction filter decision `{decision}`"
+                ))),
+            };
+        }
+        let (decision, new_value): (String, Bound<PyAny>) = decision.extract()?;
+        if decision != "change" {
+            return Err(PyException::new_err(format!(
+                "unknown compaction filter decision `{decision}`"
+            )));
+        }
+        self.new_value = encode_value(&new_value, &self.dumps, self.raw_mode)?.into_owned();
+        // SAFETY: RocksDB copies the new value before the next call of the filter
+        let new_value =
+            unsafe { std::slice::from_raw_parts(self.new_value.as_ptr(), self.new_value.len()) };
+        Ok(CompactionDecision::Change(new_value))
+    }
+}
+
+/// A Python callable creating a compaction filter per compaction job.
+struct PyCompactionFilterFactory {
+    factory: PyObject,
+    loads: PyObject,
+    dumps: PyObject,
+    raw_mode: bool,
+    name: CString,
+}
+
+/// The compaction filter of a single compaction job,
+/// keeping everything if the factory failed to create one.
+struct PyCompactionFilter {
+    filter_fn: Option<PyCompactionFilterFn>,
+    name: CString,
+}
+
+impl CompactionFilterFactory for PyCompactionFilterFactory {
+    type Filter = PyCompactionFilter;
+
+    fn create(&mut self, context: CompactionFilterContext) -> Self::Filter {
+        let filter_fn = Python::with_gil(|py| {
+            match self.factory.call1(
+                py,
+                (context.is_full_compaction, context.is_manual_compaction),
+            ) {
+                Ok(filter_fn) => Some(PyCompactionFilterFn {
+                    filter_fn,
+                    loads: self.loads.clone_ref(py),
+                    dumps: self.dumps.clone_ref(py),
+                    raw_mode: self.raw_mode,
+                    new_value: Vec::new(),
+                }),
+                Err(e) => {
+                    e.write_unraisable(py, Some(self.factory.bind(py)));
+                    None
+                }
+            }
+        });
+        PyCompactionFilter {
+            filter_fn,
+            name: self.name.clone(),
+        }
+    }
+
+    fn name(&self) -> &CStr {
+        &self.name
+    }
+}
+
+impl CompactionFilter for PyCompactionFilter {
+    fn filter(&mut self, level: u32, key: &[u8], value: &[u8]) -> CompactionDecision {
+        match &mut self.filter_fn {
+            Some(filter_fn) => filter_fn.call(level, key, value),
+            None => CompactionDecision::Keep,
+        }
+    }
+
+    fn name(&self) -> &CStr {
+        &self.name
+    }
+}
+
 #[macro_export]
 macro_rules! implement_max_len_transform {
     ($($len:literal),*) => {
@@ -3245,3 +4785,28 @@ implement_max_len_transform!(
     99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117,
     118, 119, 120, 121, 122, 123, 124, 125, 126, 127, 128
 );
+
+/// Finds a ticker or histogram by its RocksDB name (`rocksdb.block.cache.hit`)
+/// or by its upper case name (`BLOCK_CACHE_HIT`).
+fn parse_statistics_name<T: Copy + std::fmt::Debug + std::fmt::Display + 'static>(
+    name: &str,
+    mut variants: std::slice::Iter<'static, T>,
+    kind: &str,
+) -> PyResult<T> {
+    variants
+        .find(|variant| variant.to_string() == name || upper_snake_case(variant) == name)
+        .copied()
+        .ok_or_else(|| PyValueError::new_err(format!("unknown {kind} name `{name}`")))
+}
+
+/// `BlockCacheHit` -> `BLOCK_CACHE_HIT`
+fn upper_snake_case(variant: &impl std::fmt::Debug) -> String {
+    let mut result = String::new();
+    for (i, c) in format!("{variant:?}").chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            result.push('_');
+        }
+        result.push(c.to_ascii_uppercase());
+    }
+    result
+}
@@ -0,0 +1,70 @@
+use crate::db_reference::DbReferenceHolder;
+use pyo3::ffi;
+use pyo3::prelude::*;
+use rocksdb::DBPinnableSlice;
+use std::os::raw::c_int;
+
+/// A value read by `Rdict.get_pinned`, pinned in the block cache or memtable.
+///
+/// It implements the buffer protocol, so `memoryview(value)` gives access
+/// to the value without copying it, and `bytes(value)` copies it.
+///
+/// Notes:
+///     The value keeps the database alive: after `Rdict.close()`, the
+///     database files stay open until all pinned values and their
+///     memoryviews are released. Pinned values also keep the blocks
+///     holding them in memory, so do not keep many of them for long.
+#[pyclass(name = "PinnableSlice")]
+pub(crate) struct PinnableSlicePy {
+    // declared before `db` so that it is dropped first
+    slice: DBPinnableSlice<'static>,
+    /// the pinned value must keep a reference count of DB to keep DB alive.
+    #[allow(dead_code)]
+    db: DbReferenceHolder,
+}
+
+impl PinnableSlicePy {
+    /// # Safety
+    ///
+    /// `slice` must be read from the DB referenced by `db`.
+    pub(crate) unsafe fn new(slice: DBPinnableSlice<'_>, db: DbReferenceHolder) -> Self {
+        Self {
+            slice: std::mem::transmute::<DBPinnableSlice<'_>, DBPinnableSlice<'static>>(slice),
+            db,
+        }
+    }
+}
+
+#[pymethods]
+impl PinnableSlicePy {
+    fn __len__(&self) -> usize {
+        self.slice.len()
+    }
+
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        let (buf, len) = {
+            let value = slf.borrow();
+            (value.slice.as_ptr(), value.slice.len())
+        };
+        // fills a read-only, one-dimensional buffer of bytes, and increments
+        // the reference count of `slf`, which is decremented on release
+        if ffi::PyBuffer_FillInfo(
+            view,
+            slf.as_ptr(),
+            buf as *mut _,
+            len as ffi::Py_ssize_t,
+            1,
+            flags,
+        ) == -1
+        {
+            return Err(PyErr::fetch(slf.py()));
+        }
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {}
+}
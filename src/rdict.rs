@@ -1,29 +1,55 @@
+use crate::checkpoints::CheckpointPy;
 use crate::db_reference::{DbReference, DbReferenceHolder};
-use crate::encoder::{decode_value, encode_key, encode_value};
-use crate::exceptions::DbClosedError;
+use crate::encoder::{
+    compare_keys, decode_value, encode_expiring_value, encode_key, encode_u64_ts, encode_value,
+};
+use crate::exceptions::{rocksdb_error, DbClosedError, KeyNotFoundError, RocksDBError};
 use crate::iter::{RdictItems, RdictKeys, RdictValues};
-use crate::options::{CachePy, EnvPy, SliceTransformType};
+use crate::options::{
+    BottommostLevelCompactionPy, BuiltinComparator, CachePy, ComparatorError, EnvPy, SerializerPy,
+    SliceTransformType,
+};
+use crate::pinnable_slice::PinnableSlicePy;
+use crate::util::{error_message, to_cpath, unix_now};
+use crate::wal_iterator::{wal_error, WalIteratorPy};
+use crate::write_batch_with_index::WriteBatchWithIndexPy;
+use crate::{ffi_try, ffi_try_impl};
 use crate::{
     CompactOptionsPy, FlushOptionsPy, IngestExternalFileOptionsPy, OptionsPy, RdictColumns,
     RdictEntities, RdictIter, ReadOptionsPy, Snapshot, WriteBatchPy, WriteOptionsPy,
 };
-use pyo3::exceptions::{PyException, PyKeyError};
+use libc::{c_char, c_void};
+use num_bigint::BigInt;
+use pyo3::exceptions::{PyException, PyTimeoutError, PyTypeError, PyUserWarning, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList, PyTuple};
+use pyo3::types::{PyBytes, PyDict, PyList, PyTuple};
 use rocksdb::{
-    ColumnFamilyDescriptor, FlushOptions, Iterable as _, LiveFile, ReadOptions,
-    UnboundColumnFamily, WriteOptions, DEFAULT_COLUMN_FAMILY_NAME,
+    AsColumnFamilyRef, ColumnFamilyDescriptor, ErrorKind, FlushOptions, IngestExternalFileOptions,
+    Iterable as _, LiveFile, Range, ReadOptions, SstFileWriter, UnboundColumnFamily,
+    WaitForCompactOptions, WriteBatch, WriteOptions, DEFAULT_COLUMN_FAMILY_NAME,
 };
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::ffi::{CStr, CString};
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, RwLock};
+use std::sync::atomic::{self, AtomicUsize};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, TryLockError};
 use std::time::Duration;
+use std::{ptr, slice};
 
 pub const ROCKSDICT_CONFIG_FILE: &str = "rocksdict-config.json";
+/// version of the on-disk encoding of keys and values (in non-raw mode),
+/// to be increased on any change that old versions cannot read:
+///
+/// - 1: the original encoding
+/// - 2: native datetime, date, timedelta, UUID, Decimal and expiring values
+pub const ENCODING_VERSION: u32 = 2;
 /// 8MB default LRU cache size
 pub const DEFAULT_LRU_CACHE_SIZE: usize = 8 * 1024 * 1024;
 
@@ -56,6 +82,11 @@ type DB = rocksdb::DBWithThreadMode<rocksdb::MultiThreaded>;
 /// It will automatically open the db in right Options and
 /// Column Families for you in RAW MODE.
 ///
+/// Opening a database replays its WAL, which can take a while for
+/// large WALs. The GIL is released while opening, so other Python
+/// threads (e.g. a readiness probe) keep running. RocksDB does not
+/// report replay progress through its C API.
+///
 /// Args:
 ///     path (str): path to the database
 ///     options (Options): Options object
@@ -78,6 +109,14 @@ pub(crate) struct Rdict {
     pub(crate) opt_py: OptionsPy,
     pub(crate) access_type: AccessType,
     pub(crate) slice_transforms: Arc<RwLock<HashMap<String, SliceTransformType>>>,
+    pub(crate) comparators: Arc<RwLock<HashMap<String, BuiltinComparator>>>,
+    // column families with a comparator set by `Options.set_comparator`
+    pub(crate) python_comparators: Arc<RwLock<HashMap<String, ComparatorError>>>,
+    pub(crate) write_buffer: Mutex<Option<WriteBatch>>,
+    pub(crate) write_buffer_max_ops: AtomicUsize,
+    pub(crate) drop_behavior: DropBehavior,
+    pub(crate) key_type: Option<PyObject>,
+    pub(crate) value_type: Option<PyObject>,
     // drop DB last
     pub(crate) db: DbReferenceHolder,
 }
@@ -114,6 +153,28 @@ pub struct RocksDictConfig {
     pub raw_mode: bool,
     // mapping from column families to SliceTransformType
     pub prefix_extractors: HashMap<String, SliceTransformType>,
+    #[serde(default)]
+    pub normalize_str_bytes_keys: bool,
+    // configs without version are written with the first encoding
+    #[serde(default = "legacy_encoding_version")]
+    pub encoding_version: u32,
+    // `pickle` if not set
+    #[serde(default)]
+    pub serializer: Option<SerializerConfig>,
+    // mapping from column families to the comparators set in raw mode
+    #[serde(default)]
+    pub comparators: HashMap<String, BuiltinComparator>,
+}
+
+/// Importable names of the custom `dumps` and `loads` functions.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct SerializerConfig {
+    pub dumps: String,
+    pub loads: String,
+}
+
+fn legacy_encoding_version() -> u32 {
+    1
 }
 
 impl Default for RocksDictConfig {
@@ -121,11 +182,27 @@ impl Default for RocksDictConfig {
         Self {
             raw_mode: true,
             prefix_extractors: Default::default(),
+            normalize_str_bytes_keys: false,
+            encoding_version: ENCODING_VERSION,
+            serializer: None,
+            comparators: Default::default(),
         }
     }
 }
 
 impl RocksDictConfig {
+    /// Reject databases written with an encoding this version cannot read.
+    pub fn check_encoding_version(&self) -> PyResult<()> {
+        if self.encoding_version > ENCODING_VERSION {
+            return Err(PyException::new_err(format!(
+                "the database uses encoding version {}, but this version of rocksdict \
+                 only supports up to {ENCODING_VERSION}, please upgrade rocksdict",
+                self.encoding_version
+            )));
+        }
+        Ok(())
+    }
+
     pub fn load<P: AsRef<Path>>(path: P) -> PyResult<Self> {
         let config_file = fs::File::options().read(true).open(path)?;
         match serde_json::from_reader(config_file) {
@@ -151,12 +228,89 @@ impl RocksDictConfig {
     }
 }
 
+/// Warn if a column family is reopened with a prefix extractor
+/// different from the one persisted in the rocksdict config,
+/// since prefix seeks would silently return different results.
+fn warn_prefix_extractor_mismatch(
+    persisted: &HashMap<String, SliceTransformType>,
+    provided: &HashMap<String, SliceTransformType>,
+    cf_names: &[&str],
+    py: Python,
+) -> PyResult<()> {
+    let describe = |t: Option<&SliceTransformType>| match t {
+        None => "none".to_string(),
+        Some(t) => t.to_string(),
+    };
+    for &cf_name in cf_names {
+        let (old, new) = (persisted.get(cf_name), provided.get(cf_name));
+        if old != new {
+            let message = CString::new(format!(
+                "column family `{cf_name}` was created with prefix extractor `{}` \
+                 but is opened with `{}`, prefix seek results may change",
+                describe(old),
+                describe(new),
+            ))?;
+            PyErr::warn(py, &py.get_type::<PyUserWarning>(), &message, 1)?;
+        }
+    }
+    Ok(())
+}
+
+/// Raise if a column family is reopened with another builtin comparator,
+/// instead of the less explicit error of RocksDB.
+fn check_comparators(
+    persisted: &HashMap<String, BuiltinComparator>,
+    opened: &HashMap<String, BuiltinComparator>,
+    cf_names: &[&str],
+) -> PyResult<()> {
+    for cf_name in cf_names {
+        let old = persisted.get(*cf_name);
+        let new = opened.get(*cf_name);
+        // column families without a persisted comparator may be new
+        if old.is_some() && old != new {
+            let describe = |c: Option<&BuiltinComparator>| match c {
+                None => "the default comparator".to_string(),
+                Some(c) => format!("comparator `{c}`"),
+            };
+            return Err(PyException::new_err(format!(
+                "column family `{cf_name}` was created with {}, but is opened with {}",
+                describe(old),
+                describe(new),
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Raise TypeError if `obj` is not an instance of `expected` (if set).
+fn check_type(obj: &Bound<PyAny>, expected: &Option<PyObject>, what: &str) -> PyResult<()> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+    let expected = expected.bind(obj.py());
+    if obj.is_instance(expected)? {
+        return Ok(());
+    }
+    let expected_name = match expected.getattr("__name__") {
+        Ok(name) => name.to_string(),
+        Err(_) => expected.repr()?.to_string(),
+    };
+    Err(PyTypeError::new_err(format!(
+        "expected {what} of type {expected_name}, got {}",
+        obj.get_type().name()?
+    )))
+}
+
 impl Rdict {
     #[inline]
     pub(crate) fn config(&self) -> RocksDictConfig {
         RocksDictConfig {
             raw_mode: self.opt_py.raw_mode,
             prefix_extractors: self.slice_transforms.read().unwrap().clone(),
+            normalize_str_bytes_keys: self.opt_py.normalize_str_bytes_keys,
+            encoding_version: ENCODING_VERSION,
+            serializer: self.opt_py.serializer.as_ref().map(|s| s.config.clone()),
+            comparators: self.comparators.read().unwrap().clone(),
         }
     }
 
@@ -164,11 +318,63 @@ impl Rdict {
         self.config().save_to_dir(&self.path()?)
     }
 
+    /// Check that a batch encodes keys and values the same way as this DB.
+    pub(crate) fn check_batch_mode(
+        &self,
+        raw_mode: bool,
+        normalize_str_bytes_keys: bool,
+        dumps: &PyObject,
+        batch_type: &str,
+    ) -> PyResult<()> {
+        if self.opt_py.raw_mode != raw_mode {
+            return if self.opt_py.raw_mode {
+                Err(PyException::new_err(format!(
+                    "must set raw_mode=True for {batch_type}"
+                )))
+            } else {
+                Err(PyException::new_err(format!(
+                    "must set raw_mode=False for {batch_type}"
+                )))
+            };
+        }
+        if self.opt_py.normalize_str_bytes_keys != normalize_str_bytes_keys {
+            return Err(PyException::new_err(format!(
+                "must set normalize_str_bytes_keys={} for {batch_type}",
+                if self.opt_py.normalize_str_bytes_keys {
+                    "True"
+                } else {
+                    "False"
+                }
+            )));
+        }
+        // values would be read back with another serializer
+        if !self.opt_py.raw_mode && !dumps.is(&self.dumps) {
+            return Err(PyException::new_err(format!(
+                "the serializer of {batch_type} differs from the one of the database, \
+                 call {batch_type}.set_dumps and {batch_type}.set_loads"
+            )));
+        }
+        Ok(())
+    }
+
     #[inline]
     pub(crate) fn get_db(&self) -> PyResult<&DbReference> {
-        self.db
+        let db = self
+            .db
             .get()
-            .ok_or_else(|| DbClosedError::new_err("DB instance already closed"))
+            .ok_or_else(|| DbClosedError::new_err("DB instance already closed"))?;
+        self.take_comparator_error()?;
+        Ok(db)
+    }
+
+    /// raise the first exception of a Python comparator, only once
+    fn take_comparator_error(&self) -> PyResult<()> {
+        for error in self.python_comparators.read().unwrap().values() {
+            if let Some(e) = error.lock().unwrap().take() {
+                return Err(e);
+            }
+        }
+        Ok(())
     }
 }
 
@@ -193,7 +399,6 @@ impl Rdict {
         access_type: AccessType,
         py: Python,
     ) -> PyResult<Self> {
-        let pickle = PyModule::import(py, "pickle")?;
         // create db path if missing
         fs::create_dir_all(path).map_err(|e| PyException::new_err(e.to_string()))?;
         // load options
@@ -214,6 +419,13 @@ impl Rdict {
             (Err(_), Some(opt), cols) => (opt, cols),
             (Err(_), None, cols) => (OptionsPy::new(false), cols),
         };
+        // hash-based memtables require prefix extractors
+        options.check_memtable_factory(DEFAULT_COLUMN_FAMILY_NAME)?;
+        if let Some(cf) = &column_families {
+            for (name, opt) in cf.iter() {
+                opt.check_memtable_factory(name)?;
+            }
+        }
         // save slice transforms types in rocksdict config
         let config_path = config_file(path);
         let mut prefix_extractors = HashMap::new();
@@ -230,9 +442,82 @@ impl Rdict {
                 }
             }
         }
+        // save builtin comparators, so that they are set again when reopening
+        let mut comparators = HashMap::new();
+        if let Some(comparator) = options.comparator {
+            comparators.insert(DEFAULT_COLUMN_FAMILY_NAME.to_string(), comparator);
+        }
+        if let Some(cf) = &column_families {
+            for (name, opt) in cf.iter() {
+                if let Some(comparator) = opt.comparator {
+                    comparators.insert(name.clone(), comparator);
+                }
+            }
+        }
+        let mut python_comparators = HashMap::new();
+        if let Some(error) = &options.python_comparator {
+            python_comparators.insert(DEFAULT_COLUMN_FAMILY_NAME.to_string(), error.clone());
+        }
+        if let Some(cf) = &column_families {
+            for (name, opt) in cf.iter() {
+                if let Some(error) = &opt.python_comparator {
+                    python_comparators.insert(name.clone(), error.clone());
+                }
+            }
+        }
+        let serializer_config = options.serializer.as_ref().map(|s| s.config.clone());
+        if let Ok(persisted_config) = RocksDictConfig::load(&config_path) {
+            persisted_config.check_encoding_version()?;
+            if persisted_config.serializer != serializer_config {
+                let describe = |s: &Option<SerializerConfig>| match s {
+                    None => "pickle".to_string(),
+                    Some(s) => format!("({}, {})", s.dumps, s.loads),
+                };
+                return Err(PyException::new_err(format!(
+                    "the database was written with serializer {}, \
+                     but is opened with serializer {}",
+                    describe(&persisted_config.serializer),
+                    describe(&serializer_config),
+                )));
+            }
+            // these change the encoding or the order of keys
+            if persisted_config.raw_mode != options.raw_mode {
+                return Err(PyException::new_err(format!(
+                    "the database was written with raw_mode={}, \
+                     but is opened with raw_mode={}",
+                    persisted_config.raw_mode, options.raw_mode,
+                )));
+            }
+            if persisted_config.normalize_str_bytes_keys != options.normalize_str_bytes_keys {
+                return Err(PyException::new_err(format!(
+                    "the database was written with normalize_str_bytes_keys={}, \
+                     but is opened with normalize_str_bytes_keys={}",
+                    persisted_config.normalize_str_bytes_keys, options.normalize_str_bytes_keys,
+                )));
+            }
+            let mut cf_names = vec![DEFAULT_COLUMN_FAMILY_NAME];
+            if let Some(cf) = &column_families {
+                cf_names.extend(cf.keys().map(String::as_str));
+            }
+            check_comparators(&persisted_config.comparators, &comparators, &cf_names)?;
+            warn_prefix_extractor_mismatch(
+                &persisted_config.prefix_extractors,
+                &prefix_extractors,
+                &cf_names,
+                py,
+            )?;
+            // keep the comparators of the column families not opened
+            for (name, comparator) in persisted_config.comparators {
+                comparators.entry(name).or_insert(comparator);
+            }
+        }
         let rocksdict_config = RocksDictConfig {
             raw_mode: options.raw_mode,
             prefix_extractors: prefix_extractors.clone(),
+            normalize_str_bytes_keys: options.normalize_str_bytes_keys,
+            encoding_version: ENCODING_VERSION,
+            serializer: serializer_config,
+            comparators: comparators.clone(),
         };
         rocksdict_config.save(config_path)?;
         let opt_inner = &options.inner_opt;
@@ -249,6 +534,7 @@ impl Rdict {
                 // check options_raw_mode for column families
                 for (cf_name, cf_opt) in cf.iter_mut() {
                     cf_opt.raw_mode = options.raw_mode;
+                    cf_opt.normalize_str_bytes_keys = options.normalize_str_bytes_keys;
                     if cf_name.as_str() == DEFAULT_COLUMN_FAMILY_NAME {
                         has_default_cf = true;
                     }
@@ -267,35 +553,53 @@ impl Rdict {
                 cfs
             }
         };
-        // open db
-        let db = match &access_type.0 {
-            AccessTypeInner::ReadWrite => DB::open_cf_descriptors(opt_inner, path, cfs),
-            AccessTypeInner::ReadOnly {
-                error_if_log_file_exist,
-            } => DB::open_cf_descriptors_read_only(opt_inner, path, cfs, *error_if_log_file_exist),
-            AccessTypeInner::Secondary { secondary_path } => {
-                DB::open_cf_descriptors_as_secondary(opt_inner, path, secondary_path, cfs)
-            }
-            AccessTypeInner::WithTTL { ttl } => {
-                DB::open_cf_descriptors_with_ttl(opt_inner, path, cfs, *ttl)
-            }
-        }
-        .map_err(|e| PyException::new_err(e.to_string()))?;
+        // open db, release the GIL since WAL recovery might take long
+        let db = py
+            .allow_threads(|| match &access_type.0 {
+                AccessTypeInner::ReadWrite => DB::open_cf_descriptors(opt_inner, path, cfs),
+                AccessTypeInner::ReadOnly {
+                    error_if_log_file_exist,
+                } => DB::open_cf_descriptors_read_only(
+                    opt_inner,
+                    path,
+                    cfs,
+                    *error_if_log_file_exist,
+                ),
+                AccessTypeInner::Secondary { secondary_path } => {
+                    DB::open_cf_descriptors_as_secondary(opt_inner, path, secondary_path, cfs)
+                }
+                AccessTypeInner::WithTTL { ttl } => {
+                    DB::open_cf_descriptors_with_ttl(opt_inner, path, cfs, *ttl)
+                }
+            })
+            .map_err(rocksdb_error)?;
         let r_opt = ReadOptionsPy::default(py)?;
         let w_opt = WriteOptionsPy::new();
+        let (loads, dumps) = SerializerPy::loads_dumps(options.serializer.as_ref(), py)?;
         Ok(Rdict {
             db: DbReferenceHolder::new(db),
             write_opt: (&w_opt).into(),
             flush_opt: FlushOptionsPy::new(),
-            read_opt: r_opt.to_read_options(options.raw_mode, py)?,
-            loads: pickle.getattr("loads")?.unbind(),
-            dumps: pickle.getattr("dumps")?.unbind(),
+            read_opt: r_opt.to_read_options(
+                options.raw_mode,
+                options.normalize_str_bytes_keys,
+                py,
+            )?,
+            loads,
+            dumps,
             write_opt_py: w_opt,
             read_opt_py: r_opt,
             column_family: None,
             opt_py: options.clone(),
             access_type,
             slice_transforms: Arc::new(RwLock::new(prefix_extractors)),
+            comparators: Arc::new(RwLock::new(comparators)),
+            python_comparators: Arc::new(RwLock::new(python_comparators)),
+            write_buffer: Mutex::new(None),
+            write_buffer_max_ops: AtomicUsize::new(0),
+            drop_behavior: DropBehavior::Flush,
+            key_type: None,
+            value_type: None,
         })
     }
 
@@ -309,6 +613,41 @@ impl Rdict {
         self.loads = loads
     }
 
+    /// Check the types of keys and values written to this Rdict.
+    ///
+    /// Keys are checked when reading, writing or deleting, and values
+    /// when writing, with `isinstance`, before being encoded.
+    /// A mismatch raises TypeError.
+    ///
+    /// Notes:
+    ///     The schema is not saved on disk, and only applies to this
+    ///     Rdict instance, not to its column families.
+    ///
+    /// Args:
+    ///     key_type: a type or a tuple of types, None to accept any key.
+    ///     value_type: a type or a tuple of types, None to accept any value.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from dataclasses import dataclass
+    ///         from rocksdict import Rdict
+    ///
+    ///         @dataclass
+    ///         class Point:
+    ///             x: int
+    ///             y: int
+    ///
+    ///         db = Rdict("./test_dir")
+    ///         db.set_schema(key_type=int, value_type=Point)
+    ///         db[1] = Point(1, 2)
+    ///         db["oops"] = 3  # raises TypeError
+    #[pyo3(signature = (key_type = None, value_type = None))]
+    fn set_schema(&mut self, key_type: Option<PyObject>, value_type: Option<PyObject>) {
+        self.key_type = key_type;
+        self.value_type = value_type;
+    }
+
     /// Optionally disable WAL or sync for this write.
     ///
     /// Example:
@@ -338,9 +677,41 @@ impl Rdict {
         self.write_opt_py = write_opt.clone();
     }
 
+    /// Configure what is written to disk when the Rdict is closed or dropped.
+    ///
+    /// Notes:
+    ///     - `"flush"` (default): flush the WAL and the memtable
+    ///       of the current column family.
+    ///     - `"flush_wal"`: only flush the WAL (the memtable is
+    ///       recovered from it when the database is reopened).
+    ///     - `"none"`: write nothing, for throwaway databases
+    ///       (e.g. in tests). Unless the WAL is disabled, writes
+    ///       are still recovered from the WAL when reopened.
+    ///
+    ///     Column family Rdicts inherit the behavior of the Rdict
+    ///     they are obtained from. Operations buffered by
+    ///     `enable_write_buffering` are always written.
+    ///
+    /// Args:
+    ///     behavior: one of `"flush"`, `"flush_wal"` and `"none"`.
+    fn set_drop_behavior(&mut self, behavior: &str) -> PyResult<()> {
+        self.drop_behavior = match behavior {
+            "flush" => DropBehavior::Flush,
+            "flush_wal" => DropBehavior::FlushWal,
+            "none" => DropBehavior::Nothing,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown drop behavior `{behavior}`, \
+                     expected one of `flush`, `flush_wal` and `none`"
+                )))
+            }
+        };
+        Ok(())
+    }
+
     /// Configure Read Options for all the get operations.
     fn set_read_options(&mut self, read_opt: &ReadOptionsPy, py: Python) -> PyResult<()> {
-        self.read_opt = read_opt.to_read_options(self.opt_py.raw_mode, py)?;
+        self.read_opt = self.to_read_options(read_opt, py)?;
         self.read_opt_py = read_opt.clone();
         Ok(())
     }
@@ -350,7 +721,7 @@ impl Rdict {
         match self.get(key, None, None, py) {
             Ok(v) => {
                 if v.is_none() {
-                    Err(PyKeyError::new_err(format!("key {key} not found")))
+                    Err(KeyNotFoundError::new_err(format!("key {key} not found")))
                 } else {
                     Ok(v)
                 }
@@ -381,7 +752,7 @@ impl Rdict {
         let db = self.get_db()?;
         let read_opt_option = match read_opt {
             None => None,
-            Some(opt) => Some(opt.to_read_options(self.opt_py.raw_mode, py)?),
+            Some(opt) => Some(self.to_read_options(opt, py)?),
         };
         let read_opt = match &read_opt_option {
             None => &self.read_opt,
@@ -395,12 +766,15 @@ impl Rdict {
             Some(cf) => cf.clone(),
         };
         if let Ok(keys) = key.downcast() {
-            return Ok(self.get_batch_inner(db, keys, default, py, &cf)?.into_any());
+            return Ok(self
+                .get_batch_inner(db, keys, default, read_opt, py, &cf)?
+                .into_any());
         }
-        let key_bytes = encode_key(key, self.opt_py.raw_mode)?;
+        self.check_key_type(key)?;
+        let key_bytes = self.encode_key(key)?;
         let value_result = db
             .get_pinned_cf_opt(&cf, key_bytes, read_opt)
-            .map_err(|e| PyException::new_err(e.to_string()))?;
+            .map_err(rocksdb_error)?;
         match value_result {
             None => {
                 // try to return default value
@@ -419,10 +793,254 @@ impl Rdict {
         }
     }
 
-    /// Get a wide-column from a key.
+    /// Get the values of keys of several column families in one call.
     ///
     /// Args:
-    ///     key: a single key or list of keys.
+    ///     pairs: a list of `(ColumnFamily, key)` tuples, with column family
+    ///         handles of this database (see `get_column_family_handle`).
+    ///     default: the default value to return for missing keys.
+    ///     read_opt: override preset read options.
+    ///
+    /// Returns:
+    ///     the values in the order of `pairs`,
+    ///     None or default value for missing keys.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         users = db.get_column_family_handle("users")
+    ///         orders = db.get_column_family_handle("orders")
+    ///         user, order = db.multi_get_cf([(users, 42), (orders, 1001)])
+    #[pyo3(signature = (pairs, default = None, read_opt = None))]
+    fn multi_get_cf<'py>(
+        &self,
+        pairs: Vec<(PyRef<ColumnFamilyPy>, Bound<'py, PyAny>)>,
+        default: Option<Bound<'py, PyAny>>,
+        read_opt: Option<&ReadOptionsPy>,
+        py: Python<'py>,
+    ) -> PyResult<Vec<Bound<'py, PyAny>>> {
+        let db = self.get_db()?;
+        let read_opt_option = match read_opt {
+            None => None,
+            Some(opt) => Some(self.to_read_options(opt, py)?),
+        };
+        let read_opt = match &read_opt_option {
+            None => &self.read_opt,
+            Some(opt) => opt,
+        };
+        let mut cfs_and_keys = Vec::with_capacity(pairs.len());
+        for (cf, key) in pairs.iter() {
+            if !cf.db.get().is_some_and(|cf_db| Arc::ptr_eq(cf_db, db)) {
+                return Err(PyValueError::new_err(
+                    "column family handle does not belong to this database",
+                ));
+            }
+            cfs_and_keys.push((&cf.cf, self.encode_key(key)?));
+        }
+        let values = py.allow_threads(|| db.multi_get_cf_opt(cfs_and_keys, read_opt));
+        values
+            .into_iter()
+            .map(|value| match value.map_err(rocksdb_error)? {
+                None => Ok(default.clone().unwrap_or_else(|| py.None().into_bound(py))),
+                Some(value) => decode_value(py, &value, &self.loads, self.opt_py.raw_mode),
+            })
+            .collect()
+    }
+
+    /// Get the value of a key without copying it, in raw mode only.
+    ///
+    /// Notes:
+    ///     The returned `PinnableSlice` supports the buffer protocol:
+    ///     use `memoryview(value)` to read it without copy.
+    ///     It keeps the database open, even after `Rdict.close()`,
+    ///     until it and all its memoryviews are released.
+    ///
+    /// Args:
+    ///     key: bytes key.
+    ///     read_opt: override preset read options.
+    ///
+    /// Returns:
+    ///     None if the key does not exist.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options
+    ///
+    ///         db = Rdict("./test_dir", Options(raw_mode=True))
+    ///         db[b"key"] = b"value" * 1000
+    ///         value = db.get_pinned(b"key")
+    ///         with memoryview(value) as view:
+    ///             assert view[:5] == b"value"
+    ///         del value
+    ///         db.close()
+    #[pyo3(signature = (key, read_opt = None))]
+    fn get_pinned(
+        &self,
+        key: &Bound<PyAny>,
+        read_opt: Option<&ReadOptionsPy>,
+        py: Python,
+    ) -> PyResult<Option<PinnableSlicePy>> {
+        if !self.opt_py.raw_mode {
+            return Err(PyValueError::new_err(
+                "get_pinned is only supported in raw_mode",
+            ));
+        }
+        let db = self.get_db()?;
+        let read_opt_option = match read_opt {
+            None => None,
+            Some(opt) => Some(self.to_read_options(opt, py)?),
+        };
+        let read_opt = match &read_opt_option {
+            None => &self.read_opt,
+            Some(opt) => opt,
+        };
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        let key_bytes = self.encode_key(key)?;
+        let value = db
+            .get_pinned_cf_opt(&cf, key_bytes, read_opt)
+            .map_err(rocksdb_error)?;
+        // SAFETY: the slice is read from `self.db`
+        Ok(value.map(|slice| unsafe { PinnableSlicePy::new(slice, self.db.clone()) }))
+    }
+
+    /// Get the values of a list of keys in a single batch.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict
+    ///
+    ///         db = Rdict("./test_dir")
+    ///         db["a"] = 1
+    ///         db["b"] = 2
+    ///         assert db.get_batch(["a", "c"]) == [1, None]
+    ///         assert db.get_batch(["a", "c"], as_dict=True) == {"a": 1, "c": None}
+    ///         assert db.get_batch(["a", "c"], as_dict=True, skip_missing=True) == {"a": 1}
+    ///
+    /// Args:
+    ///     keys: a list of keys.
+    ///     read_opt: override preset read options
+    ///         (or use Rdict.set_read_options to preset a read options used by default),
+    ///         e.g. to read from a snapshot.
+    ///     as_dict: return a dict mapping keys to values instead of a list of values
+    ///         (in the order of the keys).
+    ///     skip_missing: leave missing keys out of the dict instead of mapping them
+    ///         to None. Only used if `as_dict` is True.
+    ///
+    /// Returns:
+    ///    A list of values, or a dict if `as_dict` is True.
+    #[pyo3(signature = (keys, read_opt = None, as_dict = false, skip_missing = false))]
+    fn get_batch<'py>(
+        &self,
+        keys: Vec<Bound<'py, PyAny>>,
+        read_opt: Option<&ReadOptionsPy>,
+        as_dict: bool,
+        skip_missing: bool,
+        py: Python<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let db = self.get_db()?;
+        let read_opt_option = match read_opt {
+            None => None,
+            Some(opt) => Some(self.to_read_options(opt, py)?),
+        };
+        let read_opt = match &read_opt_option {
+            None => &self.read_opt,
+            Some(opt) => opt,
+        };
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        let values = self.multi_get_inner(db, &keys, read_opt, py, &cf)?;
+        if !as_dict {
+            let values = values
+                .into_iter()
+                .map(|v| v.unwrap_or_else(|| py.None().into_bound(py)));
+            return Ok(PyList::new(py, values)?.into_any());
+        }
+        let result = PyDict::new(py);
+        for (key, value) in keys.iter().zip(values) {
+            match value {
+                Some(value) => result.set_item(key, value)?,
+                None if !skip_missing => result.set_item(key, py.None())?,
+                None => {}
+            }
+        }
+        Ok(result.into_any())
+    }
+
+    /// Get value from a key, telling apart a stored `None` from a missing key.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict
+    ///
+    ///         db = Rdict("./test_dir")
+    ///         db["none"] = None
+    ///         assert db.get_with_flag("none") == (True, None)
+    ///         assert db.get_with_flag("missing") == (False, None)
+    ///
+    /// Args:
+    ///     key: a single key.
+    ///     read_opt: override preset read options
+    ///         (or use Rdict.set_read_options to preset a read options used by default).
+    ///
+    /// Returns:
+    ///    A tuple `(found, value)`, where value is None if the key does not exist.
+    #[pyo3(signature = (key, read_opt = None))]
+    fn get_with_flag<'py>(
+        &self,
+        key: &Bound<PyAny>,
+        read_opt: Option<&ReadOptionsPy>,
+        py: Python<'py>,
+    ) -> PyResult<(bool, Bound<'py, PyAny>)> {
+        let db = self.get_db()?;
+        let read_opt_option = match read_opt {
+            None => None,
+            Some(opt) => Some(self.to_read_options(opt, py)?),
+        };
+        let read_opt = match &read_opt_option {
+            None => &self.read_opt,
+            Some(opt) => opt,
+        };
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        self.check_key_type(key)?;
+        let key_bytes = self.encode_key(key)?;
+        let value_result = db
+            .get_pinned_cf_opt(&cf, key_bytes, read_opt)
+            .map_err(rocksdb_error)?;
+        match value_result {
+            None => Ok((false, py.None().into_bound(py))),
+            Some(slice) => Ok((
+                true,
+                decode_value(py, slice.as_ref(), &self.loads, self.opt_py.raw_mode)?,
+            )),
+        }
+    }
+
+    /// Get the wide columns of a single key.
+    ///
+    /// Unlike iterating with `RdictIter.columns`, this is a point lookup.
+    ///
+    /// Args:
+    ///     key: a single key.
     ///     default: the default value to return if key not found.
     ///     read_opt: override preset read options
     ///         (or use Rdict.set_read_options to preset a read options used by default).
@@ -444,7 +1062,7 @@ impl Rdict {
         let db = self.get_db()?;
         let read_opt_option = match read_opt {
             None => None,
-            Some(opt) => Some(opt.to_read_options(self.opt_py.raw_mode, py)?),
+            Some(opt) => Some(self.to_read_options(opt, py)?),
         };
         let read_opt = match &read_opt_option {
             None => &self.read_opt,
@@ -457,10 +1075,11 @@ impl Rdict {
             }
             Some(cf) => cf.clone(),
         };
-        let key_bytes = encode_key(key, self.opt_py.raw_mode)?;
+        self.check_key_type(key)?;
+        let key_bytes = self.encode_key(key)?;
         let column_result = db
             .get_entity_cf_opt(&cf, key_bytes, read_opt)
-            .map_err(|e| PyException::new_err(e.to_string()))?;
+            .map_err(rocksdb_error)?;
         match column_result {
             None => {
                 // try to return default value
@@ -483,7 +1102,22 @@ impl Rdict {
     }
 
     fn __setitem__(&self, key: &Bound<PyAny>, value: &Bound<PyAny>) -> PyResult<()> {
-        self.put(key, value, None)
+        self.check_writable()?;
+        let py = key.py();
+        let mut write_buffer = self.lock_write_buffer(py);
+        let Some(batch) = write_buffer.as_mut() else {
+            drop(write_buffer);
+            return self.put(key, value, None);
+        };
+        self.check_key_type(key)?;
+        self.check_value_type(value)?;
+        let key = self.encode_key(key)?;
+        let value = encode_value(value, &self.dumps, self.opt_py.raw_mode)?;
+        match &self.column_family {
+            None => batch.put(key, value),
+            Some(cf) => batch.put_cf(cf, key, value),
+        }
+        self.flush_writes_if_full(batch, py)
     }
 
     /// Insert key value into database.
@@ -501,8 +1135,11 @@ impl Rdict {
         value: &Bound<PyAny>,
         write_opt: Option<&WriteOptionsPy>,
     ) -> PyResult<()> {
+        self.check_writable()?;
         let db = self.get_db()?;
-        let key = encode_key(key, self.opt_py.raw_mode)?;
+        self.check_key_type(key)?;
+        self.check_value_type(value)?;
+        let key = self.encode_key(key)?;
         let value = encode_value(value, &self.dumps, self.opt_py.raw_mode)?;
         let write_opt_option = write_opt.map(WriteOptions::from);
         let write_opt = match &write_opt_option {
@@ -514,30 +1151,318 @@ impl Rdict {
         } else {
             db.put_opt(key, value, write_opt)
         }
-        .map_err(|e| PyException::new_err(e.to_string()))
+        .map_err(rocksdb_error)
     }
 
-    /// Insert a wide-column.
+    /// Insert key value into database, expiring after `ttl_seconds`.
     ///
-    /// The length of `names` and `values` must be the same.
+    /// Notes:
+    ///     Expired values are removed by the compaction filter of
+    ///     `Options.set_per_key_ttl_filter`, which must be set when
+    ///     opening the database. The deletion is eventual: expired
+    ///     values can still be read until the compaction of the files
+    ///     containing them. This is not supported in raw mode.
     ///
     /// Args:
     ///     key: the key.
-    ///     names: the names of the columns.
-    ///     values: the values of the columns.
+    ///     value: the value.
+    ///     ttl_seconds: time to live of the value, in seconds.
     ///     write_opt: override preset write options
     ///         (or use Rdict.set_write_options to preset a write options used by default).
-    #[inline]
-    #[pyo3(signature = (key, names, values, write_opt = None))]
-    fn put_entity(
+    #[pyo3(signature = (key, value, ttl_seconds, write_opt = None))]
+    fn put_with_ttl(
         &self,
         key: &Bound<PyAny>,
-        names: Vec<Bound<PyAny>>,
-        values: Vec<Bound<PyAny>>,
+        value: &Bound<PyAny>,
+        ttl_seconds: u64,
         write_opt: Option<&WriteOptionsPy>,
     ) -> PyResult<()> {
+        self.check_writable()?;
+        if self.opt_py.raw_mode {
+            return Err(PyValueError::new_err(
+                "put_with_ttl is not supported in raw mode",
+            ));
+        }
+        let db = self.get_db()?;
+        self.check_key_type(key)?;
+        self.check_value_type(value)?;
+        let key = self.encode_key(key)?;
+        let value = encode_value(value, &self.dumps, false)?;
+        let value = encode_expiring_value(unix_now().saturating_add(ttl_seconds), &value);
+        let write_opt_option = write_opt.map(WriteOptions::from);
+        let write_opt = match &write_opt_option {
+            None => &self.write_opt,
+            Some(opt) => opt,
+        };
+        if let Some(cf) = &self.column_family {
+            db.put_cf_opt(cf, key, value, write_opt)
+        } else {
+            db.put_opt(key, value, write_opt)
+        }
+        .map_err(rocksdb_error)
+    }
+
+    /// Insert key value into database at timestamp `ts`, for column
+    /// families with user-defined timestamps.
+    ///
+    /// Notes:
+    ///     The column family must be opened with
+    ///     `Options.enable_user_timestamp`, which requires raw mode.
+    ///     Older versions of the key can still be read by setting
+    ///     an older timestamp with `ReadOptions.set_timestamp`.
+    ///
+    /// Args:
+    ///     key: the key.
+    ///     ts: the timestamp, an unsigned 64-bit int.
+    ///     value: the value.
+    ///     write_opt: override preset write options
+    ///         (or use Rdict.set_write_options to preset a write options used by default).
+    #[pyo3(signature = (key, ts, value, write_opt = None))]
+    fn put_with_ts(
+        &self,
+        key: &Bound<PyAny>,
+        ts: u64,
+        value: &Bound<PyAny>,
+        write_opt: Option<&WriteOptionsPy>,
+    ) -> PyResult<()> {
+        self.check_writable()?;
+        self.check_user_timestamp_mode()?;
+        let db = self.get_db()?;
+        self.check_key_type(key)?;
+        self.check_value_type(value)?;
+        let key = self.encode_key(key)?;
+        let value = encode_value(value, &self.dumps, true)?;
+        let write_opt_option = write_opt.map(WriteOptions::from);
+        let write_opt = match &write_opt_option {
+            None => &self.write_opt,
+            Some(opt) => opt,
+        };
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        db.put_cf_with_ts_opt(&cf, key, encode_u64_ts(ts), value, write_opt)
+            .map_err(rocksdb_error)
+    }
+
+    /// Read, modify, and write back the value of a key.
+    ///
+    /// Values returned by `db[key]` are copies, so mutating them
+    /// (e.g. `db[key].append(1)`) does not change the database.
+    /// Use this method instead.
+    ///
+    /// Notes:
+    ///     `update_fn` receives the current value (or `default` if the key
+    ///     does not exist), and may either return a new value, or mutate
+    ///     the value in place and return None. The read and the write
+    ///     are not atomic: concurrent writes to the same key may be lost.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         db["list"] = [1, 2]
+    ///         db.update_value("list", lambda v: v.append(3))
+    ///         db.update_value("count", lambda v: v + 1, default=0)
+    ///         assert db["list"] == [1, 2, 3]
+    ///         assert db["count"] == 1
+    ///
+    /// Args:
+    ///     key: the key.
+    ///     update_fn: function receiving the current value.
+    ///     default: the value passed to `update_fn` if the key does not exist.
+    ///     write_opt: override preset write options
+    ///         (or use Rdict.set_write_options to preset a write options used by default).
+    ///
+    /// Returns:
+    ///     The value written.
+    #[pyo3(signature = (key, update_fn, default = None, write_opt = None))]
+    fn update_value<'py>(
+        &self,
+        key: &Bound<PyAny>,
+        update_fn: &Bound<PyAny>,
+        default: Option<Bound<'py, PyAny>>,
+        write_opt: Option<&WriteOptionsPy>,
+        py: Python<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let value = self.get(key, default, None, py)?;
+        let result = update_fn.call1((&value,))?;
+        let new_value = if result.is_none() { value } else { result };
+        self.put(key, &new_value, write_opt)?;
+        Ok(new_value)
+    }
+
+    /// Store a value, and return the previous value of the key.
+    ///
+    /// Notes:
+    ///     The read and the write are not atomic: a concurrent write to
+    ///     the same key between them is overwritten, and its value is
+    ///     never returned. Use a `Transaction` of an
+    ///     `OptimisticTransactionDB` for an atomic swap.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         db["counter"] = 1
+    ///         assert db.getset("counter", 0) == 1
+    ///         assert db.getset("new", 0) is None
+    ///
+    /// Args:
+    ///     key: the key.
+    ///     value: the new value.
+    ///     write_opt: override preset write options
+    ///         (or use Rdict.set_write_options to preset a write options used by default).
+    ///
+    /// Returns:
+    ///     The previous value, or None if the key did not exist.
+    #[pyo3(signature = (key, value, write_opt = None))]
+    fn getset<'py>(
+        &self,
+        key: &Bound<PyAny>,
+        value: &Bound<PyAny>,
+        write_opt: Option<&WriteOptionsPy>,
+        py: Python<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let old_value = self.get(key, None, None, py)?;
+        self.put(key, value, write_opt)?;
+        Ok(old_value)
+    }
+
+    /// Get the stored value bytes of a key, bypassing the encoder.
+    ///
+    /// In non-raw mode, keys and values are stored with a leading
+    /// type byte, which is included in both the key bytes and the
+    /// returned value bytes.
+    ///
+    /// Args:
+    ///     key: the stored key bytes.
+    ///     read_opt: override preset read options
+    ///         (or use Rdict.set_read_options to preset a read options used by default).
+    ///
+    /// Returns:
+    ///    The stored value bytes, or None if the key does not exist.
+    #[pyo3(signature = (key, read_opt = None))]
+    fn get_raw<'py>(
+        &self,
+        key: &[u8],
+        read_opt: Option<&ReadOptionsPy>,
+        py: Python<'py>,
+    ) -> PyResult<Option<Bound<'py, PyBytes>>> {
+        let db = self.get_db()?;
+        let read_opt_option = match read_opt {
+            None => None,
+            Some(opt) => Some(self.to_read_options(opt, py)?),
+        };
+        let read_opt = match &read_opt_option {
+            None => &self.read_opt,
+            Some(opt) => opt,
+        };
+        let value_result = if let Some(cf) = &self.column_family {
+            db.get_pinned_cf_opt(cf, key, read_opt)
+        } else {
+            db.get_pinned_opt(key, read_opt)
+        }
+        .map_err(rocksdb_error)?;
+        Ok(value_result.map(|slice| PyBytes::new(py, slice.as_ref())))
+    }
+
+    /// Insert key value bytes into database, bypassing the encoder.
+    ///
+    /// Notes:
+    ///     In non-raw mode, the bytes must be a valid encoding
+    ///     (starting with the type byte, as returned by `get_raw`)
+    ///     for the key value pair to be readable with `db[key]`.
+    ///
+    /// Args:
+    ///     key: the key bytes to store.
+    ///     value: the value bytes to store.
+    ///     write_opt: override preset write options
+    ///         (or use Rdict.set_write_options to preset a write options used by default).
+    #[pyo3(signature = (key, value, write_opt = None))]
+    fn put_raw(
+        &self,
+        key: &[u8],
+        value: &[u8],
+        write_opt: Option<&WriteOptionsPy>,
+    ) -> PyResult<()> {
+        self.check_writable()?;
+        let db = self.get_db()?;
+        let write_opt_option = write_opt.map(WriteOptions::from);
+        let write_opt = match &write_opt_option {
+            None => &self.write_opt,
+            Some(opt) => opt,
+        };
+        if let Some(cf) = &self.column_family {
+            db.put_cf_opt(cf, key, value, write_opt)
+        } else {
+            db.put_opt(key, value, write_opt)
+        }
+        .map_err(rocksdb_error)
+    }
+
+    /// Merge a value into the existing value of a key,
+    /// using the merge operator set by `Options.set_merge_operator_associative`.
+    ///
+    /// Args:
+    ///     key: the key.
+    ///     value: the merge operand.
+    ///     write_opt: override preset write options
+    ///         (or use Rdict.set_write_options to preset a write options used by default).
+    #[inline]
+    #[pyo3(signature = (key, value, write_opt = None))]
+    fn merge(
+        &self,
+        key: &Bound<PyAny>,
+        value: &Bound<PyAny>,
+        write_opt: Option<&WriteOptionsPy>,
+    ) -> PyResult<()> {
+        self.check_writable()?;
+        let db = self.get_db()?;
+        self.check_key_type(key)?;
+        self.check_value_type(value)?;
+        let key = self.encode_key(key)?;
+        let value = encode_value(value, &self.dumps, self.opt_py.raw_mode)?;
+        let write_opt_option = write_opt.map(WriteOptions::from);
+        let write_opt = match &write_opt_option {
+            None => &self.write_opt,
+            Some(opt) => opt,
+        };
+        if let Some(cf) = &self.column_family {
+            db.merge_cf_opt(cf, key, value, write_opt)
+        } else {
+            db.merge_opt(key, value, write_opt)
+        }
+        .map_err(rocksdb_error)
+    }
+
+    /// Insert a wide-column.
+    ///
+    /// The length of `names` and `values` must be the same.
+    /// Columns are stored sorted by name, so they are read back
+    /// (by `get_entity` or `entities()`) in name order rather
+    /// than in the order they are given.
+    ///
+    /// Args:
+    ///     key: the key.
+    ///     names: the names of the columns.
+    ///     values: the values of the columns.
+    ///     write_opt: override preset write options
+    ///         (or use Rdict.set_write_options to preset a write options used by default).
+    #[inline]
+    #[pyo3(signature = (key, names, values, write_opt = None))]
+    fn put_entity(
+        &self,
+        key: &Bound<PyAny>,
+        names: Vec<Bound<PyAny>>,
+        values: Vec<Bound<PyAny>>,
+        write_opt: Option<&WriteOptionsPy>,
+    ) -> PyResult<()> {
+        self.check_writable()?;
         let db = self.get_db()?;
-        let key = encode_key(key, self.opt_py.raw_mode)?;
+        self.check_key_type(key)?;
+        let key = self.encode_key(key)?;
         let write_opt_option = write_opt.map(WriteOptions::from);
         let write_opt = match &write_opt_option {
             None => &self.write_opt,
@@ -561,15 +1486,17 @@ impl Rdict {
             names_vec.push(encode_value(name, &self.dumps, self.opt_py.raw_mode)?);
         }
         for value in values.iter() {
+            self.check_value_type(value)?;
             values_vec.push(encode_value(value, &self.dumps, self.opt_py.raw_mode)?);
         }
         db.put_entity_cf_opt(&cf, key, &names_vec, &values_vec, write_opt)
-            .map_err(|e| PyException::new_err(e.to_string()))
+            .map_err(rocksdb_error)
     }
 
     fn __contains__(&self, key: &Bound<PyAny>) -> PyResult<bool> {
         let db = self.get_db()?;
-        let key = encode_key(key, self.opt_py.raw_mode)?;
+        self.check_key_type(key)?;
+        let key = self.encode_key(key)?;
         let may_exist = if let Some(cf) = &self.column_family {
             db.key_may_exist_cf_opt(cf, &key[..], &self.read_opt)
         } else {
@@ -586,7 +1513,7 @@ impl Rdict {
                     None => Ok(false),
                     Some(_) => Ok(true),
                 },
-                Err(e) => Err(PyException::new_err(e.to_string())),
+                Err(e) => Err(rocksdb_error(e)),
             }
         } else {
             Ok(false)
@@ -614,6 +1541,7 @@ impl Rdict {
     ///
     /// Args:
     ///     key: Key to check
+    ///     fetch: also return the value if it is found without IO
     ///     read_opt: ReadOptions
     ///
     /// Returns:
@@ -633,10 +1561,11 @@ impl Rdict {
         py: Python<'py>,
     ) -> PyResult<Bound<'py, PyAny>> {
         let db = self.get_db()?;
-        let key = encode_key(key, self.opt_py.raw_mode)?;
+        self.check_key_type(key)?;
+        let key = self.encode_key(key)?;
         let read_opt_option = match read_opt {
             None => None,
-            Some(opt) => Some(opt.to_read_options(self.opt_py.raw_mode, py)?),
+            Some(opt) => Some(self.to_read_options(opt, py)?),
         };
         let read_opt = match &read_opt_option {
             None => &self.read_opt,
@@ -670,7 +1599,20 @@ impl Rdict {
     }
 
     fn __delitem__(&self, key: &Bound<PyAny>) -> PyResult<()> {
-        self.delete(key, None)
+        self.check_writable()?;
+        let py = key.py();
+        let mut write_buffer = self.lock_write_buffer(py);
+        let Some(batch) = write_buffer.as_mut() else {
+            drop(write_buffer);
+            return self.delete(key, None);
+        };
+        self.check_key_type(key)?;
+        let key = self.encode_key(key)?;
+        match &self.column_family {
+            None => batch.delete(key),
+            Some(cf) => batch.delete_cf(cf, key),
+        }
+        self.flush_writes_if_full(batch, py)
     }
 
     /// Delete entry from the database.
@@ -682,8 +1624,10 @@ impl Rdict {
     #[inline]
     #[pyo3(signature = (key, write_opt = None))]
     fn delete(&self, key: &Bound<PyAny>, write_opt: Option<&WriteOptionsPy>) -> PyResult<()> {
+        self.check_writable()?;
         let db = self.get_db()?;
-        let key = encode_key(key, self.opt_py.raw_mode)?;
+        self.check_key_type(key)?;
+        let key = self.encode_key(key)?;
 
         let write_opt_option = write_opt.map(WriteOptions::from);
         let write_opt = match &write_opt_option {
@@ -695,11 +1639,118 @@ impl Rdict {
         } else {
             db.delete_opt(key, write_opt)
         }
-        .map_err(|e| PyException::new_err(e.to_string()))
+        .map_err(rocksdb_error)
+    }
+
+    /// Delete the versions of a key up to timestamp `ts`, for column
+    /// families with user-defined timestamps.
+    ///
+    /// Args:
+    ///     key: the key.
+    ///     ts: the timestamp of the deletion, an unsigned 64-bit int.
+    ///     write_opt: override preset write options
+    ///         (or use Rdict.set_write_options to preset a write options used by default).
+    #[pyo3(signature = (key, ts, write_opt = None))]
+    fn delete_with_ts(
+        &self,
+        key: &Bound<PyAny>,
+        ts: u64,
+        write_opt: Option<&WriteOptionsPy>,
+    ) -> PyResult<()> {
+        self.check_writable()?;
+        self.check_user_timestamp_mode()?;
+        let db = self.get_db()?;
+        self.check_key_type(key)?;
+        let key = self.encode_key(key)?;
+        let write_opt_option = write_opt.map(WriteOptions::from);
+        let write_opt = match &write_opt_option {
+            None => &self.write_opt,
+            Some(opt) => opt,
+        };
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        db.delete_cf_with_ts_opt(&cf, key, encode_u64_ts(ts), write_opt)
+            .map_err(rocksdb_error)
+    }
+
+    /// Buffer `db[key] = value` and `del db[key]` into an internal WriteBatch.
+    ///
+    /// The buffered operations are written in a single batch every
+    /// `max_ops` operations, when `flush_writes()` is called, or when
+    /// the Rdict is closed or dropped. This greatly speeds up
+    /// tight `db[k] = v` loops.
+    ///
+    /// Notes:
+    ///     Buffered writes are not visible to reads until they are
+    ///     written. Only `__setitem__` and `__delitem__` are buffered,
+    ///     explicit `put`, `delete` and `write` calls go to the database
+    ///     immediately.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict
+    ///
+    ///         db = Rdict("./test_dir")
+    ///         db.enable_write_buffering(max_ops=10000)
+    ///         for i in range(1000000):
+    ///             db[i] = i
+    ///         db.flush_writes()
+    ///         assert db[999999] == 999999
+    ///
+    /// Args:
+    ///     max_ops: number of buffered operations that triggers a write.
+    #[pyo3(signature = (max_ops = 10000))]
+    fn enable_write_buffering(&self, max_ops: usize, py: Python) -> PyResult<()> {
+        if max_ops == 0 {
+            return Err(PyValueError::new_err("max_ops must be greater than 0"));
+        }
+        let mut write_buffer = self.lock_write_buffer(py);
+        match write_buffer.as_mut() {
+            Some(batch) => self.write_buffered(batch, py)?,
+            None => *write_buffer = Some(WriteBatch::default()),
+        }
+        self.write_buffer_max_ops
+            .store(max_ops, atomic::Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Write all buffered operations and stop buffering.
+    fn disable_write_buffering(&self, py: Python) -> PyResult<()> {
+        let mut write_buffer = self.lock_write_buffer(py);
+        if let Some(batch) = write_buffer.as_mut() {
+            self.write_buffered(batch, py)?;
+        }
+        *write_buffer = None;
+        Ok(())
+    }
+
+    /// Write all operations buffered by `enable_write_buffering` to the database.
+    ///
+    /// If the write fails, the operations stay buffered.
+    fn flush_writes(&self, py: Python) -> PyResult<()> {
+        match self.lock_write_buffer(py).as_mut() {
+            Some(batch) => self.write_buffered(batch, py),
+            None => Ok(()),
+        }
     }
 
     /// Reversible for iterating over keys and values.
     ///
+    /// Notes:
+    ///     Seeking and stepping the iterator (`seek*`, `next`, `prev`, and
+    ///     each step of `items()`, `keys()`, `values()`...) release the GIL,
+    ///     so other Python threads keep running while blocks are read.
+    ///     Decoding keys and values still holds the GIL. To let the disk
+    ///     reads of long scans overlap with Python-side work, prefetch
+    ///     blocks with `ReadOptions.set_readahead_size`, or with
+    ///     `ReadOptions.set_async_io(True)`.
+    ///
     /// Examples:
     ///     ::
     ///
@@ -753,6 +1804,7 @@ impl Rdict {
             read_opt,
             &self.loads,
             self.opt_py.raw_mode,
+            self.opt_py.normalize_str_bytes_keys,
             py,
         )
     }
@@ -771,63 +1823,211 @@ impl Rdict {
     ///         or the nearest next key for iteration
     ///         (depending on iteration direction).
     ///     read_opt: ReadOptions
-    #[pyo3(signature = (backwards = false, from_key = None, read_opt = None))]
+    ///     to_key: stop the iteration at this key: it is the excluded
+    ///         `iterate_upper_bound` when iterating forwards, and the included
+    ///         `iterate_lower_bound` when iterating backwards. It overrides
+    ///         the bound of `read_opt`, and enables `auto_total_order_seek`.
+    #[pyo3(signature = (backwards = false, from_key = None, read_opt = None, to_key = None))]
     fn items(
         &self,
         backwards: bool,
         from_key: Option<&Bound<PyAny>>,
         read_opt: Option<&ReadOptionsPy>,
+        to_key: Option<Bound<PyAny>>,
         py: Python,
     ) -> PyResult<RdictItems> {
-        RdictItems::new(self.iter(read_opt, py)?, backwards, from_key)
+        let iter = self.bounded_iter(backwards, to_key, read_opt, py)?;
+        RdictItems::new(iter, backwards, from_key, py)
     }
 
-    /// Iterate through all keys
+    /// Iterate through the items whose keys start with a prefix.
+    ///
+    /// The iteration stops at the first key without the prefix.
+    /// In non-raw mode, the prefix is compared with the encoded keys,
+    /// so a `str` prefix matches `str` keys starting with it, a `bytes`
+    /// prefix `bytes` keys, and a `tuple` prefix the tuple keys starting
+    /// with its components.
+    ///
+    /// Notes:
+    ///     If the prefix extractor of the column family covers the prefix
+    ///     (i.e. the prefix is at least as long as the extracted prefixes),
+    ///     this is a prefix seek (`prefix_same_as_start`) which can use
+    ///     prefix bloom filters. Otherwise, it falls back to a total order seek.
+    ///     In raw mode with the default bytewise comparator, the iteration
+    ///     is also bounded by the prefix and its successor
+    ///     (e.g. `b"ab\xff"` -> `b"ac"`), so that RocksDB does not
+    ///     read past the last key with the prefix.
+    ///     `int` prefixes are not supported, see `range_numeric`.
     ///
     /// Examples:
     ///     ::
     ///
-    ///         all_keys = [k for k in db.keys()]
+    ///         db["user:1"] = 1
+    ///         db["user:2"] = 2
+    ///         db["video:1"] = 3
+    ///         assert [k for k, _ in db.iter_prefix("user:")] == ["user:1", "user:2"]
     ///
     /// Args:
-    ///     backwards: iteration direction, forward if `False`.
-    ///     from_key: iterate from key, first seek to this key
-    ///         or the nearest next key for iteration
-    ///         (depending on iteration direction).
-    ///     read_opt: ReadOptions
-    #[pyo3(signature = (backwards = false, from_key = None, read_opt = None))]
-    fn keys(
+    ///     prefix: the prefix of keys.
+    ///     read_opt: ReadOptions, its `prefix_same_as_start`
+    ///         and `total_order_seek` (and iterate bounds in raw mode)
+    ///         are overridden.
+    #[pyo3(signature = (prefix, read_opt = None))]
+    fn iter_prefix(
         &self,
-        backwards: bool,
-        from_key: Option<&Bound<PyAny>>,
+        prefix: &Bound<PyAny>,
         read_opt: Option<&ReadOptionsPy>,
         py: Python,
-    ) -> PyResult<RdictKeys> {
-        RdictKeys::new(self.iter(read_opt, py)?, backwards, from_key)
-    }
-
-    /// Iterate through all values.
-    ///
-    /// Examples:
-    ///     ::
-    ///
-    ///         all_keys = [v for v in db.values()]
-    ///
-    /// Args:
-    ///     backwards: iteration direction, forward if `False`.
+    ) -> PyResult<RdictItems> {
+        let prefix_bytes = self.encode_key(prefix)?.into_owned();
+        if !self.opt_py.raw_mode && prefix_bytes.first() == Some(&3) {
+            return Err(PyValueError::new_err(
+                "int prefixes are not supported, use `range_numeric`",
+            ));
+        }
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        let cf_name = String::from_utf8_lossy(&column_family_name(&cf)).into_owned();
+        let prefix_seek = match self.slice_transforms.read().unwrap().get(&cf_name) {
+            Some(SliceTransformType::Fixed(len)) | Some(SliceTransformType::MaxLen(len)) => {
+                prefix_bytes.len() >= *len
+            }
+            Some(SliceTransformType::Noop) | None => false,
+        };
+        let mut read_opt = match read_opt {
+            None => ReadOptionsPy::default(py)?,
+            Some(opt) => opt.clone(),
+        };
+        read_opt.set_prefix_same_as_start(prefix_seek);
+        read_opt.set_total_order_seek(!prefix_seek);
+        // the successor only bounds the prefix in bytewise order
+        let bytewise = !self
+            .python_comparators
+            .read()
+            .unwrap()
+            .contains_key(&cf_name)
+            && matches!(
+                self.comparators.read().unwrap().get(&cf_name),
+                None | Some(BuiltinComparator::Bytewise)
+            );
+        if self.opt_py.raw_mode && bytewise {
+            read_opt.set_iterate_lower_bound(PyBytes::new(py, &prefix_bytes).into_any())?;
+            // no upper bound if all bytes are 0xff
+            let upper_bound = match prefix_successor(&prefix_bytes) {
+                Some(successor) => PyBytes::new(py, &successor).into_any(),
+                None => py.None().into_bound(py),
+            };
+            read_opt.set_iterate_upper_bound(upper_bound)?;
+        }
+        let mut iter = self.iter(Some(&read_opt), py)?;
+        iter.prefix = Some(prefix_bytes);
+        RdictItems::new(iter, false, Some(prefix), py)
+    }
+
+    /// Iterate through the items with integer keys in `[lo, hi]`, in numeric order.
+    ///
+    /// Notes:
+    ///     Integer keys are compared numerically by the comparator used
+    ///     in non-raw mode, so this is a bounded forward scan from `lo`
+    ///     with `hi + 1` as the iterate upper bound. Keys of other types
+    ///     are never included. Not available in raw mode.
+    ///
+    /// Examples:
+    ///     ::
+    ///
+    ///         for i in range(-100, 100):
+    ///             db[i] = i
+    ///         assert [k for k, _ in db.range_numeric(-2, 2)] == [-2, -1, 0, 1, 2]
+    ///
+    /// Args:
+    ///     lo: lower bound (included).
+    ///     hi: upper bound (included).
+    ///     read_opt: ReadOptions, its iterate_upper_bound is overridden.
+    #[pyo3(signature = (lo, hi, read_opt = None))]
+    fn range_numeric(
+        &self,
+        lo: BigInt,
+        hi: BigInt,
+        read_opt: Option<&ReadOptionsPy>,
+        py: Python,
+    ) -> PyResult<RdictItems> {
+        if self.opt_py.raw_mode {
+            return Err(PyException::new_err(
+                "range_numeric is not supported in raw mode",
+            ));
+        }
+        let mut read_opt = match read_opt {
+            None => ReadOptionsPy::default(py)?,
+            Some(opt) => opt.clone(),
+        };
+        read_opt.set_iterate_upper_bound((hi + 1).into_pyobject(py)?.into_any())?;
+        let lo = lo.into_pyobject(py)?.into_any();
+        RdictItems::new(self.iter(Some(&read_opt), py)?, false, Some(&lo), py)
+    }
+
+    /// Iterate through all keys
+    ///
+    /// Examples:
+    ///     ::
+    ///
+    ///         all_keys = [k for k in db.keys()]
+    ///
+    /// Args:
+    ///     backwards: iteration direction, forward if `False`.
+    ///     from_key: iterate from key, first seek to this key
+    ///         or the nearest next key for iteration
+    ///         (depending on iteration direction).
+    ///     read_opt: ReadOptions
+    ///     to_key: stop the iteration at this key: it is the excluded
+    ///         `iterate_upper_bound` when iterating forwards, and the included
+    ///         `iterate_lower_bound` when iterating backwards. It overrides
+    ///         the bound of `read_opt`, and enables `auto_total_order_seek`.
+    #[pyo3(signature = (backwards = false, from_key = None, read_opt = None, to_key = None))]
+    fn keys(
+        &self,
+        backwards: bool,
+        from_key: Option<&Bound<PyAny>>,
+        read_opt: Option<&ReadOptionsPy>,
+        to_key: Option<Bound<PyAny>>,
+        py: Python,
+    ) -> PyResult<RdictKeys> {
+        let iter = self.bounded_iter(backwards, to_key, read_opt, py)?;
+        RdictKeys::new(iter, backwards, from_key, py)
+    }
+
+    /// Iterate through all values.
+    ///
+    /// Examples:
+    ///     ::
+    ///
+    ///         all_keys = [v for v in db.values()]
+    ///
+    /// Args:
+    ///     backwards: iteration direction, forward if `False`.
     ///     from_key: iterate from key, first seek to this key
     ///         or the nearest next key for iteration
     ///         (depending on iteration direction).
     ///     read_opt: ReadOptions, must have the same `raw_mode` argument.
-    #[pyo3(signature = (backwards = false, from_key = None, read_opt = None))]
+    ///     to_key: stop the iteration at this key: it is the excluded
+    ///         `iterate_upper_bound` when iterating forwards, and the included
+    ///         `iterate_lower_bound` when iterating backwards. It overrides
+    ///         the bound of `read_opt`, and enables `auto_total_order_seek`.
+    #[pyo3(signature = (backwards = false, from_key = None, read_opt = None, to_key = None))]
     fn values(
         &self,
         backwards: bool,
         from_key: Option<&Bound<PyAny>>,
         read_opt: Option<&ReadOptionsPy>,
+        to_key: Option<Bound<PyAny>>,
         py: Python,
     ) -> PyResult<RdictValues> {
-        RdictValues::new(self.iter(read_opt, py)?, backwards, from_key)
+        let iter = self.bounded_iter(backwards, to_key, read_opt, py)?;
+        RdictValues::new(iter, backwards, from_key, py)
     }
 
     /// Iterate through all values as widecolumns
@@ -851,7 +2051,7 @@ impl Rdict {
         read_opt: Option<&ReadOptionsPy>,
         py: Python,
     ) -> PyResult<RdictColumns> {
-        RdictColumns::new(self.iter(read_opt, py)?, backwards, from_key)
+        RdictColumns::new(self.iter(read_opt, py)?, backwards, from_key, py)
     }
 
     /// Iterate through all keys and entities pairs.
@@ -876,7 +2076,7 @@ impl Rdict {
         read_opt: Option<&ReadOptionsPy>,
         py: Python,
     ) -> PyResult<RdictEntities> {
-        RdictEntities::new(self.iter(read_opt, py)?, backwards, from_key)
+        RdictEntities::new(self.iter(read_opt, py)?, backwards, from_key, py)
     }
 
     /// Manually flush the current column family.
@@ -902,7 +2102,7 @@ impl Rdict {
                 db.flush_opt(&f_opt)
             }
         })
-        .map_err(|e| PyException::new_err(e.into_string()))
+        .map_err(rocksdb_error)
     }
 
     /// Flushes the WAL buffer. If `sync` is set to `true`, also syncs
@@ -911,7 +2111,119 @@ impl Rdict {
     fn flush_wal(&self, sync: bool, py: Python) -> PyResult<()> {
         let db = self.get_db()?;
         py.allow_threads(|| db.flush_wal(sync))
-            .map_err(|e| PyException::new_err(e.into_string()))
+            .map_err(rocksdb_error)
+    }
+
+    /// Flushes several column families at once.
+    ///
+    /// Notes:
+    ///     The flush is atomic (all or none of the column families
+    ///     are flushed) if `Options.set_atomic_flush(True)` is set,
+    ///     which keeps the column families consistent with each other
+    ///     when the WAL is disabled.
+    ///
+    /// Args:
+    ///     cf_names: names of the column families to flush.
+    ///     wait (bool): whether to wait for the flush to finish.
+    #[pyo3(signature = (cf_names, wait = true))]
+    fn flush_cfs(&self, cf_names: Vec<String>, wait: bool, py: Python) -> PyResult<()> {
+        let db = self.get_db()?;
+        let cfs = cf_names
+            .iter()
+            .map(|name| {
+                unsafe { db.cf_handle_unbounded(name) }.ok_or_else(|| {
+                    PyException::new_err(format!("column name `{name}` does not exist"))
+                })
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        py.allow_threads(|| {
+            let mut f_opt = FlushOptions::new();
+            f_opt.set_wait(wait);
+            db.flush_cfs_opt(&cfs.iter().collect::<Vec<_>>(), &f_opt)
+        })
+        .map_err(rocksdb_error)
+    }
+
+    /// Total size in bytes of the live WAL files.
+    ///
+    /// Notes:
+    ///     WAL files are looked up in the database directory,
+    ///     so this does not work with `Options.set_wal_dir`.
+    ///     Archived WAL files (kept for `Options.set_wal_ttl_seconds`
+    ///     or `Options.set_wal_size_limit_mb`) are not counted.
+    fn wal_size(&self) -> PyResult<u64> {
+        let db = self.get_db()?;
+        // WAL files older than this are obsolete
+        let min_log_number = db
+            .property_int_value("rocksdb.min-log-number-to-keep")
+            .map_err(rocksdb_error)?
+            .unwrap_or(0);
+        let mut size = 0;
+        for entry in fs::read_dir(db.path()).map_err(|e| PyException::new_err(e.to_string()))? {
+            let entry = entry.map_err(|e| PyException::new_err(e.to_string()))?;
+            let log_number = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_suffix(".log"))
+                .and_then(|number| number.parse::<u64>().ok());
+            if matches!(log_number, Some(number) if number >= min_log_number) {
+                let metadata = entry
+                    .metadata()
+                    .map_err(|e| PyException::new_err(e.to_string()))?;
+                size += metadata.len();
+            }
+        }
+        Ok(size)
+    }
+
+    /// Path of the latest OPTIONS file of the database.
+    ///
+    /// RocksDB writes the effective options of all column families to a
+    /// new `OPTIONS-NNNNNN` file when the database is opened, and when
+    /// column families are created, dropped, or changed by `set_options`.
+    /// It is a human-readable ini file, which `Options.load_latest` reads
+    /// back to reopen the database with identical settings.
+    fn latest_options_file(&self) -> PyResult<String> {
+        let db = self.get_db()?;
+        let mut latest: Option<(u64, PathBuf)> = None;
+        for entry in fs::read_dir(db.path()).map_err(|e| PyException::new_err(e.to_string()))? {
+            let entry = entry.map_err(|e| PyException::new_err(e.to_string()))?;
+            let number = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix("OPTIONS-"))
+                .and_then(|number| number.parse::<u64>().ok());
+            match (number, &latest) {
+                (Some(number), Some((n, _))) if number <= *n => {}
+                (Some(number), _) => latest = Some((number, entry.path())),
+                (None, _) => {}
+            }
+        }
+        match latest {
+            Some((_, path)) => Ok(path.to_string_lossy().into_owned()),
+            None => Err(PyException::new_err("no OPTIONS file found")),
+        }
+    }
+
+    /// Start a new WAL file, and make the previous ones obsolete.
+    ///
+    /// This flushes the memtables of all column families (writes in
+    /// a WAL file can only be dropped once all column families are
+    /// flushed), so that RocksDB switches to a new WAL file and
+    /// deletes or archives the previous ones.
+    fn rotate_wal(&self, py: Python) -> PyResult<()> {
+        let db = self.get_db()?;
+        let names = DB::list_cf(&self.opt_py.inner_opt, db.path()).map_err(rocksdb_error)?;
+        let cfs = names
+            .iter()
+            .filter_map(|name| unsafe { db.cf_handle_unbounded(name) })
+            .collect::<Vec<_>>();
+        py.allow_threads(|| {
+            let mut f_opt = FlushOptions::new();
+            f_opt.set_wait(true);
+            db.flush_cfs_opt(&cfs.iter().collect::<Vec<_>>(), &f_opt)
+        })
+        .map_err(rocksdb_error)
     }
 
     /// Creates column family with given name and options.
@@ -931,6 +2243,7 @@ impl Rdict {
                 self.opt_py.raw_mode
             )));
         }
+        options.check_memtable_factory(name)?;
         // write slice_transform info into config file
         if let Some(slice_transform) = options.prefix_extractor {
             self.slice_transforms
@@ -938,17 +2251,28 @@ impl Rdict {
                 .unwrap()
                 .insert(name.to_string(), slice_transform);
         }
+        if let Some(comparator) = options.comparator {
+            self.comparators
+                .write()
+                .unwrap()
+                .insert(name.to_string(), comparator);
+        }
+        if let Some(error) = &options.python_comparator {
+            self.python_comparators
+                .write()
+                .unwrap()
+                .insert(name.to_string(), error.clone());
+        }
         self.dump_config()?;
         db.create_cf(name, &options.inner_opt)
-            .map_err(|e| PyException::new_err(e.to_string()))?;
+            .map_err(rocksdb_error)?;
         self.get_column_family(name, py)
     }
 
     /// Drops the column family with the given name
     fn drop_column_family(&self, name: &str) -> PyResult<()> {
         let db = self.get_db()?;
-        db.drop_cf(name)
-            .map_err(|e| PyException::new_err(e.to_string()))
+        db.drop_cf(name).map_err(rocksdb_error)
     }
 
     /// Get a column family Rdict
@@ -969,7 +2293,7 @@ impl Rdict {
                 db: self.db.clone(),
                 write_opt: (&self.write_opt_py).into(),
                 flush_opt: self.flush_opt,
-                read_opt: self.read_opt_py.to_read_options(self.opt_py.raw_mode, py)?,
+                read_opt: self.to_read_options(&self.read_opt_py, py)?,
                 loads: self.loads.clone(),
                 dumps: self.dumps.clone(),
                 column_family: Some(cf),
@@ -978,6 +2302,13 @@ impl Rdict {
                 opt_py: self.opt_py.clone(),
                 access_type: self.access_type.clone(),
                 slice_transforms: self.slice_transforms.clone(),
+                comparators: self.comparators.clone(),
+                python_comparators: self.python_comparators.clone(),
+                write_buffer: Mutex::new(None),
+                write_buffer_max_ops: AtomicUsize::new(0),
+                drop_behavior: self.drop_behavior,
+                key_type: None,
+                value_type: None,
             }),
         }
     }
@@ -1012,6 +2343,36 @@ impl Rdict {
         }
     }
 
+    /// Names of the column families of the open database.
+    ///
+    /// Unlike the static `Rdict.list_cf`, this does not need the path
+    /// and options of the database, and only lists the column families
+    /// that have been opened (or created) by this Rdict.
+    fn cf_names(&self) -> PyResult<Vec<String>> {
+        self.live_cf_names()
+    }
+
+    /// All column families of the open database.
+    ///
+    /// Returns:
+    ///     a dict from column family names to column family Rdicts,
+    ///     as returned by `get_column_family`, the `"default"` one included.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         for name, cf in db.column_families().items():
+    ///             print(name, sum(1 for _ in cf.keys()))
+    fn column_families(&self, py: Python) -> PyResult<HashMap<String, Rdict>> {
+        self.live_cf_names()?
+            .into_iter()
+            .map(|name| {
+                let cf = self.get_column_family(&name, py)?;
+                Ok((name, cf))
+            })
+            .collect()
+    }
+
     /// A snapshot of the current column family.
     ///
     /// Examples:
@@ -1061,6 +2422,7 @@ impl Rdict {
         opts: Py<IngestExternalFileOptionsPy>,
         py: Python,
     ) -> PyResult<()> {
+        self.check_writable()?;
         let db = self.get_db()?;
         let opts = &opts.borrow(py).0;
         if let Some(cf) = &self.column_family {
@@ -1068,15 +2430,66 @@ impl Rdict {
         } else {
             db.ingest_external_file_opts(opts, paths)
         }
-        .map_err(|e| PyException::new_err(e.to_string()))
+        .map_err(rocksdb_error)
+    }
+
+    /// Bulk load key-value pairs into the current column family,
+    /// by writing them into SST files and ingesting the files.
+    ///
+    /// This is much faster than writing the pairs one by one, since
+    /// it bypasses the memtable and the WAL. The GIL is released
+    /// while the files are written and ingested.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict
+    ///
+    ///         db = Rdict("./test_dir")
+    ///         db.bulk_load(((i, i**2) for i in range(1_000_000)), "./tmp_sst")
+    ///
+    /// Args:
+    ///     items: an iterable of `(key, value)` pairs, sorted by key
+    ///         in strictly ascending order (as in `db.keys()`).
+    ///     tmp_dir: directory for the temporary SST files,
+    ///         they are removed after ingestion.
+    ///     max_file_size: start a new SST file once a file reaches this size.
+    #[pyo3(signature = (items, tmp_dir, max_file_size = 256 * 1024 * 1024))]
+    fn bulk_load(
+        &self,
+        items: &Bound<PyAny>,
+        tmp_dir: &str,
+        max_file_size: u64,
+        py: Python,
+    ) -> PyResult<()> {
+        let db = self.get_db()?;
+        fs::create_dir_all(tmp_dir).map_err(|e| PyException::new_err(e.to_string()))?;
+        let mut paths = Vec::new();
+        let result = self
+            .write_sst_files(items, Path::new(tmp_dir), max_file_size, &mut paths, py)
+            .and_then(|_| {
+                if paths.is_empty() {
+                    return Ok(());
+                }
+                let mut opts = IngestExternalFileOptions::default();
+                opts.set_move_files(true);
+                py.allow_threads(|| match &self.column_family {
+                    Some(cf) => db.ingest_external_file_cf_opts(cf, &opts, paths.clone()),
+                    None => db.ingest_external_file_opts(&opts, paths.clone()),
+                })
+                .map_err(rocksdb_error)
+            });
+        for path in paths {
+            let _ = fs::remove_file(path);
+        }
+        result
     }
 
     /// Tries to catch up with the primary by reading as much as possible from the
     /// log files.
     pub fn try_catch_up_with_primary(&self) -> PyResult<()> {
         let db = self.get_db()?;
-        db.try_catch_up_with_primary()
-            .map_err(|e| PyException::new_err(e.to_string()))
+        db.try_catch_up_with_primary().map_err(rocksdb_error)
     }
 
     /// Request stopping background work, if wait is true wait until it's done.
@@ -1086,39 +2499,266 @@ impl Rdict {
         Ok(())
     }
 
+    /// Insert many key value pairs atomically, like `dict.update`.
+    ///
+    /// All pairs are encoded first, and written to the current column
+    /// family with a single WriteBatch, so nothing is written if a key
+    /// or value cannot be encoded.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict
+    ///
+    ///         db = Rdict("./test_dir")
+    ///         db.update({"a": 1, "b": 2})
+    ///         db.update([("c", 3), ("d", 4)])
+    ///
+    /// Args:
+    ///     other: a mapping, or an iterable of `(key, value)` pairs.
+    ///     write_opt: override preset write options
+    ///         (or use Rdict.set_write_options to preset a write options used by default).
+    #[pyo3(signature = (other, write_opt = None))]
+    fn update(
+        &self,
+        other: &Bound<PyAny>,
+        write_opt: Option<&WriteOptionsPy>,
+        py: Python,
+    ) -> PyResult<()> {
+        self.check_writable()?;
+        let db = self.get_db()?;
+        let raw_mode = self.opt_py.raw_mode;
+        let mut batch = WriteBatch::default();
+        let mut put = |key: &Bound<PyAny>, value: &Bound<PyAny>| -> PyResult<()> {
+            self.check_key_type(key)?;
+            self.check_value_type(value)?;
+            let key = self.encode_key(key)?;
+            let value = encode_value(value, &self.dumps, raw_mode)?;
+            match &self.column_family {
+                None => batch.put(key, value),
+                Some(cf) => batch.put_cf(cf, key, value),
+            }
+            Ok(())
+        };
+        if let Ok(dict) = other.downcast::<PyDict>() {
+            for (key, value) in dict.iter() {
+                put(&key, &value)?;
+            }
+        } else if other.hasattr("keys")? {
+            for key in other.call_method0("keys")?.try_iter()? {
+                let key = key?;
+                put(&key, &other.get_item(&key)?)?;
+            }
+        } else {
+            for item in other.try_iter()? {
+                let (key, value): (Bound<PyAny>, Bound<PyAny>) = item?.extract()?;
+                put(&key, &value)?;
+            }
+        }
+        let write_opt_option = write_opt.map(WriteOptions::from);
+        let write_opt = match &write_opt_option {
+            None => &self.write_opt,
+            Some(opt) => opt,
+        };
+        py.allow_threads(|| db.write_opt(batch, write_opt))
+            .map_err(rocksdb_error)
+    }
+
+    /// Insert many key value pairs with several threads.
+    ///
+    /// The pairs are encoded and split by key into one WriteBatch per
+    /// thread, then the batches are written concurrently without
+    /// holding the GIL. Like `update`, the pairs are written to the
+    /// current column family.
+    ///
+    /// Notes:
+    ///     Unlike `update`, this is not atomic: the batches are
+    ///     independent, so if one of them fails, the others may
+    ///     already be written. The same key always goes to the same
+    ///     batch, so its last value wins, but there is no ordering
+    ///     between different keys. All the pairs are held in memory
+    ///     until they are written, so split very large inputs into
+    ///     several calls.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict
+    ///
+    ///         db = Rdict("./test_dir")
+    ///         db.write_parallel(((i, i) for i in range(100_000)), threads=4)
+    ///
+    ///         # write to a column family
+    ///         cf = db.create_column_family("cf")
+    ///         cf.write_parallel({"a": 1, "b": 2})
+    ///
+    /// Args:
+    ///     items: a dict, or an iterable of `(key, value)` pairs.
+    ///     threads: the number of threads, defaults to the number of CPUs.
+    ///     write_opt: override preset write options
+    ///         (or use Rdict.set_write_options to preset a write options used by default).
+    #[pyo3(signature = (items, threads = None, write_opt = None))]
+    fn write_parallel(
+        &self,
+        items: &Bound<PyAny>,
+        threads: Option<usize>,
+        write_opt: Option<&WriteOptionsPy>,
+        py: Python,
+    ) -> PyResult<()> {
+        self.check_writable()?;
+        let db = self.get_db()?;
+        let threads = match threads {
+            None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            Some(0) => return Err(PyValueError::new_err("threads must be at least 1")),
+            Some(n) => n,
+        };
+        let raw_mode = self.opt_py.raw_mode;
+        let mut batches: Vec<WriteBatch> = (0..threads).map(|_| WriteBatch::default()).collect();
+        let mut put = |key: &Bound<PyAny>, value: &Bound<PyAny>| -> PyResult<()> {
+            self.check_key_type(key)?;
+            self.check_value_type(value)?;
+            let key = self.encode_key(key)?;
+            let value = encode_value(value, &self.dumps, raw_mode)?;
+            let mut hasher = DefaultHasher::new();
+            key.hash(&mut hasher);
+            let batch = &mut batches[(hasher.finish() % threads as u64) as usize];
+            match &self.column_family {
+                None => batch.put(key, value),
+                Some(cf) => batch.put_cf(cf, key, value),
+            }
+            Ok(())
+        };
+        if let Ok(dict) = items.downcast::<PyDict>() {
+            for (key, value) in dict.iter() {
+                put(&key, &value)?;
+            }
+        } else {
+            for item in items.try_iter()? {
+                let (key, value): (Bound<PyAny>, Bound<PyAny>) = item?.extract()?;
+                put(&key, &value)?;
+            }
+        }
+        let write_opt_option = write_opt.map(WriteOptions::from);
+        let write_opt = match &write_opt_option {
+            None => &self.write_opt,
+            Some(opt) => opt,
+        };
+        py.allow_threads(|| {
+            std::thread::scope(|s| {
+                let handles: Vec<_> = batches
+                    .into_iter()
+                    .filter(|batch| !batch.is_empty())
+                    .map(|batch| s.spawn(move || db.write_opt(batch, write_opt)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+                    .collect::<Result<(), _>>()
+            })
+        })
+        .map_err(rocksdb_error)
+    }
+
+    /// Delete many keys atomically.
+    ///
+    /// All keys are encoded first, and deleted from the current column
+    /// family with a single WriteBatch, so nothing is deleted if a key
+    /// cannot be encoded. Missing keys are ignored, like in `delete`.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict
+    ///
+    ///         db = Rdict("./test_dir")
+    ///         db.update({"a": 1, "b": 2, "c": 3})
+    ///         db.delete_batch(["a", "c"])
+    ///
+    /// Args:
+    ///     keys: an iterable of keys.
+    ///     write_opt: override preset write options
+    ///         (or use Rdict.set_write_options to preset a write options used by default).
+    #[pyo3(signature = (keys, write_opt = None))]
+    fn delete_batch(
+        &self,
+        keys: &Bound<PyAny>,
+        write_opt: Option<&WriteOptionsPy>,
+        py: Python,
+    ) -> PyResult<()> {
+        self.check_writable()?;
+        let db = self.get_db()?;
+        let mut batch = WriteBatch::default();
+        for key in keys.try_iter()? {
+            let key = key?;
+            self.check_key_type(&key)?;
+            let key = self.encode_key(&key)?;
+            match &self.column_family {
+                None => batch.delete(key),
+                Some(cf) => batch.delete_cf(cf, key),
+            }
+        }
+        let write_opt_option = write_opt.map(WriteOptions::from);
+        let write_opt = match &write_opt_option {
+            None => &self.write_opt,
+            Some(opt) => opt,
+        };
+        py.allow_threads(|| db.write_opt(batch, write_opt))
+            .map_err(rocksdb_error)
+    }
+
     /// WriteBatch
     ///
     /// Notes:
     ///     This WriteBatch does not write to the current column family.
+    ///     A WriteBatchWithIndex always writes to the default column family,
+    ///     so it can only be written with an Rdict on the default column family.
     ///
     /// Args:
     ///     write_batch: WriteBatch instance. This instance will be consumed.
+    ///         A WriteBatchWithIndex is also accepted, and is not consumed.
     ///     write_opt: use default value if not provided.
     #[pyo3(signature = (write_batch, write_opt = None))]
     pub fn write(
         &self,
-        write_batch: &mut WriteBatchPy,
+        write_batch: &Bound<PyAny>,
         write_opt: Option<&WriteOptionsPy>,
     ) -> PyResult<()> {
+        self.check_writable()?;
         let db = self.get_db()?;
-        if self.opt_py.raw_mode != write_batch.raw_mode {
-            return if self.opt_py.raw_mode {
-                Err(PyException::new_err(
-                    "must set raw_mode=True for WriteBatch",
-                ))
-            } else {
-                Err(PyException::new_err(
-                    "must set raw_mode=False for WriteBatch",
-                ))
-            };
-        }
+        let batch = if let Ok(write_batch) = write_batch.downcast::<WriteBatchPy>() {
+            let mut write_batch = write_batch.borrow_mut();
+            self.check_batch_mode(
+                write_batch.raw_mode,
+                write_batch.normalize_str_bytes_keys,
+                &write_batch.dumps,
+                "WriteBatch",
+            )?;
+            write_batch.consume()?
+        } else if let Ok(write_batch) = write_batch.downcast::<WriteBatchWithIndexPy>() {
+            if self.column_family.is_some() {
+                return Err(PyException::new_err(
+                    "WriteBatchWithIndex only supports the default column family",
+                ));
+            }
+            let write_batch = write_batch.borrow();
+            self.check_batch_mode(
+                write_batch.raw_mode,
+                write_batch.normalize_str_bytes_keys,
+                &write_batch.dumps,
+                "WriteBatchWithIndex",
+            )?;
+            write_batch.to_write_batch()
+        } else {
+            return Err(PyTypeError::new_err(
+                "write_batch must be a WriteBatch or a WriteBatchWithIndex",
+            ));
+        };
         let write_opt_option = write_opt.map(WriteOptions::from);
         let write_opt = match &write_opt_option {
             None => &self.write_opt,
             Some(opt) => opt,
         };
-        db.write_opt(write_batch.consume()?, write_opt)
-            .map_err(|e| PyException::new_err(e.to_string()))
+        db.write_opt(batch, write_opt).map_err(rocksdb_error)
     }
 
     /// Removes the database entries in the range `["from", "to")` of the current column family.
@@ -1134,9 +2774,12 @@ impl Rdict {
         end: &Bound<PyAny>,
         write_opt: Option<&WriteOptionsPy>,
     ) -> PyResult<()> {
+        self.check_writable()?;
         let db = self.get_db()?;
-        let from = encode_key(begin, self.opt_py.raw_mode)?;
-        let to = encode_key(end, self.opt_py.raw_mode)?;
+        self.check_key_type(begin)?;
+        self.check_key_type(end)?;
+        let from = self.encode_key(begin)?;
+        let to = self.encode_key(end)?;
         let cf = match &self.column_family {
             None => {
                 self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
@@ -1150,12 +2793,147 @@ impl Rdict {
             Some(opt) => opt,
         };
         db.delete_range_cf_opt(&cf, from, to, write_opt)
-            .map_err(|e| PyException::new_err(e.to_string()))
+            .map_err(rocksdb_error)
+    }
+
+    /// Drop the SST files whose keys are all in the range `[begin, end]`
+    /// of the current column family.
+    ///
+    /// Unlike `delete_range`, this writes no tombstones, and frees the
+    /// disk space immediately, so it is the fastest way to truncate large
+    /// ranges of old keys.
+    ///
+    /// Notes:
+    ///     Only whole files entirely contained in the range are removed
+    ///     (level 0 files are never removed): the keys of files overlapping
+    ///     the edges of the range, and of the memtables, remain. Call
+    ///     `delete_range` afterwards to remove them. Older versions of the
+    ///     deleted keys in other files may reappear, and snapshots taken
+    ///     before the call lose the deleted files.
+    ///
+    /// Args:
+    ///     begin: included
+    ///     end: included
+    pub fn delete_file_in_range(&self, begin: &Bound<PyAny>, end: &Bound<PyAny>) -> PyResult<()> {
+        self.check_writable()?;
+        let db = self.get_db()?;
+        self.check_key_type(begin)?;
+        self.check_key_type(end)?;
+        let from = self.encode_key(begin)?;
+        let to = self.encode_key(end)?;
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        db.delete_file_in_range_cf(&cf, from, to)
+            .map_err(rocksdb_error)
+    }
+
+    /// Estimate the sizes in bytes of key ranges of the current column family.
+    ///
+    /// This is cheap, and can be used to find where to split the
+    /// keyspace into shards of similar sizes.
+    ///
+    /// Notes:
+    ///     Only the data in SST files is counted, not the memtables
+    ///     (call `flush` first to include recent writes). The sizes are
+    ///     on-disk sizes, after compression.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict
+    ///
+    ///         db = Rdict("./test_dir")
+    ///         for i in range(1000):
+    ///             db[i] = i
+    ///         db.flush()
+    ///         first_half, second_half = db.get_approximate_sizes([(0, 500), (500, 1000)])
+    ///
+    /// Args:
+    ///     ranges: a list of `(start, end)` pairs, start included and end excluded.
+    ///
+    /// Returns:
+    ///     A list of sizes in bytes, aligned with `ranges`.
+    fn get_approximate_sizes(
+        &self,
+        ranges: Vec<(Bound<PyAny>, Bound<PyAny>)>,
+    ) -> PyResult<Vec<u64>> {
+        let db = self.get_db()?;
+        let keys = ranges
+            .iter()
+            .map(|(start, end)| Ok((self.encode_key(start)?, self.encode_key(end)?)))
+            .collect::<PyResult<Vec<_>>>()?;
+        let ranges = keys
+            .iter()
+            .map(|(start, end)| Range::new(start, end))
+            .collect::<Vec<_>>();
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        Ok(db.get_approximate_sizes_cf(&cf, &ranges))
+    }
+
+    /// Estimate the size in bytes of the memtable data in the key range
+    /// `[begin, end)` of the current column family.
+    ///
+    /// Together with `get_approximate_sizes`, this can be used to decide
+    /// when to flush.
+    ///
+    /// Notes:
+    ///     RocksDB's `GetApproximateMemTableStats`, which also estimates
+    ///     the number of entries, is not available in its C API. Use
+    ///     `property_int_value("rocksdb.num-entries-active-mem-table")`
+    ///     for the number of entries of the whole memtable.
+    ///
+    /// Args:
+    ///     begin: included
+    ///     end: excluded
+    fn get_approximate_memtable_size(
+        &self,
+        begin: &Bound<PyAny>,
+        end: &Bound<PyAny>,
+    ) -> PyResult<u64> {
+        let db = self.get_db()?;
+        let from = self.encode_key(begin)?;
+        let to = self.encode_key(end)?;
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        let mut size: u64 = 0;
+        unsafe {
+            ffi_try!(librocksdb_sys::rocksdb_approximate_sizes_cf_with_flags(
+                db.inner(),
+                cf.inner(),
+                1,
+                &(from.as_ptr() as *const c_char),
+                &from.len(),
+                &(to.as_ptr() as *const c_char),
+                &to.len(),
+                librocksdb_sys::rocksdb_size_approximation_flags_include_memtable as u8,
+                &mut size,
+            ));
+        }
+        Ok(size)
     }
 
     /// Flush memory to disk, and drop the current column family.
     ///
     /// Notes:
+    ///     Operations buffered by `enable_write_buffering` are written
+    ///     before closing. What else is flushed can be configured
+    ///     with `set_drop_behavior`.
     ///     Calling `db.close()` is nearly equivalent to first calling
     ///     `db.flush()` and then `del db`. However, `db.close()` does
     ///     not guarantee the underlying RocksDB to be actually closed.
@@ -1166,6 +2944,15 @@ impl Rdict {
     ///     above to actually shut down RocksDB.
     ///
     fn close(&mut self, py: Python) -> PyResult<()> {
+        // close anyway, and raise a pending comparator error afterwards
+        let comparator_error = self.take_comparator_error();
+        // write buffered operations first
+        self.flush_writes(py)?;
+        *self
+            .write_buffer
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner()) = None;
+
         // do not flush if readonly
         if let AccessTypeInner::ReadOnly { .. } | AccessTypeInner::Secondary { .. } =
             &self.access_type.0
@@ -1174,32 +2961,57 @@ impl Rdict {
                 drop(self.column_family.take());
                 self.db.close();
             });
-            return Ok(());
+            return comparator_error;
         }
 
         let (flush_wal_result, flush_result) = py.allow_threads(|| {
             let f_opt = &self.flush_opt;
             let db = self.get_db()?;
 
-            let flush_wal_result = db.flush_wal(true);
-            let flush_result = if let Some(cf) = &self.column_family {
-                db.flush_cf_opt(cf, &f_opt.into())
-            } else {
-                db.flush_opt(&f_opt.into())
+            let flush_wal_result = match self.drop_behavior {
+                DropBehavior::Flush | DropBehavior::FlushWal => db.flush_wal(true),
+                DropBehavior::Nothing => Ok(()),
+            };
+            let flush_result = match (self.drop_behavior, &self.column_family) {
+                (DropBehavior::Flush, Some(cf)) => db.flush_cf_opt(cf, &f_opt.into()),
+                (DropBehavior::Flush, None) => db.flush_opt(&f_opt.into()),
+                _ => Ok(()),
             };
             drop(self.column_family.take());
             self.db.close();
 
             Ok::<_, PyErr>((flush_wal_result, flush_result))
         })?;
+        comparator_error?;
         match (flush_result, flush_wal_result) {
             (Ok(_), Ok(_)) => Ok(()),
-            (Err(e), Ok(_)) => Err(PyException::new_err(e.to_string())),
-            (Ok(_), Err(e)) => Err(PyException::new_err(e.to_string())),
-            (Err(e), Err(wal_e)) => Err(PyException::new_err(format!("{e}; {wal_e}"))),
+            (Err(e), Ok(_)) => Err(rocksdb_error(e)),
+            (Ok(_), Err(e)) => Err(rocksdb_error(e)),
+            (Err(e), Err(wal_e)) => Err(RocksDBError::new_err(format!("{e}; {wal_e}"))),
         }
     }
 
+    /// Close the database, even if other references to it are still alive.
+    ///
+    /// Notes:
+    ///     Unlike `close`, which only drops the reference held by this
+    ///     instance, `force_close` flushes the current column family,
+    ///     cancels all background work, and invalidates every `Rdict`
+    ///     instance of this database (including other column families) as
+    ///     well as snapshots taken from it: using them afterwards raises
+    ///     `DbClosedError` instead of keeping the database usable.
+    ///     Iterators, snapshots and `ColumnFamily` handles still hold
+    ///     native resources that must not outlive RocksDB, so the
+    ///     database files are only released (and can be reopened) after
+    ///     those objects are garbage collected.
+    ///
+    fn force_close(&mut self, py: Python) -> PyResult<()> {
+        let mut db = self.db.clone();
+        let result = self.close(py);
+        py.allow_threads(|| db.force_close());
+        result
+    }
+
     /// Return current database path.
     fn path(&self) -> PyResult<String> {
         Ok(self
@@ -1225,59 +3037,567 @@ impl Rdict {
         let from = if begin.is_none() {
             None
         } else {
-            Some(encode_key(begin, self.opt_py.raw_mode)?)
+            Some(self.encode_key(begin)?)
         };
         let to = if end.is_none() {
             None
         } else {
-            Some(encode_key(end, self.opt_py.raw_mode)?)
+            Some(self.encode_key(end)?)
         };
         py.allow_threads(|| {
             if let Some(cf) = &self.column_family {
-                db.compact_range_cf_opt(cf, from, to, &opt_ref.0)
+                db.compact_range_cf_opt(cf, from, to, &opt_ref.inner)
             } else {
-                db.compact_range_opt(from, to, &opt_ref.0)
+                db.compact_range_opt(from, to, &opt_ref.inner)
             };
         });
         Ok(())
     }
 
-    /// Set options for the current column family.
-    fn set_options(&self, options: HashMap<String, String>) -> PyResult<()> {
-        let db = self.get_db()?;
-        let options: Vec<(&str, &str)> = options
-            .iter()
-            .map(|(opt, v)| (opt.as_str(), v.as_str()))
-            .collect();
-        match &self.column_family {
-            None => db.set_options(&options),
-            Some(cf) => db.set_options_cf(cf, &options),
+    /// Compact all the data of the current column family.
+    ///
+    /// This is a shortcut for `compact_range(None, None)`, which blocks
+    /// until the compaction finishes. Use it to merge the many small SST
+    /// files left by bulk loads with auto compactions disabled, or to
+    /// drop deleted and overwritten data right away.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, CompactOptions, BottommostLevelCompaction
+    ///
+    ///         db = Rdict("./test_dir")
+    ///         db.compact()
+    ///
+    ///         # move all the data to level 1
+    ///         db.compact(target_level=1)
+    ///
+    ///         # also rewrite the files of the bottommost level
+    ///         opt = CompactOptions()
+    ///         opt.set_bottommost_level_compaction(BottommostLevelCompaction.force())
+    ///         db.compact(compact_opt=opt)
+    ///
+    /// Args:
+    ///     target_level: if set, move the compacted files to this level
+    ///         (this sets `change_level` and `target_level` of `compact_opt`).
+    ///     compact_opt: the compaction options.
+    #[pyo3(signature = (target_level = None, compact_opt = None))]
+    fn compact(
+        &self,
+        target_level: Option<i32>,
+        compact_opt: Option<Py<CompactOptionsPy>>,
+        py: Python,
+    ) -> PyResult<()> {
+        // work on a copy, so that the caller's options are left unchanged
+        let mut opt = match &compact_opt {
+            Some(opt) => opt.borrow(py).copy(),
+            None => CompactOptionsPy::default(),
+        };
+        if let Some(target_level) = target_level {
+            opt.set_change_level(true);
+            opt.set_target_level(target_level);
+        }
+        let none = py.None().into_bound(py);
+        self.compact_range(&none, &none, Py::new(py, opt)?, py)
+    }
+
+    /// Wait until background flushes and compactions are finished.
+    ///
+    /// Use it after bulk loads or `compact`, before measuring the
+    /// number of SST files or copying the database files.
+    ///
+    /// Args:
+    ///     flush: flush all column families before waiting.
+    ///     timeout: maximum time to wait in seconds, no limit if None.
+    ///         `TimeoutError` is raised when it is exceeded.
+    ///     abort_on_pause: stop waiting if background work is paused,
+    ///         instead of waiting for it to be resumed.
+    #[pyo3(signature = (flush = true, timeout = None, abort_on_pause = false))]
+    fn wait_for_compact(
+        &self,
+        flush: bool,
+        timeout: Option<f64>,
+        abort_on_pause: bool,
+        py: Python,
+    ) -> PyResult<()> {
+        let db = self.get_db()?;
+        let mut opts = WaitForCompactOptions::default();
+        opts.set_flush(flush);
+        opts.set_abort_on_pause(abort_on_pause);
+        if let Some(timeout) = timeout {
+            if timeout <= 0.0 {
+                return Err(PyValueError::new_err("timeout must be positive"));
+            }
+            // 0 means no timeout
+            opts.set_timeout(((timeout * 1e6) as u64).max(1));
+        }
+        py.allow_threads(|| db.wait_for_compact(&opts))
+            .map_err(|e| match e.kind() {
+                ErrorKind::TimedOut => PyTimeoutError::new_err(e.to_string()),
+                _ => rocksdb_error(e),
+            })
+    }
+
+    /// Compact the current column family into a single SST file.
+    ///
+    /// Runs a full manual compaction with `target_file_size_base` and
+    /// `max_compaction_bytes` temporarily raised so that the output is not
+    /// split into multiple files. The original values are restored afterwards.
+    ///
+    /// Notes:
+    ///     This rewrites all the data of the column family and blocks until
+    ///     the compaction finishes. Writes that happen concurrently may
+    ///     end up in additional files.
+    fn collapse_to_single_file(&self, py: Python) -> PyResult<()> {
+        let db = self.get_db()?;
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        let (target_file_size_base, max_compaction_bytes) =
+            read_latest_cf_options(db, &cf, |_, opt| unsafe {
+                (
+                    librocksdb_sys::rocksdb_options_get_target_file_size_base(opt),
+                    librocksdb_sys::rocksdb_options_get_max_compaction_bytes(opt),
+                )
+            })?;
+        let unlimited = u64::MAX.to_string();
+        db.set_options_cf(
+            &cf,
+            &[
+                ("target_file_size_base", unlimited.as_str()),
+                ("max_compaction_bytes", unlimited.as_str()),
+            ],
+        )
+        .map_err(rocksdb_error)?;
+        let mut compact_opt = CompactOptionsPy::default();
+        compact_opt.set_exclusive_manual_compaction(true);
+        compact_opt.set_change_level(true);
+        compact_opt.set_bottommost_level_compaction(BottommostLevelCompactionPy::force());
+        py.allow_threads(|| {
+            db.compact_range_cf_opt(&cf, None::<&[u8]>, None::<&[u8]>, &compact_opt.inner)
+        });
+        db.set_options_cf(
+            &cf,
+            &[
+                (
+                    "target_file_size_base",
+                    target_file_size_base.to_string().as_str(),
+                ),
+                (
+                    "max_compaction_bytes",
+                    max_compaction_bytes.to_string().as_str(),
+                ),
+            ],
+        )
+        .map_err(rocksdb_error)
+    }
+
+    /// Garbage collect all the blob files of the current column family.
+    ///
+    /// Runs a full manual compaction with blob garbage collection
+    /// temporarily enabled for all blob files (`blob_gc_age_cutoff` of 1.0),
+    /// so that the live values are moved to new blob files and the old
+    /// ones, with their garbage, are deleted. The original values are
+    /// restored afterwards.
+    ///
+    /// Notes:
+    ///     RocksDB has no API to only collect blob garbage, so this
+    ///     rewrites all the data of the column family and blocks until
+    ///     the compaction finishes. For continuous reclamation, use
+    ///     `Options.set_enable_blob_gc`, `Options.set_blob_gc_age_cutoff`
+    ///     and `Options.set_blob_gc_force_threshold` instead.
+    fn force_blob_gc(&self, py: Python) -> PyResult<()> {
+        self.check_writable()?;
+        let db = self.get_db()?;
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        let (enable_blob_gc, blob_gc_age_cutoff) =
+            read_latest_cf_options(db, &cf, |_, opt| unsafe {
+                (
+                    librocksdb_sys::rocksdb_options_get_enable_blob_gc(opt) != 0,
+                    librocksdb_sys::rocksdb_options_get_blob_gc_age_cutoff(opt),
+                )
+            })?;
+        db.set_options_cf(
+            &cf,
+            &[
+                ("enable_blob_garbage_collection", "true"),
+                ("blob_garbage_collection_age_cutoff", "1.0"),
+            ],
+        )
+        .map_err(rocksdb_error)?;
+        let mut compact_opt = CompactOptionsPy::default();
+        compact_opt.set_exclusive_manual_compaction(true);
+        compact_opt.set_bottommost_level_compaction(BottommostLevelCompactionPy::force());
+        py.allow_threads(|| {
+            db.compact_range_cf_opt(&cf, None::<&[u8]>, None::<&[u8]>, &compact_opt.inner)
+        });
+        db.set_options_cf(
+            &cf,
+            &[
+                (
+                    "enable_blob_garbage_collection",
+                    enable_blob_gc.to_string().as_str(),
+                ),
+                (
+                    "blob_garbage_collection_age_cutoff",
+                    blob_gc_age_cutoff.to_string().as_str(),
+                ),
+            ],
+        )
+        .map_err(rocksdb_error)
+    }
+
+    /// Check whether a value would be stored in a blob file.
+    ///
+    /// Returns True if blob files are enabled for the current column family
+    /// and the encoded value is at least `min_blob_size` bytes.
+    /// This reflects the options currently in effect, including changes
+    /// made by `set_options`.
+    fn value_goes_to_blob(&self, value: &Bound<PyAny>) -> PyResult<bool> {
+        let db = self.get_db()?;
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        let value = encode_value(value, &self.dumps, self.opt_py.raw_mode)?;
+        let (enable_blob_files, min_blob_size) =
+            read_latest_cf_options(db, &cf, |_, opt| unsafe {
+                (
+                    librocksdb_sys::rocksdb_options_get_enable_blob_files(opt) != 0,
+                    librocksdb_sys::rocksdb_options_get_min_blob_size(opt),
+                )
+            })?;
+        Ok(enable_blob_files && value.len() as u64 >= min_blob_size)
+    }
+
+    /// Read the options in effect for the current column family.
+    ///
+    /// The values are read from the latest OPTIONS file of the database,
+    /// so they include the options loaded from an OPTIONS file, the
+    /// sanitization done by RocksDB and the changes made by `set_options`.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict, Options
+    ///
+    ///         opt = Options()
+    ///         opt.set_write_buffer_size(128 * 1024 * 1024)
+    ///         db = Rdict("./db", opt)
+    ///         assert db.get_effective_options()["write_buffer_size"] == 128 * 1024 * 1024
+    ///
+    /// Returns:
+    ///     A dict with the keys `num_levels`, `write_buffer_size`,
+    ///     `max_write_buffer_number`, `min_write_buffer_number_to_merge`,
+    ///     `target_file_size_base`, `target_file_size_multiplier`,
+    ///     `max_bytes_for_level_base`, `max_bytes_for_level_multiplier`,
+    ///     `level0_file_num_compaction_trigger`, `level0_slowdown_writes_trigger`,
+    ///     `level0_stop_writes_trigger`, `max_compaction_bytes`,
+    ///     `disable_auto_compactions`, `max_background_jobs`,
+    ///     `max_subcompactions`, `max_open_files` and `use_fsync`.
+    fn get_effective_options<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let db = self.get_db()?;
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        read_latest_cf_options(db, &cf, |db_opt, opt| -> PyResult<Bound<'py, PyDict>> {
+            let result = PyDict::new(py);
+            unsafe {
+                result.set_item(
+                    "num_levels",
+                    librocksdb_sys::rocksdb_options_get_num_levels(opt),
+                )?;
+                result.set_item(
+                    "write_buffer_size",
+                    librocksdb_sys::rocksdb_options_get_write_buffer_size(opt),
+                )?;
+                result.set_item(
+                    "max_write_buffer_number",
+                    librocksdb_sys::rocksdb_options_get_max_write_buffer_number(opt),
+                )?;
+                result.set_item(
+                    "min_write_buffer_number_to_merge",
+                    librocksdb_sys::rocksdb_options_get_min_write_buffer_number_to_merge(opt),
+                )?;
+                result.set_item(
+                    "target_file_size_base",
+                    librocksdb_sys::rocksdb_options_get_target_file_size_base(opt),
+                )?;
+                result.set_item(
+                    "target_file_size_multiplier",
+                    librocksdb_sys::rocksdb_options_get_target_file_size_multiplier(opt),
+                )?;
+                result.set_item(
+                    "max_bytes_for_level_base",
+                    librocksdb_sys::rocksdb_options_get_max_bytes_for_level_base(opt),
+                )?;
+                result.set_item(
+                    "max_bytes_for_level_multiplier",
+                    librocksdb_sys::rocksdb_options_get_max_bytes_for_level_multiplier(opt),
+                )?;
+                result.set_item(
+                    "level0_file_num_compaction_trigger",
+                    librocksdb_sys::rocksdb_options_get_level0_file_num_compaction_trigger(opt),
+                )?;
+                result.set_item(
+                    "level0_slowdown_writes_trigger",
+                    librocksdb_sys::rocksdb_options_get_level0_slowdown_writes_trigger(opt),
+                )?;
+                result.set_item(
+                    "level0_stop_writes_trigger",
+                    librocksdb_sys::rocksdb_options_get_level0_stop_writes_trigger(opt),
+                )?;
+                result.set_item(
+                    "max_compaction_bytes",
+                    librocksdb_sys::rocksdb_options_get_max_compaction_bytes(opt),
+                )?;
+                result.set_item(
+                    "disable_auto_compactions",
+                    librocksdb_sys::rocksdb_options_get_disable_auto_compactions(opt) != 0,
+                )?;
+                result.set_item(
+                    "max_background_jobs",
+                    librocksdb_sys::rocksdb_options_get_max_background_jobs(db_opt),
+                )?;
+                result.set_item(
+                    "max_subcompactions",
+                    librocksdb_sys::rocksdb_options_get_max_subcompactions(db_opt),
+                )?;
+                result.set_item(
+                    "max_open_files",
+                    librocksdb_sys::rocksdb_options_get_max_open_files(db_opt),
+                )?;
+                result.set_item(
+                    "use_fsync",
+                    librocksdb_sys::rocksdb_options_get_use_fsync(db_opt) != 0,
+                )?;
+            }
+            Ok(result)
+        })?
+    }
+
+    /// Set options for the current column family.
+    fn set_options(&self, options: HashMap<String, String>) -> PyResult<()> {
+        let db = self.get_db()?;
+        let options: Vec<(&str, &str)> = options
+            .iter()
+            .map(|(opt, v)| (opt.as_str(), v.as_str()))
+            .collect();
+        match &self.column_family {
+            None => db.set_options(&options),
+            Some(cf) => db.set_options_cf(cf, &options),
+        }
+        .map_err(rocksdb_error)
+    }
+
+    /// Retrieves a RocksDB property by name, for the current column family.
+    fn property_value(&self, name: &str) -> PyResult<Option<String>> {
+        let db = self.get_db()?;
+        match &self.column_family {
+            None => db.property_value(name),
+            Some(cf) => db.property_value_cf(cf, name),
+        }
+        .map_err(rocksdb_error)
+    }
+
+    /// Retrieves a RocksDB property and casts it to an integer
+    /// (for the current column family).
+    ///
+    /// Full list of properties that return int values could be find
+    /// [here](https://github.com/facebook/rocksdb/blob/08809f5e6cd9cc4bc3958dd4d59457ae78c76660/include/rocksdb/db.h#L654-L689).
+    fn property_int_value(&self, name: &str) -> PyResult<Option<u64>> {
+        let db = self.get_db()?;
+        match &self.column_family {
+            None => db.property_int_value(name),
+            Some(cf) => db.property_int_value_cf(cf, name),
+        }
+        .map_err(rocksdb_error)
+    }
+
+    /// Retrieves a RocksDB property and casts it to an integer,
+    /// for every column family of the database.
+    ///
+    /// Args:
+    ///     name: name of the property, e.g. `"rocksdb.estimate-num-keys"`.
+    ///
+    /// Returns:
+    ///     A dict mapping each column family name to its value
+    ///     (None if not available).
+    fn property_int_value_all_cf(&self, name: &str) -> PyResult<HashMap<String, Option<u64>>> {
+        let db = self.get_db()?;
+        self.live_cf_names()?
+            .into_iter()
+            .filter_map(|cf_name| {
+                let cf = unsafe { db.cf_handle_unbounded(&cf_name) }?;
+                let value = db.property_int_value_cf(&cf, name).map_err(rocksdb_error);
+                Some(value.map(|value| (cf_name, value)))
+            })
+            .collect()
+    }
+
+    /// Retrieves several RocksDB properties at once, for the current column family.
+    ///
+    /// The properties are read in a single call without holding the GIL,
+    /// which is cheaper than calling `property_value` repeatedly.
+    ///
+    /// Args:
+    ///     names: names of the properties.
+    ///
+    /// Returns:
+    ///     A dict mapping each name to its value (None if not available).
+    fn properties(
+        &self,
+        names: Vec<String>,
+        py: Python,
+    ) -> PyResult<HashMap<String, Option<String>>> {
+        let db = self.get_db()?;
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        py.allow_threads(|| {
+            names
+                .into_iter()
+                .map(|name| {
+                    let value = db.property_value_cf(&cf, name.as_str())?;
+                    Ok((name, value))
+                })
+                .collect::<Result<HashMap<_, _>, rocksdb::Error>>()
+        })
+        .map_err(rocksdb_error)
+    }
+
+    /// Approximate compression ratio of each level of the current column family.
+    ///
+    /// Read from the `rocksdb.compression-ratio-at-level<N>` properties,
+    /// as the uncompressed size of the data divided by its size on disk.
+    /// Levels without any file are omitted.
+    ///
+    /// Returns:
+    ///     A dict mapping levels to compression ratios.
+    fn compression_stats(&self) -> PyResult<HashMap<usize, f64>> {
+        let mut stats = HashMap::new();
+        for level in 0.. {
+            let name = format!("rocksdb.compression-ratio-at-level{level}");
+            // the property does not exist beyond the last level
+            let Some(ratio) = self.property_value(&name)? else {
+                break;
+            };
+            let ratio: f64 = ratio
+                .parse()
+                .map_err(|e: std::num::ParseFloatError| PyException::new_err(e.to_string()))?;
+            // negative for empty levels
+            if ratio >= 0.0 {
+                stats.insert(level, ratio);
+            }
         }
-        .map_err(|e| PyException::new_err(e.to_string()))
+        Ok(stats)
     }
 
-    /// Retrieves a RocksDB property by name, for the current column family.
-    fn property_value(&self, name: &str) -> PyResult<Option<String>> {
+    /// Estimated number of keys in the current column family.
+    ///
+    /// Notes:
+    ///     This is backed by the `rocksdb.estimate-num-keys` property,
+    ///     so it is fast but only an estimate: overwritten and deleted
+    ///     keys that have not been compacted yet may be counted.
+    ///     Use `count_exact` when precision matters.
+    fn __len__(&self) -> PyResult<usize> {
+        Ok(self
+            .property_int_value("rocksdb.estimate-num-keys")?
+            .unwrap_or(0) as usize)
+    }
+
+    /// Whether the current column family has any key.
+    ///
+    /// Notes:
+    ///     Unlike `len(db)`, which is an estimate, this seeks to the first
+    ///     key, so that `if db:` stays correct after deleting all the keys.
+    fn __bool__(&self, py: Python) -> PyResult<bool> {
         let db = self.get_db()?;
-        match &self.column_family {
-            None => db.property_value(name),
-            Some(cf) => db.property_value_cf(cf, name),
-        }
-        .map_err(|e| PyException::new_err(e.to_string()))
+        let read_opt = self.to_read_options(&self.read_opt_py, py)?;
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        py.allow_threads(|| {
+            let mut iter = db.raw_iterator_cf_opt(&cf, read_opt);
+            iter.seek_to_first();
+            iter.status().map(|_| iter.valid())
+        })
+        .map_err(rocksdb_error)
     }
 
-    /// Retrieves a RocksDB property and casts it to an integer
-    /// (for the current column family).
+    /// Count the keys in the current column family by iterating over them.
     ///
-    /// Full list of properties that return int values could be find
-    /// [here](https://github.com/facebook/rocksdb/blob/08809f5e6cd9cc4bc3958dd4d59457ae78c76660/include/rocksdb/db.h#L654-L689).
-    fn property_int_value(&self, name: &str) -> PyResult<Option<u64>> {
+    /// This is precise, but takes time proportional to the size of
+    /// the column family. See `len(db)` for a fast estimate.
+    ///
+    /// Args:
+    ///     read_opt: override preset read options
+    ///         (or use Rdict.set_read_options to preset a read options used by default).
+    #[pyo3(signature = (read_opt = None))]
+    fn count_exact(&self, read_opt: Option<&ReadOptionsPy>, py: Python) -> PyResult<usize> {
         let db = self.get_db()?;
-        match &self.column_family {
-            None => db.property_int_value(name),
-            Some(cf) => db.property_int_value_cf(cf, name),
-        }
-        .map_err(|e| PyException::new_err(e.to_string()))
+        let read_opt = match read_opt {
+            None => self.to_read_options(&self.read_opt_py, py)?,
+            Some(opt) => self.to_read_options(opt, py)?,
+        };
+        let cf = match &self.column_family {
+            None => {
+                self.get_column_family_handle(DEFAULT_COLUMN_FAMILY_NAME)?
+                    .cf
+            }
+            Some(cf) => cf.clone(),
+        };
+        py.allow_threads(|| {
+            let mut iter = db.raw_iterator_cf_opt(&cf, read_opt);
+            let mut count = 0;
+            iter.seek_to_first();
+            while iter.valid() {
+                count += 1;
+                iter.next();
+            }
+            iter.status().map(|_| count)
+        })
+        .map_err(rocksdb_error)
+    }
+
+    /// Creates a checkpoint of the database in a new directory.
+    ///
+    /// This is a shortcut of `Checkpoint(db).create_checkpoint(path)`.
+    /// The checkpoint can be opened as a database on its own.
+    ///
+    /// Args:
+    ///     path: directory of the checkpoint, which must not exist.
+    ///     log_size_for_flush: the memtables are flushed first if the total
+    ///         WAL size is at least this many bytes (always with the default 0),
+    ///         otherwise WAL files are copied into the checkpoint.
+    #[pyo3(signature = (path, log_size_for_flush = 0))]
+    fn create_checkpoint(&self, path: &str, log_size_for_flush: u64) -> PyResult<()> {
+        CheckpointPy::new(self)?.create_checkpoint(path, log_size_for_flush)
     }
 
     /// The sequence number of the most recent transaction.
@@ -1285,7 +3605,51 @@ impl Rdict {
         Ok(self.get_db()?.latest_sequence_number())
     }
 
-    /// Returns a list of all table files with their level, start key and end key
+    /// Iterate over the write batches written after a sequence number,
+    /// read from the WAL, e.g. to replicate the database.
+    ///
+    /// Notes:
+    ///     WAL files are deleted once their writes are flushed, so only
+    ///     recent updates can be read, unless the WAL files are kept with
+    ///     `Options.set_wal_ttl_seconds` or `Options.set_wal_size_limit_mb`.
+    ///     An exception is raised if the WAL files holding the requested
+    ///     updates are gone. Batches are yielded whole: a batch is skipped
+    ///     if its first operation is not after `sequence_number`.
+    ///
+    /// Args:
+    ///     sequence_number: typically the last sequence number seen,
+    ///         e.g. from `latest_sequence_number()`.
+    ///
+    /// Returns:
+    ///     an iterator of `(sequence_number, WriteBatch)` pairs.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         seq = db.latest_sequence_number()
+    ///         db["a"] = 1
+    ///         del db["b"]
+    ///         for seq, batch in db.get_updates_since(seq):
+    ///             for op, cf_id, key, value in batch.operations():
+    ///                 print(seq, op, key, value)
+    fn get_updates_since(&self, sequence_number: u64) -> PyResult<WalIteratorPy> {
+        let db = self.get_db()?;
+        let inner = db
+            .get_updates_since(sequence_number)
+            .map_err(|e| wal_error(e, sequence_number))?;
+        Ok(WalIteratorPy::new(
+            inner,
+            self.db.clone(),
+            sequence_number,
+            self.dumps.clone(),
+            self.loads.clone(),
+            self.opt_py.raw_mode,
+            self.opt_py.normalize_str_bytes_keys,
+        ))
+    }
+
+    /// Returns a list of all table files with their column family name, level,
+    /// start key and end key
     fn live_files<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
         let db = self.get_db()?;
         match db.live_files() {
@@ -1301,7 +3665,7 @@ impl Rdict {
                 }
                 Ok(result)
             }
-            Err(e) => Err(PyException::new_err(e.to_string())),
+            Err(e) => Err(rocksdb_error(e)),
         }
     }
 
@@ -1319,27 +3683,217 @@ impl Rdict {
             fs::remove_file(config_file(path)).ok();
             DB::destroy(&inner_opt, path)
         })
-        .map_err(|e| PyException::new_err(e.to_string()))
+        .map_err(rocksdb_error)
     }
 
     /// Repair the database.
     ///
+    /// Notes:
+    ///     RocksDB only reports the outcome of a repair in its info log
+    ///     (the `LOG` file of the database), so the returned summary is
+    ///     parsed from the lines written there by the repairer.
+    ///     A successful repair always rebuilds the MANIFEST: the old
+    ///     MANIFEST files, the converted WAL files and the tables that
+    ///     could not be read are moved to the `lost` sub-directory and
+    ///     listed in `archived_files`.
+    ///     If `Options.set_db_log_dir` is used, the info log is not in the
+    ///     database directory and `log_path`, `recovered_files` and
+    ///     `recovered_bytes` are `None`.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict
+    ///
+    ///         summary = Rdict.repair("./db")
+    ///         print(summary["recovered_files"], summary["warnings"])
+    ///
     /// Args:
     ///     path (str): path to this database
     ///     options (rocksdict.Options): Rocksdb options object
+    ///
+    /// Returns:
+    ///     A dict with the keys `column_families` (the column families
+    ///     after the repair), `log_path`, `recovered_files`, `recovered_bytes`,
+    ///     `archived_files` and `warnings` (the warnings and errors
+    ///     logged by the repairer, such as dropped WAL records).
     #[staticmethod]
     #[pyo3(signature = (path, options = OptionsPy::new(false)))]
-    fn repair(path: &str, options: OptionsPy, py: Python) -> PyResult<()> {
+    fn repair<'py>(
+        path: &str,
+        options: OptionsPy,
+        py: Python<'py>,
+    ) -> PyResult<Bound<'py, PyDict>> {
         let inner_opt = options.inner_opt;
 
         py.allow_threads(|| DB::repair(&inner_opt, path))
-            .map_err(|e| PyException::new_err(e.to_string()))
+            .map_err(rocksdb_error)?;
+        let column_families = DB::list_cf(&inner_opt, path).map_err(rocksdb_error)?;
+        // the repairer starts a new info log, so it only holds the repair
+        let log_path = Path::new(path).join("LOG");
+        let summary = fs::read_to_string(&log_path)
+            .map(|log| RepairSummary::parse(&log))
+            .unwrap_or_default();
+        // a successful repair always logs how many files were recovered
+        let log_path = summary.recovered_files.map(|_| log_path);
+
+        let result = PyDict::new(py);
+        result.set_item("column_families", column_families)?;
+        result.set_item("log_path", log_path)?;
+        result.set_item("recovered_files", summary.recovered_files)?;
+        result.set_item("recovered_bytes", summary.recovered_bytes)?;
+        result.set_item("archived_files", summary.archived_files)?;
+        result.set_item("warnings", summary.warnings)?;
+        Ok(result)
+    }
+
+    /// Open the database with all of its column families.
+    ///
+    /// Column families are discovered with `list_cf`, and opened with
+    /// their options from the latest OPTIONS file (including the prefix
+    /// extractors saved by rocksdict), or with `options` for those that
+    /// are missing from the OPTIONS file.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         from rocksdict import Rdict
+    ///
+    ///         dbs = Rdict.open_all_cfs("./test_dir")
+    ///         db = dbs["default"]
+    ///         for name, cf in dbs.items():
+    ///             print(name, list(cf.keys()))
+    ///
+    /// Args:
+    ///     path (str): path to the database
+    ///     options (Options): Options of the default column family,
+    ///         loaded from the path if not provided.
+    ///     access_type (AccessType): there are four access types:
+    ///         ReadWrite, ReadOnly, WithTTL, and Secondary, use
+    ///         AccessType class to create.
+    ///
+    /// Returns:
+    ///     A dict from column family name to `Rdict`, including `"default"`.
+    #[staticmethod]
+    #[pyo3(signature = (path, options = None, access_type = AccessType::read_write()))]
+    fn open_all_cfs(
+        path: &str,
+        options: Option<OptionsPy>,
+        access_type: AccessType,
+        py: Python,
+    ) -> PyResult<HashMap<String, Rdict>> {
+        let (options_loaded, mut column_families) = match OptionsPy::load_latest_inner(
+            path,
+            EnvPy::default()?,
+            false,
+            CachePy::new_lru_cache(DEFAULT_LRU_CACHE_SIZE),
+        ) {
+            Ok((opt, cols)) => (Some(opt), cols),
+            Err(_) => (None, HashMap::new()),
+        };
+        let fallback = options
+            .clone()
+            .or(options_loaded)
+            .unwrap_or_else(|| OptionsPy::new(false));
+        // a database that does not exist yet only has the default column family
+        let names = DB::list_cf(&fallback.inner_opt, path)
+            .unwrap_or_else(|_| vec![DEFAULT_COLUMN_FAMILY_NAME.to_string()]);
+        for name in names {
+            column_families
+                .entry(name)
+                .or_insert_with(|| fallback.clone());
+        }
+        let names = column_families.keys().cloned().collect::<Vec<_>>();
+        let db = Rdict::new(path, options, Some(column_families), access_type, py)?;
+        let mut result = HashMap::with_capacity(names.len());
+        for name in names {
+            if name != DEFAULT_COLUMN_FAMILY_NAME {
+                let cf = db.get_column_family(&name, py)?;
+                result.insert(name, cf);
+            }
+        }
+        result.insert(DEFAULT_COLUMN_FAMILY_NAME.to_string(), db);
+        Ok(result)
     }
 
     #[staticmethod]
     #[pyo3(signature = (path, options = OptionsPy::new(false)))]
     fn list_cf(path: &str, options: OptionsPy) -> PyResult<Vec<String>> {
-        DB::list_cf(&options.inner_opt, path).map_err(|e| PyException::new_err(e.to_string()))
+        DB::list_cf(&options.inner_opt, path).map_err(rocksdb_error)
+    }
+}
+
+/// The smallest key greater than all the keys starting with `prefix`
+/// in bytewise order, or `None` if there is none (`prefix` is all 0xff).
+fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let end = prefix.iter().rposition(|b| *b != 0xff)?;
+    let mut successor = prefix[..=end].to_vec();
+    successor[end] += 1;
+    Some(successor)
+}
+
+/// Name of a column family, as bytes.
+fn column_family_name(cf: &Arc<UnboundColumnFamily>) -> Vec<u8> {
+    unsafe {
+        let mut name_len: usize = 0;
+        let name_ptr =
+            librocksdb_sys::rocksdb_column_family_handle_get_name(cf.inner(), &mut name_len);
+        let cf_name = slice::from_raw_parts(name_ptr as *const u8, name_len).to_vec();
+        librocksdb_sys::rocksdb_free(name_ptr as *mut c_void);
+        cf_name
+    }
+}
+
+/// Read options of a column family from the latest OPTIONS file of the database.
+///
+/// `read` receives the DB-wide options and the column family options: the
+/// latter only hold the column family options, with the default DB options.
+fn read_latest_cf_options<T>(
+    db: &DB,
+    cf: &Arc<UnboundColumnFamily>,
+    read: impl Fn(*mut librocksdb_sys::rocksdb_options_t, *mut librocksdb_sys::rocksdb_options_t) -> T,
+) -> PyResult<T> {
+    let path = to_cpath(db.path())?;
+    let cf_name = column_family_name(cf);
+    unsafe {
+        let env = librocksdb_sys::rocksdb_create_default_env();
+        let cache = librocksdb_sys::rocksdb_cache_create_lru(0);
+        let mut db_options: *mut librocksdb_sys::rocksdb_options_t = ptr::null_mut();
+        let mut num_column_families: usize = 0;
+        let mut column_family_names: *mut *mut c_char = ptr::null_mut();
+        let mut column_family_options: *mut *mut librocksdb_sys::rocksdb_options_t =
+            ptr::null_mut();
+        let mut err: *mut c_char = ptr::null_mut();
+        librocksdb_sys::rocksdb_load_latest_options(
+            path.as_ptr(),
+            env,
+            false,
+            cache,
+            &mut db_options,
+            &mut num_column_families,
+            &mut column_family_names,
+            &mut column_family_options,
+            &mut err,
+        );
+        librocksdb_sys::rocksdb_cache_destroy(cache);
+        librocksdb_sys::rocksdb_env_destroy(env);
+        if !err.is_null() {
+            return Err(RocksDBError::new_err(error_message(err)));
+        }
+
+        let mut result = None;
+        for i in 0..num_column_families {
+            if CStr::from_ptr(*column_family_names.add(i)).to_bytes() == cf_name.as_slice() {
+                result = Some(read(db_options, *column_family_options.add(i)));
+            }
+        }
+        librocksdb_sys::rocksdb_load_latest_options_destroy(
+            db_options,
+            column_family_names,
+            column_family_options,
+            num_column_families,
+        );
+        result.ok_or_else(|| PyException::new_err("column family not found in OPTIONS file"))
     }
 }
 
@@ -1358,6 +3912,7 @@ fn display_live_file_dict<'py>(
         None => py.None().bind(py).to_owned(),
         Some(k) => decode_value(py, &k, pickle_loads, raw_mode)?,
     };
+    result.set_item("column_family_name", lf.column_family_name)?;
     result.set_item("name", lf.name)?;
     result.set_item("size", lf.size)?;
     result.set_item("level", lf.level)?;
@@ -1368,41 +3923,293 @@ fn display_live_file_dict<'py>(
     Ok(result.into_any())
 }
 
+/// What the repairer wrote to the info log, used by `Rdict.repair`.
+#[derive(Default)]
+struct RepairSummary {
+    recovered_files: Option<u64>,
+    recovered_bytes: Option<u64>,
+    archived_files: Vec<String>,
+    warnings: Vec<String>,
+}
+
+impl RepairSummary {
+    fn parse(log: &str) -> Self {
+        let mut summary = Self::default();
+        for line in log.lines() {
+            // lines look like `<time> <thread> [WARN] [db/repair.cc:238] <message>`
+            let Some(pos) = line.find("[db/repair.cc:") else {
+                continue;
+            };
+            let Some(end) = line[pos..].find("] ") else {
+                continue;
+            };
+            let message = line[pos + end + 2..].trim_end();
+            if let Some(idx) = message.rfind("; recovered ") {
+                // `**** Repaired rocksdb <path>; recovered <n> files; <n> bytes. ...`
+                let mut numbers = message[idx..]
+                    .split(|c: char| !c.is_ascii_digit())
+                    .filter_map(|s| s.parse::<u64>().ok());
+                summary.recovered_files = numbers.next();
+                summary.recovered_bytes = numbers.next();
+            } else if let Some(archived) = message.strip_prefix("Archiving ") {
+                // `Archiving <file>: <status>`
+                if let Some((file, _)) = archived.rsplit_once(": ") {
+                    summary.archived_files.push(file.to_string());
+                }
+            } else if line[..pos].contains("[WARN]") || line[..pos].contains("[ERROR]") {
+                summary.warnings.push(message.to_string());
+            }
+        }
+        summary
+    }
+}
+
 impl Rdict {
+    /// An iterator stopping at `to_key`, in the direction of the iteration.
+    fn bounded_iter(
+        &self,
+        backwards: bool,
+        to_key: Option<Bound<PyAny>>,
+        read_opt: Option<&ReadOptionsPy>,
+        py: Python,
+    ) -> PyResult<RdictIter> {
+        let Some(to_key) = to_key else {
+            return self.iter(read_opt, py);
+        };
+        let mut read_opt = match read_opt {
+            None => ReadOptionsPy::default(py)?,
+            Some(opt) => opt.clone(),
+        };
+        if backwards {
+            read_opt.set_iterate_lower_bound(to_key)?;
+        } else {
+            read_opt.set_iterate_upper_bound(to_key)?;
+        }
+        read_opt.set_auto_total_order_seek(true);
+        self.iter(Some(&read_opt), py)
+    }
+
+    /// Names of the column families listed in the MANIFEST that have an open handle.
+    fn live_cf_names(&self) -> PyResult<Vec<String>> {
+        let db = self.get_db()?;
+        let names = DB::list_cf(&self.opt_py.inner_opt, db.path()).map_err(rocksdb_error)?;
+        Ok(names
+            .into_iter()
+            .filter(|name| unsafe { db.cf_handle_unbounded(name) }.is_some())
+            .collect())
+    }
+
+    fn check_key_type(&self, key: &Bound<PyAny>) -> PyResult<()> {
+        check_type(key, &self.key_type, "key")
+    }
+
+    fn check_value_type(&self, value: &Bound<PyAny>) -> PyResult<()> {
+        check_type(value, &self.value_type, "value")
+    }
+
+    /// encode a key with the `raw_mode` and `normalize_str_bytes_keys` of this Rdict
+    fn encode_key<'a>(&self, key: &'a Bound<PyAny>) -> PyResult<Cow<'a, [u8]>> {
+        encode_key(
+            key,
+            self.opt_py.raw_mode,
+            self.opt_py.normalize_str_bytes_keys,
+        )
+    }
+
+    fn check_user_timestamp_mode(&self) -> PyResult<()> {
+        if !self.opt_py.raw_mode {
+            return Err(PyValueError::new_err(
+                "user-defined timestamps are only supported in raw mode",
+            ));
+        }
+        Ok(())
+    }
+
+    /// fail before touching RocksDB when the database cannot be written to
+    fn check_writable(&self) -> PyResult<()> {
+        match &self.access_type.0 {
+            AccessTypeInner::ReadOnly { .. } => Err(RocksDBError::new_err(
+                "cannot write: database opened read-only",
+            )),
+            AccessTypeInner::Secondary { .. } => Err(RocksDBError::new_err(
+                "cannot write: database opened as secondary",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn to_read_options(&self, read_opt: &ReadOptionsPy, py: Python) -> PyResult<ReadOptions> {
+        read_opt.check_snapshot(self.get_db()?, py)?;
+        read_opt.to_read_options(
+            self.opt_py.raw_mode,
+            self.opt_py.normalize_str_bytes_keys,
+            py,
+        )
+    }
+
+    /// lock the write buffer, without holding the GIL while waiting for it:
+    /// the thread holding it may need the GIL to finish its write
+    fn lock_write_buffer(&self, py: Python) -> MutexGuard<'_, Option<WriteBatch>> {
+        loop {
+            match self.write_buffer.try_lock() {
+                Ok(guard) => return guard,
+                Err(TryLockError::Poisoned(e)) => return e.into_inner(),
+                Err(TryLockError::WouldBlock) => {
+                    py.allow_threads(|| drop(self.write_buffer.lock()));
+                }
+            }
+        }
+    }
+
+    /// write the buffered operations, and clear them if the write succeeds
+    fn write_buffered(&self, batch: &mut WriteBatch, py: Python) -> PyResult<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let db = self.get_db()?;
+        // `write_opt` consumes the batch, write a copy so that
+        // the buffer is only cleared once the write succeeded
+        let copy = WriteBatch::from_data(batch.data());
+        py.allow_threads(|| db.write_opt(copy, &self.write_opt))
+            .map_err(rocksdb_error)?;
+        batch.clear();
+        Ok(())
+    }
+
+    fn flush_writes_if_full(&self, batch: &mut WriteBatch, py: Python) -> PyResult<()> {
+        if batch.len() >= self.write_buffer_max_ops.load(atomic::Ordering::Relaxed) {
+            self.write_buffered(batch, py)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Write sorted `(key, value)` pairs into SST files in `tmp_dir`,
+    /// pushing the path of each file created into `paths`.
+    fn write_sst_files(
+        &self,
+        items: &Bound<PyAny>,
+        tmp_dir: &Path,
+        max_file_size: u64,
+        paths: &mut Vec<PathBuf>,
+        py: Python,
+    ) -> PyResult<()> {
+        // number of items encoded with the GIL before writing them without
+        const CHUNK_SIZE: usize = 10000;
+        let raw_mode = self.opt_py.raw_mode;
+        let opts = &self.opt_py.inner_opt;
+        let mut items = items.try_iter()?;
+        let mut writer: Option<SstFileWriter> = None;
+        let mut last_key: Option<Vec<u8>> = None;
+        let mut index = 0usize;
+        loop {
+            let mut chunk: Vec<(Vec<u8>, Vec<u8>)> = Vec::with_capacity(CHUNK_SIZE);
+            for item in items.by_ref().take(CHUNK_SIZE) {
+                let (key, value): (Bound<PyAny>, Bound<PyAny>) = item?.extract()?;
+                let key = self.encode_key(&key)?.into_owned();
+                let value = encode_value(&value, &self.dumps, raw_mode)?.into_owned();
+                if let Some(last) = chunk.last().map(|(k, _)| k).or(last_key.as_ref()) {
+                    let ordering = if raw_mode {
+                        last.cmp(&key)
+                    } else {
+                        compare_keys(last, &key)
+                    };
+                    if ordering != Ordering::Less {
+                        return Err(PyValueError::new_err(format!(
+                            "items must be sorted by key in strictly ascending order, \
+                             item {index} is not greater than the previous one"
+                        )));
+                    }
+                }
+                chunk.push((key, value));
+                index += 1;
+            }
+            let Some((key, _)) = chunk.last() else {
+                break;
+            };
+            last_key = Some(key.clone());
+            py.allow_threads(|| {
+                for (key, value) in chunk.iter() {
+                    if writer.is_none() {
+                        let path = tmp_dir.join(format!("bulk_load_{}.sst", paths.len()));
+                        let new_writer = SstFileWriter::create(opts);
+                        new_writer.open(&path)?;
+                        paths.push(path);
+                        writer = Some(new_writer);
+                    }
+                    if let Some(w) = &mut writer {
+                        w.put(key, value)?;
+                        if w.file_size() >= max_file_size {
+                            w.finish()?;
+                            writer = None;
+                        }
+                    }
+                }
+                Ok::<_, rocksdb::Error>(())
+            })
+            .map_err(rocksdb_error)?;
+        }
+        if let Some(mut w) = writer {
+            py.allow_threads(|| w.finish()).map_err(rocksdb_error)?;
+        }
+        Ok(())
+    }
+
     fn get_batch_inner<'py>(
         &self,
         db: &DB,
         key_list: &Bound<PyList>,
         default: Option<Bound<'py, PyAny>>,
+        read_opt: &ReadOptions,
         py: Python<'py>,
         cf: &Arc<UnboundColumnFamily>,
     ) -> PyResult<Bound<'py, PyList>> {
         let keys_py = key_list.iter().collect::<Vec<_>>();
-        let mut keys: Vec<Cow<[u8]>> = Vec::with_capacity(key_list.len());
+        let values = self.multi_get_inner(db, &keys_py, read_opt, py, cf)?;
+        let result = PyList::empty(py);
+        for value in values {
+            match value {
+                None => {
+                    if let Some(default) = &default {
+                        result.append(default)?;
+                    } else {
+                        result.append(py.None())?;
+                    }
+                }
+                Some(value) => result.append(value)?,
+            }
+        }
+        Ok(result)
+    }
+
+    /// decoded values of the keys, None for missing keys
+    fn multi_get_inner<'py>(
+        &self,
+        db: &DB,
+        keys_py: &[Bound<PyAny>],
+        read_opt: &ReadOptions,
+        py: Python<'py>,
+        cf: &Arc<UnboundColumnFamily>,
+    ) -> PyResult<Vec<Option<Bound<'py, PyAny>>>> {
+        let mut keys: Vec<Cow<[u8]>> = Vec::with_capacity(keys_py.len());
         for key in keys_py.iter() {
-            keys.push(encode_key(key, self.opt_py.raw_mode)?);
+            self.check_key_type(key)?;
+            keys.push(self.encode_key(key)?);
         }
-        let values =
-            py.allow_threads(|| db.batched_multi_get_cf_opt(cf, &keys, false, &self.read_opt));
-        let result = PyList::empty(py);
+        let values = py.allow_threads(|| db.batched_multi_get_cf_opt(cf, &keys, false, read_opt));
+        let mut result = Vec::with_capacity(values.len());
         for v in values {
             match v {
-                Ok(value) => match value {
-                    None => {
-                        if let Some(default) = &default {
-                            result.append(default)?;
-                        } else {
-                            result.append(py.None())?;
-                        }
-                    }
-                    Some(slice) => result.append(decode_value(
+                Ok(value) => result.push(match value {
+                    None => None,
+                    Some(slice) => Some(decode_value(
                         py,
                         slice.as_ref(),
                         &self.loads,
                         self.opt_py.raw_mode,
-                    )?)?,
-                },
-                Err(e) => return Err(PyException::new_err(e.to_string())),
+                    )?),
+                }),
+                Err(e) => return Err(rocksdb_error(e)),
             }
         }
         Ok(result)
@@ -1412,18 +4219,31 @@ impl Rdict {
 impl Drop for Rdict {
     // flush
     fn drop(&mut self) {
-        if let Some(db) = self.db.get() {
-            let f_opt = &self.flush_opt;
-            let _ = if let Some(cf) = &self.column_family {
-                db.flush_cf_opt(cf, &f_opt.into())
-            } else {
-                db.flush_opt(&f_opt.into())
-            };
-        }
-        // important, always drop column families first
-        // to ensure that CF handles have shorter life than DB.
-        drop(self.column_family.take());
-        self.db.close();
+        // release the GIL: the flush may need it for Python comparators,
+        // merge operators or compaction filters running in background threads
+        Python::with_gil(|py| {
+            py.allow_threads(|| {
+                if let Some(db) = self.db.get() {
+                    let write_buffer = self.write_buffer.get_mut();
+                    if let Some(batch) = write_buffer.unwrap_or_else(|e| e.into_inner()).take() {
+                        if !batch.is_empty() {
+                            let _ = db.write_opt(batch, &self.write_opt);
+                        }
+                    }
+                    let f_opt = &self.flush_opt;
+                    let _ = match (self.drop_behavior, &self.column_family) {
+                        (DropBehavior::Flush, Some(cf)) => db.flush_cf_opt(cf, &f_opt.into()),
+                        (DropBehavior::Flush, None) => db.flush_opt(&f_opt.into()),
+                        (DropBehavior::FlushWal, _) => db.flush_wal(true),
+                        (DropBehavior::Nothing, _) => Ok(()),
+                    };
+                }
+                // important, always drop column families first
+                // to ensure that CF handles have shorter life than DB.
+                drop(self.column_family.take());
+                self.db.close();
+            })
+        })
     }
 }
 
@@ -1442,6 +4262,14 @@ pub(crate) struct ColumnFamilyPy {
 
 unsafe impl Send for ColumnFamilyPy {}
 
+#[pymethods]
+impl ColumnFamilyPy {
+    /// The id of the column family, 0 for the default column family.
+    fn id(&self) -> u32 {
+        unsafe { librocksdb_sys::rocksdb_column_family_handle_get_id(self.cf.inner()) }
+    }
+}
+
 #[pymethods]
 impl AccessType {
     /// Define DB Access Types.
@@ -1566,6 +4394,14 @@ impl AccessType {
     }
 }
 
+/// What `Rdict` writes to disk when it is closed or dropped.
+#[derive(Clone, Copy)]
+pub(crate) enum DropBehavior {
+    Flush,
+    FlushWal,
+    Nothing,
+}
+
 #[derive(Clone)]
 enum AccessTypeInner {
     ReadWrite,
@@ -1,10 +1,10 @@
 use crate::db_reference::{DbReference, DbReferenceHolder};
 use crate::encoder::{decode_value, encode_key};
-use crate::exceptions::DbClosedError;
+use crate::exceptions::{rocksdb_error, DbClosedError, KeyNotFoundError};
 use crate::{Rdict, RdictItems, RdictIter, RdictKeys, RdictValues, ReadOptionsPy};
 use pyo3::exceptions::PyException;
 use pyo3::prelude::*;
-use rocksdb::{ReadOptions, UnboundColumnFamily};
+use rocksdb::{ReadOptions, UnboundColumnFamily, DEFAULT_COLUMN_FAMILY_NAME};
 use std::sync::Arc;
 
 /// A consistent view of the database at the point of creation.
@@ -31,6 +31,8 @@ use std::sync::Arc;
 ///         # but they are still in the snapshot
 ///         for i in range(100):
 ///             assert snapshot[i] == i
+///         assert list(snapshot.keys()) == list(range(100))
+///         assert snapshot.get_batch([0, 95]) == [0, 95]
 ///
 ///         # drop the snapshot
 ///         del snapshot, db
@@ -45,6 +47,7 @@ pub struct Snapshot {
     // decrease db Rc last
     pub(crate) db: DbReferenceHolder,
     pub(crate) raw_mode: bool,
+    pub(crate) normalize_str_bytes_keys: bool,
 }
 
 #[pymethods]
@@ -60,17 +63,17 @@ impl Snapshot {
             None => ReadOptionsPy::default(py)?,
             Some(opt) => opt.clone(),
         };
-        let opt_pointer = read_opt.to_read_opt(self.raw_mode, py)?;
+        let opt_pointer = read_opt.to_read_opt(self.raw_mode, self.normalize_str_bytes_keys, py)?;
         unsafe {
             set_snapshot(opt_pointer.0, self.inner);
         }
-        RdictIter::new(
+        RdictIter::from_read_opt(
             &self.db,
             &self.column_family,
-            read_opt,
+            opt_pointer,
             &self.pickle_loads,
             self.raw_mode,
-            py,
+            self.normalize_str_bytes_keys,
         )
     }
 
@@ -90,7 +93,7 @@ impl Snapshot {
         read_opt: Option<&ReadOptionsPy>,
         py: Python,
     ) -> PyResult<RdictItems> {
-        RdictItems::new(self.iter(read_opt, py)?, backwards, from_key)
+        RdictItems::new(self.iter(read_opt, py)?, backwards, from_key, py)
     }
 
     /// Iterate through all keys.
@@ -109,7 +112,7 @@ impl Snapshot {
         read_opt: Option<&ReadOptionsPy>,
         py: Python,
     ) -> PyResult<RdictKeys> {
-        RdictKeys::new(self.iter(read_opt, py)?, backwards, from_key)
+        RdictKeys::new(self.iter(read_opt, py)?, backwards, from_key, py)
     }
 
     /// Iterate through all values.
@@ -128,13 +131,56 @@ impl Snapshot {
         read_opt: Option<&ReadOptionsPy>,
         py: Python,
     ) -> PyResult<RdictValues> {
-        RdictValues::new(self.iter(read_opt, py)?, backwards, from_key)
+        RdictValues::new(self.iter(read_opt, py)?, backwards, from_key, py)
+    }
+
+    /// Get the values of a list of keys from this snapshot, in one call.
+    ///
+    /// Args:
+    ///     keys: a list of keys.
+    ///     default: the default value to return for missing keys.
+    ///
+    /// Returns:
+    ///     the values in the order of `keys`,
+    ///     None or default value for missing keys.
+    #[pyo3(signature = (keys, default = None))]
+    fn get_batch<'py>(
+        &self,
+        keys: Vec<Bound<'py, PyAny>>,
+        default: Option<Bound<'py, PyAny>>,
+        py: Python<'py>,
+    ) -> PyResult<Vec<Bound<'py, PyAny>>> {
+        let db = self
+            .db
+            .get()
+            .ok_or_else(|| DbClosedError::new_err("DB instance already closed"))?;
+        let cf = match &self.column_family {
+            Some(cf) => cf.clone(),
+            None => unsafe { db.cf_handle_unbounded(DEFAULT_COLUMN_FAMILY_NAME) }
+                .ok_or_else(|| PyException::new_err("default column family not found"))?,
+        };
+        let keys = keys
+            .iter()
+            .map(|key| encode_key(key, self.raw_mode, self.normalize_str_bytes_keys))
+            .collect::<PyResult<Vec<_>>>()?;
+        let values =
+            py.allow_threads(|| db.batched_multi_get_cf_opt(&cf, &keys, false, &self.read_opt));
+        values
+            .into_iter()
+            .map(|value| match value.map_err(rocksdb_error)? {
+                None => Ok(default.clone().unwrap_or_else(|| py.None().into_bound(py))),
+                Some(slice) => decode_value(py, slice.as_ref(), &self.pickle_loads, self.raw_mode),
+            })
+            .collect()
     }
 
     /// read from snapshot
     fn __getitem__<'py>(&self, key: &Bound<PyAny>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        let db = self.get_db();
-        let key = encode_key(key, self.raw_mode)?;
+        let db = self
+            .db
+            .get()
+            .ok_or_else(|| DbClosedError::new_err("DB instance already closed"))?;
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
         let value_result = if let Some(cf) = &self.column_family {
             db.get_pinned_cf_opt(cf, &key[..], &self.read_opt)
         } else {
@@ -142,10 +188,10 @@ impl Snapshot {
         };
         match value_result {
             Ok(value) => match value {
-                None => Err(PyException::new_err("key not found")),
+                None => Err(KeyNotFoundError::new_err("key not found")),
                 Some(slice) => decode_value(py, slice.as_ref(), &self.pickle_loads, self.raw_mode),
             },
-            Err(e) => Err(PyException::new_err(e.to_string())),
+            Err(e) => Err(rocksdb_error(e)),
         }
     }
 }
@@ -158,9 +204,11 @@ impl Snapshot {
             .ok_or_else(|| DbClosedError::new_err("DB instance already closed"))?
             .inner();
         let snapshot = unsafe { librocksdb_sys::rocksdb_create_snapshot(db_inner) };
-        let r_opt: ReadOptions = rdict
-            .read_opt_py
-            .to_read_options(rdict.opt_py.raw_mode, py)?;
+        let r_opt: ReadOptions = rdict.read_opt_py.to_read_options(
+            rdict.opt_py.raw_mode,
+            rdict.opt_py.normalize_str_bytes_keys,
+            py,
+        )?;
         unsafe {
             set_snapshot(r_opt.inner(), snapshot);
         }
@@ -171,12 +219,13 @@ impl Snapshot {
             read_opt: r_opt,
             db: rdict.db.clone(),
             raw_mode: rdict.opt_py.raw_mode,
+            normalize_str_bytes_keys: rdict.opt_py.normalize_str_bytes_keys,
         })
     }
 
     fn get_db(&self) -> &DbReference {
         self.db
-            .get()
+            .get_unchecked()
             .expect("Snapshot should never close its DbReference")
     }
 }
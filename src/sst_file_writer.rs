@@ -1,8 +1,8 @@
 use crate::encoder::{encode_key, encode_value};
+use crate::exceptions::RocksDBError;
 use crate::util::{error_message, to_cpath};
-use crate::{ffi_try, ffi_try_impl, OptionsPy};
+use crate::{ffi_try, ffi_try_impl, OptionsPy, SerializerPy};
 use libc::{self, c_char, size_t};
-use pyo3::exceptions::PyException;
 use pyo3::prelude::*;
 use pyo3::PyResult;
 use rocksdb::Options;
@@ -20,6 +20,7 @@ pub struct SstFileWriterPy {
     opts: Options,
     dumps: PyObject,
     raw_mode: bool,
+    normalize_str_bytes_keys: bool,
 }
 
 unsafe impl Send for SstFileWriterPy {}
@@ -55,16 +56,17 @@ impl SstFileWriterPy {
     fn create(options: OptionsPy, py: Python) -> PyResult<Self> {
         let env_options = EnvOptions::default();
         let raw_mode = options.raw_mode;
+        let normalize_str_bytes_keys = options.normalize_str_bytes_keys;
+        let (_, pickle_dumps) = SerializerPy::loads_dumps(options.serializer.as_ref(), py)?;
         let options = &options.inner_opt;
         let writer = Self::create_raw(options, &env_options);
-        let pickle = PyModule::import(py, "pickle")?.unbind();
-        let pickle_dumps = pickle.getattr(py, "dumps")?;
 
         Ok(Self {
             inner: writer,
             opts: options.clone(),
             dumps: pickle_dumps,
             raw_mode,
+            normalize_str_bytes_keys,
         })
     }
 
@@ -92,16 +94,34 @@ impl SstFileWriterPy {
     /// Adds a Put key with value to currently opened file
     /// REQUIRES: key is after any previously added key according to comparator.
     fn __setitem__(&mut self, key: &Bound<PyAny>, value: &Bound<PyAny>) -> PyResult<()> {
-        let key = encode_key(key, self.raw_mode)?;
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
         let value = encode_value(value, &self.dumps, self.raw_mode)?;
-        self.setitem_raw(&key, &value)
+        self.setitem_raw(&key, &value).map_err(out_of_order_hint)
     }
 
     /// Adds a deletion key to currently opened file
     /// REQUIRES: key is after any previously added key according to comparator.
     fn __delitem__(&mut self, key: &Bound<PyAny>) -> PyResult<()> {
-        let key = encode_key(key, self.raw_mode)?;
-        self.delitem_raw(&key)
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
+        self.delitem_raw(&key).map_err(out_of_order_hint)
+    }
+
+    /// Same as `writer[key] = value`.
+    ///
+    /// Notes:
+    ///     Keys must be added in strictly increasing order, the order
+    ///     in which `Rdict` iterates them (in non-raw mode, keys of
+    ///     different types are ordered by type first).
+    fn put(&mut self, key: &Bound<PyAny>, value: &Bound<PyAny>) -> PyResult<()> {
+        self.__setitem__(key, value)
+    }
+
+    /// Same as `del writer[key]`.
+    ///
+    /// Notes:
+    ///     Keys must be added in strictly increasing order.
+    fn delete(&mut self, key: &Bound<PyAny>) -> PyResult<()> {
+        self.__delitem__(key)
     }
 }
 
@@ -168,6 +188,20 @@ impl SstFileWriterPy {
     }
 }
 
+/// RocksDB only says "Keys must be added in strict ascending order.",
+/// which does not tell that the order is the one of the DB comparator.
+fn out_of_order_hint(e: PyErr) -> PyErr {
+    let message = Python::with_gil(|py| e.value(py).to_string());
+    if message.contains("ascending order") {
+        RocksDBError::new_err(format!(
+            "{message} Keys must be added to SstFileWriter in the same order as \
+             Rdict iterates them, without duplicates."
+        ))
+    } else {
+        e
+    }
+}
+
 impl Drop for SstFileWriterPy {
     fn drop(&mut self) {
         unsafe {
@@ -0,0 +1,367 @@
+use crate::encoder::{decode_value, encode_key, encode_value};
+use crate::exceptions::{rocksdb_error, DbClosedError, KeyNotFoundError};
+use crate::options::{CachePy, EnvPy, SerializerPy};
+use crate::rdict::{config_file, DEFAULT_LRU_CACHE_SIZE, ENCODING_VERSION};
+use crate::{OptionsPy, RocksDictConfig};
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use rocksdb::{
+    ColumnFamilyDescriptor, MultiThreaded, OptimisticTransactionDB, OptimisticTransactionOptions,
+    Transaction, WriteOptions, DEFAULT_COLUMN_FAMILY_NAME,
+};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+type TxnDB = OptimisticTransactionDB<MultiThreaded>;
+
+/// A database supporting optimistic transactions.
+///
+/// Transactions group reads and writes to several keys: the writes are
+/// applied atomically on commit, and the commit fails if any key written
+/// (or read with `get_for_update`) by the transaction was modified by
+/// someone else in the meantime.
+///
+/// Notes:
+///     Only the default column family is supported. The database uses
+///     the same encoding as `Rdict`, and can be reopened with `Rdict`
+///     once closed.
+///
+/// Args:
+///     path (str): path to the database.
+///     options (Options): Options object. If not provided, the options
+///         of the existing database are loaded, or the default ones are used.
+///
+/// Example:
+///     ::
+///
+///         from rocksdict import OptimisticTransactionDB
+///
+///         db = OptimisticTransactionDB("./txn_db")
+///         db["balance_a"] = 100
+///         db["balance_b"] = 0
+///
+///         txn = db.transaction()
+///         txn["balance_a"] = txn.get_for_update("balance_a") - 10
+///         txn["balance_b"] = txn.get_for_update("balance_b") + 10
+///         txn.commit()
+///
+///         db.close()
+#[pyclass(name = "OptimisticTransactionDB")]
+pub(crate) struct OptimisticTransactionDBPy {
+    db: Option<Arc<TxnDB>>,
+    loads: PyObject,
+    dumps: PyObject,
+    raw_mode: bool,
+    normalize_str_bytes_keys: bool,
+}
+
+/// A transaction of an `OptimisticTransactionDB`.
+///
+/// Writes are only visible to the transaction itself until `commit`.
+/// A transaction can no longer be used after `commit` or `rollback`.
+#[pyclass(name = "Transaction")]
+pub(crate) struct TransactionPy {
+    // declared before `db` so that it is dropped first
+    inner: Mutex<Option<Transaction<'static, TxnDB>>>,
+    /// the transaction must keep a reference count of DB to keep DB alive.
+    #[allow(dead_code)]
+    db: Arc<TxnDB>,
+    loads: PyObject,
+    dumps: PyObject,
+    raw_mode: bool,
+    normalize_str_bytes_keys: bool,
+}
+
+impl OptimisticTransactionDBPy {
+    fn get_db(&self) -> PyResult<&Arc<TxnDB>> {
+        self.db
+            .as_ref()
+            .ok_or_else(|| DbClosedError::new_err("DB instance already closed"))
+    }
+}
+
+#[pymethods]
+impl OptimisticTransactionDBPy {
+    #[new]
+    #[pyo3(signature = (path, options = None))]
+    fn new(path: &str, options: Option<OptionsPy>, py: Python) -> PyResult<Self> {
+        fs::create_dir_all(path).map_err(|e| PyException::new_err(e.to_string()))?;
+        let options_loaded = OptionsPy::load_latest_inner(
+            path,
+            EnvPy::default()?,
+            false,
+            CachePy::new_lru_cache(DEFAULT_LRU_CACHE_SIZE),
+        );
+        // all column families must be opened, even if only the default one is used
+        let (options, column_families) = match (options_loaded, options) {
+            (Ok((_, cols_loaded)), Some(opt)) => (opt, cols_loaded),
+            (Ok((opt_loaded, cols_loaded)), None) => (opt_loaded, cols_loaded),
+            (Err(_), Some(opt)) => (opt, HashMap::new()),
+            (Err(_), None) => (OptionsPy::new(false), HashMap::new()),
+        };
+        let config_path = config_file(path);
+        let serializer_config = options.serializer.as_ref().map(|s| s.config.clone());
+        let mut prefix_extractors = HashMap::new();
+        let mut comparators = HashMap::new();
+        if let Ok(persisted_config) = RocksDictConfig::load(&config_path) {
+            persisted_config.check_encoding_version()?;
+            if persisted_config.raw_mode != options.raw_mode
+                || persisted_config.normalize_str_bytes_keys != options.normalize_str_bytes_keys
+                || persisted_config.serializer != serializer_config
+            {
+                return Err(PyException::new_err(
+                    "the database was written with a different raw_mode, \
+                     normalize_str_bytes_keys or serializer",
+                ));
+            }
+            prefix_extractors = persisted_config.prefix_extractors;
+            comparators = persisted_config.comparators;
+        }
+        if let Some(slice_transform) = &options.prefix_extractor {
+            prefix_extractors.insert(
+                DEFAULT_COLUMN_FAMILY_NAME.to_string(),
+                slice_transform.clone(),
+            );
+        }
+        if let Some(comparator) = options.comparator {
+            comparators.insert(DEFAULT_COLUMN_FAMILY_NAME.to_string(), comparator);
+        }
+        RocksDictConfig {
+            raw_mode: options.raw_mode,
+            prefix_extractors,
+            normalize_str_bytes_keys: options.normalize_str_bytes_keys,
+            encoding_version: ENCODING_VERSION,
+            serializer: serializer_config,
+            comparators,
+        }
+        .save(config_path)?;
+        let opt_inner = &options.inner_opt;
+        let mut cfs = column_families
+            .into_iter()
+            .filter(|(name, _)| name != DEFAULT_COLUMN_FAMILY_NAME)
+            .map(|(name, opt)| ColumnFamilyDescriptor::new(name, opt.inner_opt))
+            .collect::<Vec<_>>();
+        cfs.push(ColumnFamilyDescriptor::new(
+            DEFAULT_COLUMN_FAMILY_NAME,
+            opt_inner.clone(),
+        ));
+        let db = py
+            .allow_threads(|| TxnDB::open_cf_descriptors(opt_inner, path, cfs))
+            .map_err(rocksdb_error)?;
+        let (loads, dumps) = SerializerPy::loads_dumps(options.serializer.as_ref(), py)?;
+        Ok(Self {
+            db: Some(Arc::new(db)),
+            loads,
+            dumps,
+            raw_mode: options.raw_mode,
+            normalize_str_bytes_keys: options.normalize_str_bytes_keys,
+        })
+    }
+
+    /// Begins a new transaction.
+    ///
+    /// Args:
+    ///     set_snapshot: take a snapshot when the transaction begins, so that
+    ///         the commit fails if any key written by the transaction was
+    ///         modified after the transaction began, rather than after the
+    ///         key was first written. Reads are not made from the snapshot.
+    #[pyo3(signature = (set_snapshot = false))]
+    fn transaction(&self, set_snapshot: bool, py: Python) -> PyResult<TransactionPy> {
+        let db = self.get_db()?.clone();
+        let mut txn_opts = OptimisticTransactionOptions::default();
+        txn_opts.set_snapshot(set_snapshot);
+        let txn = db.transaction_opt(&WriteOptions::default(), &txn_opts);
+        // SAFETY: the transaction keeps the DB alive, and is dropped before it.
+        let txn = unsafe {
+            std::mem::transmute::<Transaction<'_, TxnDB>, Transaction<'static, TxnDB>>(txn)
+        };
+        Ok(TransactionPy {
+            inner: Mutex::new(Some(txn)),
+            db,
+            loads: self.loads.clone_ref(py),
+            dumps: self.dumps.clone_ref(py),
+            raw_mode: self.raw_mode,
+            normalize_str_bytes_keys: self.normalize_str_bytes_keys,
+        })
+    }
+
+    /// Get the value of a key outside of any transaction.
+    ///
+    /// Returns:
+    ///    None or default value if the key does not exist.
+    #[pyo3(signature = (key, default = None))]
+    fn get<'py>(
+        &self,
+        key: &Bound<PyAny>,
+        default: Option<Bound<'py, PyAny>>,
+        py: Python<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
+        let value = self.get_db()?.get_pinned(key).map_err(rocksdb_error)?;
+        match value {
+            None => Ok(default.unwrap_or_else(|| py.None().into_bound(py))),
+            Some(slice) => decode_value(py, slice.as_ref(), &self.loads, self.raw_mode),
+        }
+    }
+
+    fn __getitem__<'py>(&self, key: &Bound<PyAny>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let value = self.get(key, None, py)?;
+        if value.is_none() {
+            return Err(KeyNotFoundError::new_err(format!("key {key} not found")));
+        }
+        Ok(value)
+    }
+
+    /// Write a key outside of any transaction.
+    fn put(&self, key: &Bound<PyAny>, value: &Bound<PyAny>) -> PyResult<()> {
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
+        let value = encode_value(value, &self.dumps, self.raw_mode)?;
+        self.get_db()?.put(key, value).map_err(rocksdb_error)
+    }
+
+    fn __setitem__(&self, key: &Bound<PyAny>, value: &Bound<PyAny>) -> PyResult<()> {
+        self.put(key, value)
+    }
+
+    /// Delete a key outside of any transaction.
+    fn delete(&self, key: &Bound<PyAny>) -> PyResult<()> {
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
+        self.get_db()?.delete(key).map_err(rocksdb_error)
+    }
+
+    fn __delitem__(&self, key: &Bound<PyAny>) -> PyResult<()> {
+        self.delete(key)
+    }
+
+    /// Close the database.
+    ///
+    /// The database is only closed once all its transactions are dropped.
+    fn close(&mut self) {
+        self.db = None;
+    }
+}
+
+impl TransactionPy {
+    fn with_txn<T>(
+        &self,
+        f: impl FnOnce(&Transaction<'static, TxnDB>) -> Result<T, rocksdb::Error>,
+    ) -> PyResult<T> {
+        let guard = self.inner.lock().unwrap();
+        let txn = guard
+            .as_ref()
+            .ok_or_else(|| PyException::new_err("transaction already committed or rolled back"))?;
+        f(txn).map_err(rocksdb_error)
+    }
+
+    fn decode<'py>(
+        &self,
+        value: Option<Vec<u8>>,
+        default: Option<Bound<'py, PyAny>>,
+        py: Python<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        match value {
+            None => Ok(default.unwrap_or_else(|| py.None().into_bound(py))),
+            Some(value) => decode_value(py, &value, &self.loads, self.raw_mode),
+        }
+    }
+}
+
+#[pymethods]
+impl TransactionPy {
+    /// Get the value of a key, including the writes of this transaction.
+    ///
+    /// Returns:
+    ///    None or default value if the key does not exist.
+    #[pyo3(signature = (key, default = None))]
+    fn get<'py>(
+        &self,
+        key: &Bound<PyAny>,
+        default: Option<Bound<'py, PyAny>>,
+        py: Python<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
+        let value = self.with_txn(|txn| txn.get(key))?;
+        self.decode(value, default, py)
+    }
+
+    /// Get the value of a key, and make the commit fail if the key is
+    /// modified outside of this transaction after this read.
+    ///
+    /// Returns:
+    ///    None or default value if the key does not exist.
+    #[pyo3(signature = (key, default = None))]
+    fn get_for_update<'py>(
+        &self,
+        key: &Bound<PyAny>,
+        default: Option<Bound<'py, PyAny>>,
+        py: Python<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
+        let value = self.with_txn(|txn| txn.get_for_update(key, true))?;
+        self.decode(value, default, py)
+    }
+
+    fn __getitem__<'py>(&self, key: &Bound<PyAny>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let value = self.get(key, None, py)?;
+        if value.is_none() {
+            return Err(KeyNotFoundError::new_err(format!("key {key} not found")));
+        }
+        Ok(value)
+    }
+
+    /// Write a key in this transaction.
+    fn put(&self, key: &Bound<PyAny>, value: &Bound<PyAny>) -> PyResult<()> {
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
+        let value = encode_value(value, &self.dumps, self.raw_mode)?;
+        self.with_txn(|txn| txn.put(key, value))
+    }
+
+    fn __setitem__(&self, key: &Bound<PyAny>, value: &Bound<PyAny>) -> PyResult<()> {
+        self.put(key, value)
+    }
+
+    /// Delete a key in this transaction.
+    fn delete(&self, key: &Bound<PyAny>) -> PyResult<()> {
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
+        self.with_txn(|txn| txn.delete(key))
+    }
+
+    fn __delitem__(&self, key: &Bound<PyAny>) -> PyResult<()> {
+        self.delete(key)
+    }
+
+    /// Write all the changes of this transaction atomically.
+    ///
+    /// Raises an exception starting with "Resource busy" if a key of this
+    /// transaction was modified outside of it. Nothing is written then,
+    /// and the transaction is rolled back: start a new one to retry.
+    fn commit(&self, py: Python) -> PyResult<()> {
+        let txn =
+            self.inner.lock().unwrap().take().ok_or_else(|| {
+                PyException::new_err("transaction already committed or rolled back")
+            })?;
+        py.allow_threads(|| txn.commit()).map_err(rocksdb_error)
+    }
+
+    /// Discard all the changes of this transaction.
+    fn rollback(&self) -> PyResult<()> {
+        self.with_txn(|txn| txn.rollback())?;
+        self.inner.lock().unwrap().take();
+        Ok(())
+    }
+
+    /// Records the state of the transaction, to return to with
+    /// `rollback_to_savepoint`.
+    fn set_savepoint(&self) -> PyResult<()> {
+        self.with_txn(|txn| {
+            txn.set_savepoint();
+            Ok(())
+        })
+    }
+
+    /// Discards the changes made since the last `set_savepoint`.
+    fn rollback_to_savepoint(&self) -> PyResult<()> {
+        self.with_txn(|txn| txn.rollback_to_savepoint())
+    }
+}
@@ -3,6 +3,7 @@ use pyo3::exceptions::PyException;
 use pyo3::PyResult;
 use std::ffi::{CStr, CString};
 use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[macro_export]
 macro_rules! ffi_try {
@@ -21,7 +22,7 @@ macro_rules! ffi_try_impl {
         let mut err: *mut ::libc::c_char = ::std::ptr::null_mut();
         let result = $($function)::*($($arg,)* &mut err);
         if !err.is_null() {
-            return Err(PyException::new_err(error_message(err)));
+            return Err($crate::exceptions::RocksDBError::new_err(error_message(err)));
         }
         result
     }};
@@ -48,3 +49,11 @@ pub(crate) fn to_cpath<P: AsRef<Path>>(path: P) -> PyResult<CString> {
         ))),
     }
 }
+
+/// Seconds since the unix epoch.
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
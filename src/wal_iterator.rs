@@ -0,0 +1,87 @@
+use crate::db_reference::DbReferenceHolder;
+use crate::exceptions::RocksDBError;
+use crate::WriteBatchPy;
+use pyo3::prelude::*;
+use rocksdb::DBWALIterator;
+
+/// Iterator over the write batches of the WAL, returned by
+/// `Rdict.get_updates_since`.
+///
+/// It yields `(sequence_number, WriteBatch)` pairs, where the sequence
+/// number is the one of the first operation of the batch. Use
+/// `WriteBatch.operations()` to inspect the operations of a batch.
+#[pyclass(name = "WalIterator")]
+pub(crate) struct WalIteratorPy {
+    // declared before `db` so that it is dropped first
+    inner: DBWALIterator,
+    /// the iterator must keep a reference count of DB to keep DB alive.
+    #[allow(dead_code)]
+    db: DbReferenceHolder,
+    since: u64,
+    dumps: PyObject,
+    loads: PyObject,
+    raw_mode: bool,
+    normalize_str_bytes_keys: bool,
+}
+
+unsafe impl Send for WalIteratorPy {}
+
+// the WAL iterator is only used through `&mut self` (PyO3 borrow checking),
+// so it is never accessed from two threads at once
+unsafe impl Sync for WalIteratorPy {}
+
+impl WalIteratorPy {
+    pub(crate) fn new(
+        inner: DBWALIterator,
+        db: DbReferenceHolder,
+        since: u64,
+        dumps: PyObject,
+        loads: PyObject,
+        raw_mode: bool,
+        normalize_str_bytes_keys: bool,
+    ) -> Self {
+        Self {
+            inner,
+            db,
+            since,
+            dumps,
+            loads,
+            raw_mode,
+            normalize_str_bytes_keys,
+        }
+    }
+}
+
+#[pymethods]
+impl WalIteratorPy {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<(u64, WriteBatchPy)>> {
+        match self.inner.next() {
+            None => Ok(None),
+            Some(Err(e)) => Err(wal_error(e, self.since)),
+            Some(Ok((seq, batch))) => Ok(Some((
+                seq,
+                WriteBatchPy::from_write_batch(
+                    batch,
+                    self.dumps.clone_ref(py),
+                    self.loads.clone_ref(py),
+                    self.raw_mode,
+                    self.normalize_str_bytes_keys,
+                ),
+            ))),
+        }
+    }
+}
+
+/// Explains the usual cause of WAL read errors: the WAL files were deleted.
+pub(crate) fn wal_error(e: rocksdb::Error, since: u64) -> PyErr {
+    RocksDBError::new_err(format!(
+        "cannot read the WAL since sequence number {since}: {e}. \
+         The WAL files holding these updates may have been deleted after a flush, \
+         use `Options.set_wal_ttl_seconds` or `Options.set_wal_size_limit_mb` \
+         to keep them longer"
+    ))
+}
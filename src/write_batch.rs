@@ -1,8 +1,11 @@
-use crate::encoder::{encode_key, encode_value};
-use crate::ColumnFamilyPy;
+use crate::encoder::{decode_value, encode_key, encode_value};
+use crate::exceptions::rocksdb_error;
+use crate::util::error_message;
+use crate::{ffi_try, ffi_try_impl, ColumnFamilyPy};
 use pyo3::exceptions::PyException;
 use pyo3::prelude::*;
-use rocksdb::WriteBatch;
+use pyo3::types::PyTuple;
+use rocksdb::{WriteBatch, WriteBatchIteratorCf};
 
 macro_rules! inner_ref {
     ($self:ident) => {
@@ -34,12 +37,16 @@ macro_rules! inner_mut {
 ///
 /// Args:
 ///     raw_mode (bool): make sure that this is consistent with the Rdict.
+///     normalize_str_bytes_keys (bool): make sure that this is consistent
+///         with the Rdict (see `Options.set_normalize_str_bytes_keys`).
 #[pyclass(name = "WriteBatch")]
 pub(crate) struct WriteBatchPy {
     inner: Option<WriteBatch>,
     default_column_family: Option<ColumnFamilyPy>,
-    dumps: PyObject,
+    pub(crate) dumps: PyObject,
+    loads: PyObject,
     pub(crate) raw_mode: bool,
+    pub(crate) normalize_str_bytes_keys: bool,
 }
 
 #[pymethods]
@@ -52,15 +59,19 @@ impl WriteBatchPy {
     ///
     /// Args:
     ///     raw_mode (bool): make sure that this is consistent with the Rdict.
+    ///     normalize_str_bytes_keys (bool): make sure that this is consistent
+    ///         with the Rdict (see `Options.set_normalize_str_bytes_keys`).
     #[new]
-    #[pyo3(signature = (raw_mode = false))]
-    pub fn default(py: Python, raw_mode: bool) -> PyResult<Self> {
+    #[pyo3(signature = (raw_mode = false, normalize_str_bytes_keys = false))]
+    pub fn default(py: Python, raw_mode: bool, normalize_str_bytes_keys: bool) -> PyResult<Self> {
         let pickle = PyModule::import(py, "pickle")?.unbind();
         Ok(WriteBatchPy {
             inner: Some(WriteBatch::default()),
             default_column_family: None,
             dumps: pickle.getattr(py, "dumps")?,
+            loads: pickle.getattr(py, "loads")?,
             raw_mode,
+            normalize_str_bytes_keys,
         })
     }
 
@@ -69,13 +80,18 @@ impl WriteBatchPy {
         self.dumps = dumps
     }
 
+    /// change to a custom loads function
+    pub fn set_loads(&mut self, loads: PyObject) {
+        self.loads = loads
+    }
+
     pub fn __len__(&self) -> PyResult<usize> {
         self.len()
     }
 
     pub fn __setitem__(&mut self, key: &Bound<PyAny>, value: &Bound<PyAny>) -> PyResult<()> {
         let inner = inner_mut!(self)?;
-        let key = encode_key(key, self.raw_mode)?;
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
         let value = encode_value(value, &self.dumps, self.raw_mode)?;
         match &self.default_column_family {
             None => inner.put(key, value),
@@ -86,7 +102,7 @@ impl WriteBatchPy {
 
     pub fn __delitem__(&mut self, key: &Bound<PyAny>) -> PyResult<()> {
         let inner = inner_mut!(self)?;
-        let key = encode_key(key, self.raw_mode)?;
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
         match &self.default_column_family {
             None => inner.delete(key),
             Some(cf) => inner.delete_cf(&cf.cf, key),
@@ -139,7 +155,7 @@ impl WriteBatchPy {
         column_family: Option<ColumnFamilyPy>,
     ) -> PyResult<()> {
         let inner = inner_mut!(self)?;
-        let key = encode_key(key, self.raw_mode)?;
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
         let value = encode_value(value, &self.dumps, self.raw_mode)?;
         match column_family {
             Some(cf) => inner.put_cf(&cf.cf, key, value),
@@ -148,9 +164,33 @@ impl WriteBatchPy {
         Ok(())
     }
 
+    /// Merge a value into the database under the given key,
+    /// using the merge operator of the column family.
+    ///
+    /// Args:
+    ///     column_family: override the default column family set by set_default_column_family
+    #[pyo3(signature = (key, value, column_family = None))]
+    pub fn merge(
+        &mut self,
+        key: &Bound<PyAny>,
+        value: &Bound<PyAny>,
+        column_family: Option<ColumnFamilyPy>,
+    ) -> PyResult<()> {
+        let inner = inner_mut!(self)?;
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
+        let value = encode_value(value, &self.dumps, self.raw_mode)?;
+        match column_family {
+            Some(cf) => inner.merge_cf(&cf.cf, key, value),
+            None => inner.merge(key, value),
+        }
+        Ok(())
+    }
+
     /// Insert a wide-column.
     ///
     /// The length of `names` and `values` must be the same.
+    /// Columns are stored sorted by name, so they are read back
+    /// in name order rather than in the order they are given.
     ///
     /// Args:
     ///     key: the key.
@@ -165,7 +205,7 @@ impl WriteBatchPy {
         values: Vec<Bound<PyAny>>,
     ) -> PyResult<()> {
         let inner = inner_mut!(self)?;
-        let key = encode_key(key, self.raw_mode)?;
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
         let cf = if let Some(cf) = &self.default_column_family {
             cf
         } else {
@@ -188,7 +228,7 @@ impl WriteBatchPy {
         }
         inner
             .put_entity_cf_opt(&cf.cf, key, &names_vec, &values_vec)
-            .map_err(|e| PyException::new_err(e.to_string()))
+            .map_err(rocksdb_error)
     }
 
     /// Removes the database entry for key. Does nothing if the key was not found.
@@ -202,7 +242,7 @@ impl WriteBatchPy {
         column_family: Option<ColumnFamilyPy>,
     ) -> PyResult<()> {
         let inner = inner_mut!(self)?;
-        let key = encode_key(key, self.raw_mode)?;
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
         match column_family {
             Some(cf) => inner.delete_cf(&cf.cf, key),
             None => inner.delete(key),
@@ -229,8 +269,8 @@ impl WriteBatchPy {
         column_family: Option<ColumnFamilyPy>,
     ) -> PyResult<()> {
         let inner = inner_mut!(self)?;
-        let from = encode_key(begin, self.raw_mode)?;
-        let to = encode_key(end, self.raw_mode)?;
+        let from = encode_key(begin, self.raw_mode, self.normalize_str_bytes_keys)?;
+        let to = encode_key(end, self.raw_mode, self.normalize_str_bytes_keys)?;
         match column_family {
             Some(cf) => inner.delete_range_cf(&cf.cf, from, to),
             None => inner.delete_range(from, to),
@@ -238,15 +278,171 @@ impl WriteBatchPy {
         Ok(())
     }
 
-    /// Clear all updates buffered in this batch.
+    /// Clear all updates buffered in this batch, and its save points.
     pub fn clear(&mut self) -> PyResult<()> {
         let inner = inner_mut!(self)?;
         inner.clear();
         Ok(())
     }
+
+    /// Records the state of the batch, to be restored by `rollback_to_save_point`.
+    ///
+    /// Save points can be nested: each call of `rollback_to_save_point`
+    /// or `pop_save_point` applies to the most recent save point.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         wb = WriteBatch()
+    ///         wb["a"] = 1
+    ///         wb.set_save_point()
+    ///         wb["b"] = 2
+    ///         wb.rollback_to_save_point()
+    ///         # only "a" is written
+    ///         db.write(wb)
+    pub fn set_save_point(&mut self) -> PyResult<()> {
+        let inner = inner_mut!(self)?;
+        unsafe { librocksdb_sys::rocksdb_writebatch_set_save_point(inner.inner()) };
+        Ok(())
+    }
+
+    /// Removes the updates made since the most recent save point,
+    /// and removes this save point.
+    pub fn rollback_to_save_point(&mut self) -> PyResult<()> {
+        let inner = inner_mut!(self)?;
+        unsafe {
+            ffi_try!(librocksdb_sys::rocksdb_writebatch_rollback_to_save_point(
+                inner.inner(),
+            ))
+        };
+        Ok(())
+    }
+
+    /// Removes the most recent save point, keeping the updates made since.
+    pub fn pop_save_point(&mut self) -> PyResult<()> {
+        let inner = inner_mut!(self)?;
+        unsafe {
+            ffi_try!(librocksdb_sys::rocksdb_writebatch_pop_save_point(
+                inner.inner(),
+            ))
+        };
+        Ok(())
+    }
+
+    /// The operations of the batch, in the order they were made.
+    ///
+    /// Notes:
+    ///     The listing stops at the first `delete_range` or `put_entity`
+    ///     operation, which are not supported.
+    ///
+    /// Returns:
+    ///     a list of `("put", cf_id, key, value)`, `("delete", cf_id, key, None)`
+    ///     and `("merge", cf_id, key, value)` tuples, where `cf_id` is the
+    ///     `ColumnFamily.id()` of the column family (0 for the default one).
+    pub fn operations<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyTuple>>> {
+        self.decoded_operations(py)?
+            .into_iter()
+            .map(|(name, cf_id, key, value)| {
+                PyTuple::new(
+                    py,
+                    [
+                        name.into_pyobject(py)?.into_any(),
+                        cf_id.into_pyobject(py)?.into_any(),
+                        key,
+                        value,
+                    ],
+                )
+            })
+            .collect()
+    }
+
+    /// Calls the methods of `handler` for the operations of the batch,
+    /// in the order they were made.
+    ///
+    /// The handler may define `put(cf_id, key, value)`, `delete(cf_id, key)`
+    /// and `merge(cf_id, key, value)`, operations without a method are skipped.
+    /// `cf_id` is the `ColumnFamily.id()` of the column family
+    /// (0 for the default one).
+    ///
+    /// Notes:
+    ///     The iteration stops at the first `delete_range` or `put_entity`
+    ///     operation, which are not supported.
+    ///
+    /// Example:
+    ///     ::
+    ///
+    ///         class Printer:
+    ///             def put(self, cf_id, key, value):
+    ///                 print(f"put {key} = {value}")
+    ///
+    ///             def delete(self, cf_id, key):
+    ///                 print(f"delete {key}")
+    ///
+    ///         wb = WriteBatch()
+    ///         wb["a"] = 1
+    ///         del wb["b"]
+    ///         wb.iterate(Printer())
+    pub fn iterate(&self, handler: &Bound<PyAny>) -> PyResult<()> {
+        for (name, cf_id, key, value) in self.decoded_operations(handler.py())? {
+            if !handler.hasattr(name)? {
+                continue;
+            }
+            if name == "delete" {
+                handler.call_method1(name, (cf_id, key))?;
+            } else {
+                handler.call_method1(name, (cf_id, key, value))?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl WriteBatchPy {
+    /// Wraps a batch read from the database, e.g. from the WAL.
+    pub(crate) fn from_write_batch(
+        batch: WriteBatch,
+        dumps: PyObject,
+        loads: PyObject,
+        raw_mode: bool,
+        normalize_str_bytes_keys: bool,
+    ) -> Self {
+        WriteBatchPy {
+            inner: Some(batch),
+            default_column_family: None,
+            dumps,
+            loads,
+            raw_mode,
+            normalize_str_bytes_keys,
+        }
+    }
+
+    /// `(name, cf_id, key, value)` of the operations, value is None for deletes.
+    fn decoded_operations<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> PyResult<Vec<(&'static str, u32, Bound<'py, PyAny>, Bound<'py, PyAny>)>> {
+        let inner = inner_ref!(self)?;
+        let mut operations = BatchOperations::default();
+        inner.iterate_cf(&mut operations);
+        operations
+            .0
+            .into_iter()
+            .map(|operation| {
+                let (name, cf_id, key, value) = match operation {
+                    BatchOperation::Put(cf_id, key, value) => ("put", cf_id, key, Some(value)),
+                    BatchOperation::Delete(cf_id, key) => ("delete", cf_id, key, None),
+                    BatchOperation::Merge(cf_id, key, value) => ("merge", cf_id, key, Some(value)),
+                };
+                let value = match value {
+                    Some(value) => decode_value(py, &value, &self.loads, self.raw_mode)?,
+                    None => py.None().into_bound(py),
+                };
+                let key = decode_value(py, &key, &self.loads, self.raw_mode)?;
+                Ok((name, cf_id, key, value))
+            })
+            .collect()
+    }
+
     #[inline]
     pub(crate) fn consume(&mut self) -> PyResult<WriteBatch> {
         if let Some(inner) = self.inner.take() {
@@ -261,3 +457,29 @@ impl WriteBatchPy {
 }
 
 unsafe impl Sync for WriteBatchPy {}
+
+/// An operation of a batch, with the id of its column family.
+enum BatchOperation {
+    Put(u32, Vec<u8>, Vec<u8>),
+    Delete(u32, Vec<u8>),
+    Merge(u32, Vec<u8>, Vec<u8>),
+}
+
+#[derive(Default)]
+struct BatchOperations(Vec<BatchOperation>);
+
+impl WriteBatchIteratorCf for BatchOperations {
+    fn put_cf(&mut self, cf_id: u32, key: &[u8], value: &[u8]) {
+        self.0
+            .push(BatchOperation::Put(cf_id, key.to_vec(), value.to_vec()));
+    }
+
+    fn delete_cf(&mut self, cf_id: u32, key: &[u8]) {
+        self.0.push(BatchOperation::Delete(cf_id, key.to_vec()));
+    }
+
+    fn merge_cf(&mut self, cf_id: u32, key: &[u8], value: &[u8]) {
+        self.0
+            .push(BatchOperation::Merge(cf_id, key.to_vec(), value.to_vec()));
+    }
+}
@@ -0,0 +1,307 @@
+use crate::encoder::{decode_value, encode_key, encode_value};
+use crate::util::error_message;
+use crate::{ffi_try, ffi_try_impl, Rdict, ReadOptionsPy};
+use libc::{c_char, c_void, size_t};
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+use rocksdb::{Options, WriteBatch};
+use std::slice;
+
+/// WriteBatch whose staged writes can be read back before being written.
+///
+/// Use `get_from_batch` to read the staged writes only, or
+/// `get_from_batch_and_db` to read the staged writes on top of a DB.
+/// Use db.write() to write it.
+///
+/// Notes:
+///     Only the default column family is supported, and merge operands
+///     are not supported. Unlike `WriteBatch`, writing this batch to a
+///     DB does not consume it.
+///
+/// Args:
+///     raw_mode (bool): make sure that this is consistent with the Rdict.
+///     normalize_str_bytes_keys (bool): make sure that this is consistent
+///         with the Rdict (see `Options.set_normalize_str_bytes_keys`).
+///
+/// Example:
+///     ::
+///
+///         from rocksdict import Rdict, WriteBatchWithIndex
+///
+///         db = Rdict("./test_dir")
+///         db["a"] = 1
+///
+///         wb = WriteBatchWithIndex()
+///         wb["b"] = 2
+///         del wb["a"]
+///         assert wb.get_from_batch("b") == 2
+///         assert wb.get_from_batch_and_db(db, "a") is None
+///         db.write(wb)
+#[pyclass(name = "WriteBatchWithIndex")]
+pub(crate) struct WriteBatchWithIndexPy {
+    inner: *mut librocksdb_sys::rocksdb_writebatch_wi_t,
+    opts: Options,
+    pub(crate) dumps: PyObject,
+    loads: PyObject,
+    pub(crate) raw_mode: bool,
+    pub(crate) normalize_str_bytes_keys: bool,
+}
+
+unsafe impl Send for WriteBatchWithIndexPy {}
+unsafe impl Sync for WriteBatchWithIndexPy {}
+
+/// An entry of the batch, in the order of the writes.
+enum StagedEntry {
+    Put(Vec<u8>, Vec<u8>),
+    Delete(Vec<u8>),
+}
+
+unsafe extern "C" fn staged_put(
+    state: *mut c_void,
+    k: *const c_char,
+    klen: size_t,
+    v: *const c_char,
+    vlen: size_t,
+) {
+    let entries = &mut *(state as *mut Vec<StagedEntry>);
+    entries.push(StagedEntry::Put(
+        slice::from_raw_parts(k as *const u8, klen).to_vec(),
+        slice::from_raw_parts(v as *const u8, vlen).to_vec(),
+    ));
+}
+
+unsafe extern "C" fn staged_delete(state: *mut c_void, k: *const c_char, klen: size_t) {
+    let entries = &mut *(state as *mut Vec<StagedEntry>);
+    entries.push(StagedEntry::Delete(
+        slice::from_raw_parts(k as *const u8, klen).to_vec(),
+    ));
+}
+
+#[pymethods]
+impl WriteBatchWithIndexPy {
+    #[new]
+    #[pyo3(signature = (raw_mode = false, normalize_str_bytes_keys = false))]
+    fn new(py: Python, raw_mode: bool, normalize_str_bytes_keys: bool) -> PyResult<Self> {
+        let pickle = PyModule::import(py, "pickle")?.unbind();
+        // later writes to a key overwrite the previous ones in the index
+        let inner = unsafe { librocksdb_sys::rocksdb_writebatch_wi_create(0, 1) };
+        Ok(Self {
+            inner,
+            opts: Options::default(),
+            dumps: pickle.getattr(py, "dumps")?,
+            loads: pickle.getattr(py, "loads")?,
+            raw_mode,
+            normalize_str_bytes_keys,
+        })
+    }
+
+    /// change to a custom dumps function
+    fn set_dumps(&mut self, dumps: PyObject) {
+        self.dumps = dumps
+    }
+
+    /// change to a custom loads function
+    fn set_loads(&mut self, loads: PyObject) {
+        self.loads = loads
+    }
+
+    /// number of writes in the batch, overwritten ones included
+    fn __len__(&self) -> usize {
+        unsafe { librocksdb_sys::rocksdb_writebatch_wi_count(self.inner) as usize }
+    }
+
+    fn __setitem__(&mut self, key: &Bound<PyAny>, value: &Bound<PyAny>) -> PyResult<()> {
+        self.put(key, value)
+    }
+
+    fn __delitem__(&mut self, key: &Bound<PyAny>) -> PyResult<()> {
+        self.delete(key)
+    }
+
+    /// Insert a value into the database under the given key.
+    fn put(&mut self, key: &Bound<PyAny>, value: &Bound<PyAny>) -> PyResult<()> {
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
+        let value = encode_value(value, &self.dumps, self.raw_mode)?;
+        unsafe {
+            librocksdb_sys::rocksdb_writebatch_wi_put(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                value.as_ptr() as *const c_char,
+                value.len() as size_t,
+            );
+        }
+        Ok(())
+    }
+
+    /// Removes the database entry for key. Does nothing if the key was not found.
+    fn delete(&mut self, key: &Bound<PyAny>) -> PyResult<()> {
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
+        unsafe {
+            librocksdb_sys::rocksdb_writebatch_wi_delete(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            );
+        }
+        Ok(())
+    }
+
+    /// Get the value staged in the batch for a key.
+    ///
+    /// Returns:
+    ///     None or default value if the key is not in the batch,
+    ///     or if it is deleted by the batch.
+    #[pyo3(signature = (key, default = None))]
+    fn get_from_batch<'py>(
+        &self,
+        key: &Bound<PyAny>,
+        default: Option<Bound<'py, PyAny>>,
+        py: Python<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
+        let mut value_len: size_t = 0;
+        let value = unsafe {
+            ffi_try!(librocksdb_sys::rocksdb_writebatch_wi_get_from_batch(
+                self.inner,
+                self.opts.inner(),
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                &mut value_len,
+            ))
+        };
+        self.take_value(value, value_len, default, py)
+    }
+
+    /// Get the value of a key as if the batch was written to the DB.
+    ///
+    /// Args:
+    ///     db: the DB to read from, in the same `raw_mode` as the batch.
+    ///     key: the key.
+    ///     default: the default value to return if key not found.
+    ///     read_opt: override the read options of the DB.
+    ///
+    /// Returns:
+    ///     None or default value if the key is deleted by the batch,
+    ///     or is neither in the batch nor in the DB.
+    #[pyo3(signature = (db, key, default = None, read_opt = None))]
+    fn get_from_batch_and_db<'py>(
+        &self,
+        db: &Rdict,
+        key: &Bound<PyAny>,
+        default: Option<Bound<'py, PyAny>>,
+        read_opt: Option<&ReadOptionsPy>,
+        py: Python<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        db.check_batch_mode(
+            self.raw_mode,
+            self.normalize_str_bytes_keys,
+            &self.dumps,
+            "WriteBatchWithIndex",
+        )?;
+        if db.column_family.is_some() {
+            return Err(PyException::new_err(
+                "WriteBatchWithIndex only supports the default column family",
+            ));
+        }
+        let db_ref = db.get_db()?;
+        let db_inner = db_ref.inner();
+        let read_opt = read_opt.unwrap_or(&db.read_opt_py);
+        read_opt.check_snapshot(db_ref, py)?;
+        let read_opt = read_opt.to_read_opt(self.raw_mode, self.normalize_str_bytes_keys, py)?;
+        let key = encode_key(key, self.raw_mode, self.normalize_str_bytes_keys)?;
+        let mut value_len: size_t = 0;
+        let value = unsafe {
+            ffi_try!(librocksdb_sys::rocksdb_writebatch_wi_get_from_batch_and_db(
+                self.inner,
+                db_inner,
+                read_opt.0,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                &mut value_len,
+            ))
+        };
+        self.take_value(value, value_len, default, py)
+    }
+
+    /// The writes staged in the batch, in the order they were made.
+    ///
+    /// Returns:
+    ///     a list of `("put", key, value)` and `("delete", key, None)` tuples.
+    fn staged_entries<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyTuple>>> {
+        let mut entries: Vec<StagedEntry> = Vec::new();
+        unsafe {
+            librocksdb_sys::rocksdb_writebatch_wi_iterate(
+                self.inner,
+                &mut entries as *mut Vec<StagedEntry> as *mut c_void,
+                Some(staged_put),
+                Some(staged_delete),
+            );
+        }
+        entries
+            .into_iter()
+            .map(|entry| match entry {
+                StagedEntry::Put(key, value) => PyTuple::new(
+                    py,
+                    [
+                        "put".into_pyobject(py)?.into_any(),
+                        decode_value(py, &key, &self.loads, self.raw_mode)?,
+                        decode_value(py, &value, &self.loads, self.raw_mode)?,
+                    ],
+                ),
+                StagedEntry::Delete(key) => PyTuple::new(
+                    py,
+                    [
+                        "delete".into_pyobject(py)?.into_any(),
+                        decode_value(py, &key, &self.loads, self.raw_mode)?,
+                        py.None().into_bound(py),
+                    ],
+                ),
+            })
+            .collect()
+    }
+
+    /// Clear all updates buffered in this batch.
+    fn clear(&mut self) {
+        unsafe { librocksdb_sys::rocksdb_writebatch_wi_clear(self.inner) }
+    }
+}
+
+impl WriteBatchWithIndexPy {
+    /// A copy of the writes of this batch, to write to a DB.
+    pub(crate) fn to_write_batch(&self) -> WriteBatch {
+        let mut size: size_t = 0;
+        unsafe {
+            let data = librocksdb_sys::rocksdb_writebatch_wi_data(self.inner, &mut size);
+            WriteBatch::from_data(slice::from_raw_parts(data as *const u8, size))
+        }
+    }
+
+    /// Decodes and frees a value returned by the C API.
+    fn take_value<'py>(
+        &self,
+        value: *mut c_char,
+        value_len: size_t,
+        default: Option<Bound<'py, PyAny>>,
+        py: Python<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        if value.is_null() {
+            return Ok(default.unwrap_or_else(|| py.None().into_bound(py)));
+        }
+        unsafe {
+            let bytes = slice::from_raw_parts(value as *const u8, value_len);
+            let result = decode_value(py, bytes, &self.loads, self.raw_mode);
+            librocksdb_sys::rocksdb_free(value as *mut c_void);
+            result
+        }
+    }
+}
+
+impl Drop for WriteBatchWithIndexPy {
+    fn drop(&mut self) {
+        unsafe {
+            librocksdb_sys::rocksdb_writebatch_wi_destroy(self.inner);
+        }
+    }
+}
@@ -75,6 +75,39 @@ def perf_put_multi_thread(rand_bytes: List[bytes], num_threads: int):
     Rdict.destroy("test.db")
 
 
+def perf_update(rand_bytes: List[bytes]):
+    rdict = Rdict("test.db", Options(raw_mode=True))
+    start = time.perf_counter()
+    rdict.update(zip(rand_bytes, rand_bytes))
+    end = time.perf_counter()
+    print(
+        "Update performance: {} items in {} seconds".format(
+            len(rand_bytes), end - start
+        )
+    )
+    rdict.close()
+    Rdict.destroy("test.db")
+
+
+def perf_write_parallel(rand_bytes: List[bytes], num_threads: int):
+    rdict = Rdict("test.db", Options(raw_mode=True))
+    start = time.perf_counter()
+    rdict.write_parallel(zip(rand_bytes, rand_bytes), threads=num_threads)
+    end = time.perf_counter()
+    print(
+        "Write parallel performance: {} items in {} seconds".format(
+            len(rand_bytes), end - start
+        )
+    )
+    count = 0
+    for k, v in rdict.items():
+        assert k == v
+        count += 1
+    assert count == len(rand_bytes), f"{count=} != {len(rand_bytes)}"
+    rdict.close()
+    Rdict.destroy("test.db")
+
+
 def perf_iterator_single_thread(rand_bytes: List[bytes]):
     rdict = Rdict("test.db", Options(raw_mode=True))
     start = time.perf_counter()
@@ -166,6 +199,8 @@ if __name__ == "__main__":
     # perf write
     perf_put_single_thread(rand_bytes)
     perf_put_multi_thread(rand_bytes, num_threads=NUM_THREADS)
+    perf_update(rand_bytes)
+    perf_write_parallel(rand_bytes, num_threads=NUM_THREADS)
 
     # Create a new Rdict instance
     rdict = Rdict("test.db", Options(raw_mode=True))
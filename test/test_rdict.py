@@ -7,13 +7,37 @@ from rocksdict import (
     SliceTransform,
     CuckooTableOptions,
     DbClosedError,
+    KeyNotFoundError,
+    RocksDBError,
     WriteBatch,
-    Checkpoint
+    WriteBatchWithIndex,
+    WriteBufferManager,
+    Checkpoint,
+    BackupEngine,
+    SstFileWriter,
+    OptimisticTransactionDB,
+    CompactOptions,
+    BottommostLevelCompaction,
+    DBCompressionType,
+    MemtableFactory,
+    DBRecoveryMode,
+    DBPath,
+    ReadOptions,
+    WriteOptions,
+    Env,
+    Cache,
+    BlockBasedOptions,
+    CompactionPri,
 )
 from random import randint, random, getrandbits
 import os
 import gc
+import shutil
 import sys
+import warnings
+from datetime import date, datetime, timedelta, timezone
+from decimal import Decimal
+from uuid import UUID, uuid4
 import platform
 from json import loads, dumps
 
@@ -55,12 +79,71 @@ class TestGetDel(unittest.TestCase):
         self.assertRaises(KeyError, lambda: self.test_dict["b"] if self.test_dict is not None else None)
         self.assertRaises(KeyError, lambda: self.test_dict[250] if self.test_dict is not None else None)
 
+    def testGetBatchDefault(self):
+        assert self.test_dict is not None
+        self.assertEqual(self.test_dict.get(["a", "b", 123]), ["a", None, 123])
+        self.assertEqual(self.test_dict.get(["b", 123, 250], 0), [0, 123, 0])
+
+    def testGetBatch(self):
+        assert self.test_dict is not None
+        self.assertEqual(self.test_dict.get_batch(["a", "b", 123]), ["a", None, 123])
+        self.assertEqual(self.test_dict.get_batch(["a", "b", 123], as_dict=True), {"a": "a", "b": None, 123: 123})
+        self.assertEqual(
+            self.test_dict.get_batch(["a", "b", 123], read_opt=ReadOptions(), as_dict=True, skip_missing=True),
+            {"a": "a", 123: 123},
+        )
+
     def testDelItem(self):
         assert self.test_dict is not None
         # no exception raise when deleting non-existing key
         self.test_dict.__delitem__("b")
         self.test_dict.__delitem__(250)
 
+    def testGetWithFlag(self):
+        assert self.test_dict is not None
+        self.test_dict["none"] = None
+        self.assertEqual(self.test_dict.get_with_flag("none"), (True, None))
+        self.assertEqual(self.test_dict.get_with_flag("missing"), (False, None))
+        del self.test_dict["none"]
+
+    def testGetPutRaw(self):
+        assert self.test_dict is not None
+        self.assertEqual(self.test_dict.get_raw(b"\x02a"), b"\x02a")
+        self.assertIsNone(self.test_dict.get_raw(b"a"))
+        self.test_dict.put_raw(b"\x02raw", b"\x03\x07")
+        self.assertEqual(self.test_dict["raw"], 7)
+        del self.test_dict["raw"]
+
+    def testUpdate(self):
+        assert self.test_dict is not None
+        self.test_dict.update({"u1": 1, "u2": [2]})
+        self.test_dict.update([("u3", 3), ("u4", 4)])
+        self.assertEqual(self.test_dict.get(["u1", "u2", "u3", "u4"]), [1, [2], 3, 4])
+        with self.assertRaises(ValueError):
+            self.test_dict.update({"u5": 5, "u6": lambda x: x})
+        self.assertNotIn("u5", self.test_dict)
+        for key in ["u1", "u2", "u3", "u4"]:
+            del self.test_dict[key]
+
+    def testUpdateValue(self):
+        assert self.test_dict is not None
+        self.test_dict["uv_list"] = [1, 2]
+        self.test_dict["uv_list"].append(3)
+        self.assertEqual(self.test_dict["uv_list"], [1, 2])
+        self.test_dict.update_value("uv_list", lambda v: v.append(3))
+        self.assertEqual(self.test_dict["uv_list"], [1, 2, 3])
+        self.assertEqual(self.test_dict.update_value("uv_count", lambda v: v + 1, default=0), 1)
+        self.assertEqual(self.test_dict.update_value("uv_count", lambda v: v + 1, default=0), 2)
+        for key in ["uv_list", "uv_count"]:
+            del self.test_dict[key]
+
+    def testSetUnpicklable(self):
+        assert self.test_dict is not None
+        with self.assertRaises(ValueError) as ctx:
+            self.test_dict["unpicklable"] = lambda x: x
+        self.assertIn("cannot serialize", str(ctx.exception))
+        self.assertNotIn("unpicklable", self.test_dict)
+
     @classmethod
     def tearDownClass(cls):
         assert cls.test_dict is not None
@@ -167,6 +250,7 @@ class TestIterBytes(unittest.TestCase):
             self.assertTrue(may_exists)
             if value is not None:
                 self.assertEqual(v, value)
+            self.assertIs(self.test_dict.key_may_exist(k), True)
 
     def test_seek_forward(self):
         assert self.ref_dict is not None
@@ -333,6 +417,16 @@ class TestInt(unittest.TestCase):
             [self.ref_dict[k] for k in keys] + [0] * 3,
         )
 
+    def test_range_numeric(self):
+        assert self.ref_dict is not None
+        assert self.test_dict is not None
+        lo = randint(0, TEST_INT_RANGE_UPPER // 2)
+        hi = lo + TEST_INT_RANGE_UPPER // 4
+        self.assertEqual(
+            [(k, v) for k, v in self.test_dict.range_numeric(lo, hi)],
+            sorted((k, v) for k, v in self.ref_dict.items() if lo <= k <= hi),
+        )
+
     @classmethod
     def tearDownClass(cls):
         assert cls.opt is not None
@@ -340,6 +434,2547 @@ class TestInt(unittest.TestCase):
         Rdict.destroy(cls.path, cls.opt)
 
 
+class TestWriteBuffering(unittest.TestCase):
+    path = "./temp_write_buffering"
+
+    def test_write_buffering(self):
+        test_dict = Rdict(self.path)
+        test_dict.enable_write_buffering(max_ops=100)
+        for i in range(50):
+            test_dict[i] = i
+        # not yet written
+        self.assertIsNone(test_dict.get(0))
+        for i in range(50, 100):
+            test_dict[i] = i
+        # auto-flushed at the threshold
+        self.assertEqual(test_dict[99], 99)
+        del test_dict[0]
+        test_dict[100] = 100
+        self.assertEqual(test_dict[0], 0)
+        test_dict.flush_writes()
+        self.assertNotIn(0, test_dict)
+        self.assertEqual(test_dict[100], 100)
+        test_dict[101] = 101
+        test_dict.close()
+
+        # buffered writes are flushed on close
+        test_dict = Rdict(self.path)
+        self.assertEqual(test_dict[101], 101)
+        test_dict.close()
+
+    def test_failed_flush_keeps_buffer(self):
+        test_dict = Rdict(self.path)
+        test_dict.enable_write_buffering(max_ops=100)
+        test_dict["kept"] = 1
+        # RocksDB rejects sync writes without WAL
+        invalid = WriteOptions()
+        invalid.sync = True
+        invalid.disable_wal(True)
+        test_dict.set_write_options(invalid)
+        with self.assertRaises(RocksDBError):
+            test_dict.flush_writes()
+        test_dict.set_write_options(WriteOptions())
+        test_dict.flush_writes()
+        self.assertEqual(test_dict["kept"], 1)
+        test_dict.close()
+
+    def test_setitem_while_another_thread_flushes(self):
+        import threading
+
+        test_dict = Rdict(self.path)
+        stop = threading.Event()
+
+        def flush_loop():
+            while not stop.is_set():
+                test_dict.flush()
+
+        flusher = threading.Thread(target=flush_loop)
+        flusher.start()
+        try:
+            for buffered in (False, True):
+                if buffered:
+                    test_dict.enable_write_buffering(max_ops=10)
+                for i in range(1000):
+                    test_dict[i] = i
+                    del test_dict[i]
+        finally:
+            stop.set()
+            flusher.join()
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+class TestBottommostCompression(unittest.TestCase):
+    path_plain = "./temp_bottommost_plain"
+    path_compressed = "./temp_bottommost_compressed"
+
+    @staticmethod
+    def write_and_compact(path: str, opt: Options) -> int:
+        test_dict = Rdict(path, opt)
+        for i in range(10000):
+            test_dict[i] = "rocksdict" * 20
+        test_dict.flush()
+        compact_opt = CompactOptions()
+        compact_opt.set_bottommost_level_compaction(BottommostLevelCompaction.force())
+        test_dict.compact_range(None, None, compact_opt)
+        size = sum(f["size"] for f in test_dict.live_files())
+        test_dict.close()
+        return size
+
+    def test_bottommost_compression(self):
+        opt_plain = Options()
+        opt_plain.set_compression_type(DBCompressionType.none())
+        opt_plain.set_bottommost_compression_type(DBCompressionType.none())
+        opt_compressed = Options()
+        opt_compressed.set_compression_type(DBCompressionType.none())
+        opt_compressed.set_bottommost_compression_type(DBCompressionType.zstd())
+        size_plain = self.write_and_compact(self.path_plain, opt_plain)
+        size_compressed = self.write_and_compact(self.path_compressed, opt_compressed)
+        self.assertLess(size_compressed, size_plain)
+
+    def test_compression_stats(self):
+        opt = Options()
+        opt.set_compression_type(DBCompressionType.none())
+        opt.set_bottommost_compression_type(DBCompressionType.zstd())
+        self.write_and_compact(self.path_compressed, opt)
+        test_dict = Rdict(self.path_compressed, opt)
+        stats = test_dict.compression_stats()
+        test_dict.close()
+        self.assertTrue(stats)
+        # everything is compacted into the compressed bottommost level
+        self.assertGreater(stats[max(stats)], 1.0)
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path_plain)
+        Rdict.destroy(cls.path_compressed)
+
+
+class TestHashMemtableFactory(unittest.TestCase):
+    path = "./temp_hash_memtable"
+
+    def test_requires_prefix_extractor(self):
+        opt = Options()
+        opt.set_allow_concurrent_memtable_write(False)
+        opt.set_memtable_factory(MemtableFactory.hash_skip_list(1000, 4, 4))
+        with self.assertRaises(Exception) as ctx:
+            Rdict(self.path, opt)
+        self.assertIn("prefix extractor", str(ctx.exception))
+
+        opt.set_prefix_extractor(SliceTransform.create_fixed_prefix(2))
+        test_dict = Rdict(self.path, opt)
+        test_dict["ab"] = "ab"
+        self.assertEqual(test_dict["ab"], "ab")
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+class TestCollapseToSingleFile(unittest.TestCase):
+    path = "./temp_collapse_to_single_file"
+
+    def test_collapse_to_single_file(self):
+        opt = Options()
+        opt.set_target_file_size_base(64 * 1024)
+        test_dict = Rdict(self.path, opt)
+        for i in range(5):
+            for j in range(10000):
+                test_dict[i * 10000 + j] = randbytes(32)
+            test_dict.flush()
+        self.assertGreater(len(test_dict.live_files()), 1)
+        test_dict.collapse_to_single_file()
+        self.assertEqual(len(test_dict.live_files()), 1)
+        self.assertEqual(len(list(test_dict.keys())), 50000)
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+def merge_add(key, existing_value, operands):
+    return (existing_value or 0) + sum(operands)
+
+
+class TestMergeOperator(unittest.TestCase):
+    test_dict = None
+    opt = None
+    path = "./temp_merge_operator"
+
+    @classmethod
+    def setUpClass(cls) -> None:
+        cls.opt = Options()
+        cls.opt.set_merge_operator_associative("add", merge_add)
+        cls.test_dict = Rdict(cls.path, cls.opt)
+
+    def test_merge(self):
+        assert self.test_dict is not None
+        for i in range(100):
+            self.test_dict.merge("counter", i)
+        self.assertEqual(self.test_dict["counter"], sum(range(100)))
+        self.test_dict["counter"] = 10
+        self.test_dict.merge("counter", 5)
+        self.assertEqual(self.test_dict["counter"], 15)
+
+    def test_write_batch_merge(self):
+        assert self.test_dict is not None
+        wb = WriteBatch()
+        for i in range(10):
+            wb.merge("batch_counter", 1)
+        self.test_dict.write(wb)
+        self.assertEqual(self.test_dict["batch_counter"], 10)
+        wb = WriteBatch()
+        wb.merge("batch_counter", 5)
+        self.test_dict.write(wb)
+        self.assertEqual(self.test_dict["batch_counter"], 15)
+
+    @classmethod
+    def tearDownClass(cls):
+        assert cls.test_dict is not None
+        cls.test_dict.close()
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+class TestValueGoesToBlob(unittest.TestCase):
+    path = "./temp_value_goes_to_blob"
+
+    def test_value_goes_to_blob(self):
+        opt = Options(raw_mode=True)
+        opt.set_enable_blob_files(True)
+        opt.set_min_blob_size(1024)
+        test_dict = Rdict(self.path, opt)
+        self.assertFalse(test_dict.value_goes_to_blob(b"a" * 1023))
+        self.assertTrue(test_dict.value_goes_to_blob(b"a" * 1024))
+        test_dict.set_options({"min_blob_size": "16"})
+        self.assertTrue(test_dict.value_goes_to_blob(b"a" * 16))
+        test_dict.set_options({"enable_blob_files": "false"})
+        self.assertFalse(test_dict.value_goes_to_blob(b"a" * 1024))
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path, Options(raw_mode=True))
+
+
+class TestWalRecoveryMode(unittest.TestCase):
+    path = "./temp_wal_recovery_mode"
+    crash_path = "./temp_wal_recovery_mode_crash"
+
+    def test_corrupted_wal_tail(self):
+        test_dict = Rdict(self.path)
+        for i in range(1000):
+            test_dict[i] = i
+        # copy the db while it is still open, as if the process crashed
+        shutil.copytree(self.path, self.crash_path)
+        test_dict.close()
+
+        wal_files = [f for f in os.listdir(self.crash_path) if f.endswith(".log")]
+        self.assertEqual(len(wal_files), 1)
+        wal_path = os.path.join(self.crash_path, wal_files[0])
+        with open(wal_path, "r+b") as f:
+            f.truncate(os.path.getsize(wal_path) - 3)
+
+        opt = Options()
+        opt.set_wal_recovery_mode(DBRecoveryMode.absolute_consistency())
+        self.assertRaises(Exception, lambda: Rdict(self.crash_path, opt))
+
+        gc.collect()
+        opt = Options()
+        opt.set_wal_recovery_mode(DBRecoveryMode.tolerate_corrupted_tail_records())
+        test_dict = Rdict(self.crash_path, opt)
+        self.assertEqual(test_dict[0], 0)
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+        Rdict.destroy(cls.crash_path)
+
+
+def reverse_cmp(a: bytes, b: bytes) -> int:
+    return (a < b) - (a > b)
+
+
+class TestCustomComparator(unittest.TestCase):
+    path = "./temp_custom_comparator"
+
+    def test_custom_comparator(self):
+        opt = Options(raw_mode=True)
+        opt.set_comparator("reverse", reverse_cmp)
+        test_dict = Rdict(self.path, opt)
+        keys = [randbytes(8) for _ in range(1000)]
+        for key in keys:
+            test_dict[key] = key
+        expected = sorted(set(keys), reverse=True)
+        self.assertEqual(list(test_dict.keys()), expected)
+        self.assertEqual(list(test_dict.keys(backwards=True)), expected[::-1])
+        test_dict.flush()
+        self.assertEqual(list(test_dict.keys(from_key=expected[10])), expected[10:])
+        test_dict.close()
+
+    def test_invalid_comparator(self):
+        opt = Options(raw_mode=True)
+        with self.assertRaises(TypeError):
+            opt.set_comparator("bad", lambda a, b: None)
+        with self.assertRaises(TypeError):
+            opt.set_comparator("raising", lambda a, b: 1 // 0)
+
+    def test_failing_comparator(self):
+        failures = []
+
+        def fail_once(a, b):
+            if b"boom" in (a, b) and not failures:
+                failures.append((a, b))
+                raise ValueError("boom")
+            return reverse_cmp(a, b)
+
+        path = self.path + "_failing"
+        opt = Options(raw_mode=True)
+        opt.set_comparator("fail_once", fail_once)
+        test_dict = Rdict(path, opt)
+        test_dict[b"a"] = b"a"
+        test_dict[b"boom"] = b"boom"
+        self.assertEqual(len(failures), 1)
+        # the exception is raised by the next call, only once
+        with self.assertRaisesRegex(ValueError, "boom"):
+            test_dict[b"b"] = b"b"
+        self.assertNotIn(b"b", test_dict)
+        test_dict[b"c"] = b"c"
+        self.assertEqual(test_dict[b"c"], b"c")
+        test_dict.close()
+        Rdict.destroy(path, opt)
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        opt = Options(raw_mode=True)
+        opt.set_comparator("reverse", reverse_cmp)
+        Rdict.destroy(cls.path, opt)
+
+
+class TestAutoTotalOrderSeek(unittest.TestCase):
+    path = "./temp_auto_total_order_seek"
+
+    def test_bounded_scan_with_prefix_extractor(self):
+        opt = Options(raw_mode=True)
+        opt.set_prefix_extractor(SliceTransform.create_fixed_prefix(1))
+        test_dict = Rdict(self.path, opt)
+        keys = [bytes([p]) + randbytes(4) for p in b"abcd" for _ in range(100)]
+        for key in keys:
+            test_dict[key] = key
+        test_dict.flush()
+
+        read_opt = ReadOptions()
+        read_opt.set_iterate_lower_bound(b"b")
+        read_opt.set_iterate_upper_bound(b"d")
+        read_opt.set_auto_total_order_seek(True)
+        self.assertEqual(
+            list(test_dict.keys(from_key=b"b", read_opt=read_opt)),
+            sorted(k for k in set(keys) if b"b" <= k < b"d"),
+        )
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path, Options(raw_mode=True))
+
+
+def drop_expired(level, key, value):
+    if value["expired"]:
+        return "remove"
+    if value["version"] < 2:
+        return "change", {"expired": False, "version": 2}
+    return "keep"
+
+
+class TestCompactionFilter(unittest.TestCase):
+    path = "./temp_compaction_filter"
+
+    def test_compaction_filter(self):
+        opt = Options()
+        opt.set_compaction_filter("drop_expired", drop_expired)
+        test_dict = Rdict(self.path, opt)
+        for i in range(100):
+            test_dict[i] = {"expired": i % 2 == 0, "version": 1}
+        # the filter only runs during compaction
+        self.assertEqual(len(list(test_dict.keys())), 100)
+        test_dict.flush()
+        test_dict.compact_range(None, None)
+        self.assertEqual(list(test_dict.keys()), list(range(1, 100, 2)))
+        for value in test_dict.values():
+            self.assertEqual(value, {"expired": False, "version": 2})
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+class KeepFirstOfGroup:
+    """keeps the first key of each group of ten within a compaction"""
+
+    created = 0
+
+    def __init__(self, is_full_compaction, is_manual_compaction):
+        KeepFirstOfGroup.created += 1
+        self.seen = set()
+
+    def __call__(self, level, key, value):
+        if key // 10 in self.seen:
+            return "remove"
+        self.seen.add(key // 10)
+        return "keep"
+
+
+class TestCompactionFilterFactory(unittest.TestCase):
+    path = "./temp_compaction_filter_factory"
+
+    def test_compaction_filter_factory(self):
+        opt = Options()
+        opt.set_compaction_filter_factory(KeepFirstOfGroup)
+        test_dict = Rdict(self.path, opt)
+        for i in range(100):
+            test_dict[i] = i
+        test_dict.flush()
+        test_dict.compact_range(None, None)
+        self.assertGreaterEqual(KeepFirstOfGroup.created, 1)
+        self.assertEqual(list(test_dict.keys()), list(range(0, 100, 10)))
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+class TestForceClose(unittest.TestCase):
+    path = "./temp_force_close"
+
+    def test_force_close(self):
+        opt = Options()
+        opt.create_if_missing(True)
+        test_dict = Rdict(self.path, opt)
+        cf = test_dict.create_column_family("cf", opt)
+        cf["key"] = "value"
+        test_dict["key"] = "value"
+        test_dict.force_close()
+        with self.assertRaises(DbClosedError):
+            cf["key"]
+        with self.assertRaises(DbClosedError):
+            test_dict["key"]
+        del cf
+        gc.collect()
+        test_dict = Rdict(self.path, column_families={"cf": opt})
+        self.assertEqual(test_dict["key"], "value")
+        self.assertEqual(test_dict.get_column_family("cf")["key"], "value")
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+class TestSharedEnv(unittest.TestCase):
+    paths = ["./temp_shared_env_0", "./temp_shared_env_1"]
+
+    def test_shared_env(self):
+        env = Env()
+        env.set_low_priority_background_threads(1)
+        env.set_high_priority_background_threads(1)
+        opt = Options()
+        opt.set_env(env)
+        dbs = [Rdict(path, opt) for path in self.paths]
+        for i, db in enumerate(dbs):
+            for j in range(100):
+                db[j] = i
+            db.flush()
+            db.compact_range(None, None)
+        for i, db in enumerate(dbs):
+            self.assertEqual(list(db.values()), [i] * 100)
+            db.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        for path in cls.paths:
+            Rdict.destroy(path)
+
+
+class TestTupleKeys(unittest.TestCase):
+    path = "./temp_tuple_keys"
+
+    def test_round_trip(self):
+        test_dict = Rdict(self.path)
+        keys = [
+            (),
+            ("a",),
+            ("a", "b"),
+            ("ab",),
+            ("a\x00", "b"),
+            ("a", "\x00b"),
+            (b"\x00\x00", b"\xff"),
+            (1, -1, 2**100),
+            (1.5, True, "c"),
+            ((1, "x"), (b"\x00",), ()),
+        ]
+        for i, key in enumerate(keys):
+            test_dict[key] = i
+        for i, key in enumerate(keys):
+            self.assertEqual(test_dict[key], i)
+        self.assertEqual(len(list(test_dict.keys())), len(keys))
+        self.assertEqual(set(test_dict.keys()), set(keys))
+        test_dict.close()
+        Rdict.destroy(self.path)
+
+    def test_ordering(self):
+        test_dict = Rdict(self.path)
+        keys = [
+            (a, b)
+            for a in [-300, -1, 0, 1, 255, 256, 2**70]
+            for b in ["", "\x00", "a", "a\x00", "ab", "b"]
+        ]
+        keys += [(a,) for a in [-300, 0, 256]]
+        keys += [((a, "x"), b) for a in [1, 10, 100] for b in [2, 1]]
+        for key in keys:
+            test_dict[key] = key
+        tuples = [k for k in keys if not isinstance(k[0], tuple)]
+        nested = [k for k in keys if isinstance(k[0], tuple)]
+        self.assertEqual(list(test_dict.keys()), sorted(tuples) + sorted(nested))
+        # tuples sharing leading components form a contiguous range
+        self.assertEqual(
+            list(test_dict.keys(from_key=(256,)))[:7],
+            [(256,)] + [(256, b) for b in ["", "\x00", "a", "a\x00", "ab", "b"]],
+        )
+        test_dict.close()
+        Rdict.destroy(self.path)
+
+    def test_invalid_component(self):
+        test_dict = Rdict(self.path)
+        with self.assertRaises(Exception):
+            test_dict[("a", [1])] = 1
+        test_dict.close()
+        Rdict.destroy(self.path)
+
+
+class TestOpenAllColumnFamilies(unittest.TestCase):
+    path = "./temp_open_all_cfs"
+
+    def test_open_all_cfs(self):
+        test_dict = Rdict(self.path)
+        prefix_opt = Options()
+        prefix_opt.set_prefix_extractor(SliceTransform.create_fixed_prefix(2))
+        test_dict.create_column_family("cf_1", Options())["key"] = 1
+        test_dict.create_column_family("cf_2", prefix_opt)["key"] = 2
+        test_dict["key"] = 0
+        test_dict.close()
+        gc.collect()
+
+        dbs = Rdict.open_all_cfs(self.path)
+        self.assertEqual(set(dbs), {"default", "cf_1", "cf_2"})
+        self.assertEqual(dbs["default"]["key"], 0)
+        self.assertEqual(dbs["cf_1"]["key"], 1)
+        self.assertEqual(dbs["cf_2"]["key"], 2)
+        for db in dbs.values():
+            db.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+class TestDropBehavior(unittest.TestCase):
+    path = "./temp_drop_behavior"
+
+    def test_drop_behavior(self):
+        for behavior in ["flush", "flush_wal", "none"]:
+            test_dict = Rdict(self.path)
+            test_dict.set_drop_behavior(behavior)
+            test_dict[behavior] = behavior
+            test_dict.close()
+            # writes are recovered from the WAL
+            test_dict = Rdict(self.path)
+            self.assertEqual(test_dict[behavior], behavior)
+            test_dict.close()
+
+    def test_unknown_drop_behavior(self):
+        test_dict = Rdict(self.path)
+        with self.assertRaises(ValueError):
+            test_dict.set_drop_behavior("sync")
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+class TestLen(unittest.TestCase):
+    path = "./temp_len"
+
+    def test_len(self):
+        test_dict = Rdict(self.path)
+        self.assertEqual(test_dict.count_exact(), 0)
+        self.assertFalse(test_dict)
+        for i in range(1000):
+            test_dict[i] = i
+        self.assertTrue(test_dict)
+        del test_dict[0]
+        self.assertEqual(test_dict.count_exact(), 999)
+        test_dict.flush()
+        test_dict.compact_range(None, None)
+        self.assertEqual(len(test_dict), 999)
+        props = test_dict.properties(["rocksdb.estimate-num-keys", "rocksdb.num-files-at-level0", "rocksdb.no-such-property"])
+        self.assertEqual(props["rocksdb.estimate-num-keys"], "999")
+        self.assertEqual(props["rocksdb.num-files-at-level0"], test_dict.property_value("rocksdb.num-files-at-level0"))
+        self.assertIsNone(props["rocksdb.no-such-property"])
+        # the estimate may lag behind deletions, but not the truth value
+        test_dict.delete_range(1, 1000)
+        self.assertFalse(test_dict)
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+class TestBulkLoad(unittest.TestCase):
+    path = "./temp_bulk_load"
+    tmp_dir = "./temp_bulk_load_sst"
+
+    def test_bulk_load(self):
+        test_dict = Rdict(self.path)
+        test_dict.bulk_load(((i, str(i)) for i in range(-5000, 5000)), self.tmp_dir, 64 * 1024)
+        self.assertEqual(list(test_dict.keys()), list(range(-5000, 5000)))
+        self.assertEqual(test_dict[42], "42")
+        self.assertTrue(test_dict.live_files())
+        self.assertEqual(os.listdir(self.tmp_dir), [])
+        test_dict.close()
+
+    def test_bulk_load_unsorted(self):
+        test_dict = Rdict(self.path)
+        with self.assertRaises(ValueError):
+            test_dict.bulk_load([(b"b", 1), (b"a", 2)], self.tmp_dir)
+        self.assertNotIn(b"b", test_dict)
+        self.assertEqual(os.listdir(self.tmp_dir), [])
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+        shutil.rmtree(cls.tmp_dir, ignore_errors=True)
+
+
+class TestNormalizeStrBytesKeys(unittest.TestCase):
+    path = "./temp_normalize_str_bytes_keys"
+
+    def test_normalize_str_bytes_keys(self):
+        opt = Options()
+        opt.set_normalize_str_bytes_keys(True)
+        test_dict = Rdict(self.path, opt)
+        test_dict["abc"] = 1
+        self.assertEqual(test_dict[b"abc"], 1)
+        test_dict[b"abc"] = 2
+        self.assertEqual(test_dict["abc"], 2)
+        test_dict[("x", b"y")] = 3
+        self.assertEqual(test_dict[(b"x", "y")], 3)
+        wb = WriteBatch(normalize_str_bytes_keys=True)
+        wb["def"] = 4
+        test_dict.write(wb)
+        with self.assertRaises(Exception):
+            test_dict.write(WriteBatch())
+        test_dict.close()
+
+        # the setting is restored when reopened
+        test_dict = Rdict(self.path)
+        self.assertEqual(test_dict[b"def"], 4)
+        self.assertEqual(list(test_dict.keys()), [b"abc", b"def", (b"x", b"y")])
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+class TestWalSize(unittest.TestCase):
+    path = "./temp_wal_size"
+
+    def test_wal_size_and_rotation(self):
+        test_dict = Rdict(self.path)
+        cf = test_dict.create_column_family("cf")
+        for i in range(1000):
+            test_dict[i] = randbytes(100)
+            cf[i] = randbytes(100)
+        test_dict.flush_wal()
+        self.assertGreater(test_dict.wal_size(), 200 * 1000)
+        test_dict.rotate_wal()
+        self.assertLess(test_dict.wal_size(), 1000)
+        self.assertEqual(len(cf[999]), 100)
+        cf.close()
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+class TestPrefixExtractorMismatch(unittest.TestCase):
+    path = "./temp_prefix_extractor_mismatch"
+
+    def test_warn_on_different_prefix_extractor(self):
+        opt = Options()
+        opt.set_prefix_extractor(SliceTransform.create_fixed_prefix(2))
+        Rdict(self.path, opt).close()
+        # same prefix extractor: no warning
+        with warnings.catch_warnings():
+            warnings.simplefilter("error")
+            Rdict(self.path, opt).close()
+        other_opt = Options()
+        other_opt.set_prefix_extractor(SliceTransform.create_fixed_prefix(3))
+        with self.assertWarnsRegex(UserWarning, "fixed_prefix\\(2\\)"):
+            Rdict(self.path, other_opt).close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+class TestIntKeyOrder(unittest.TestCase):
+    path = "./temp_int_key_order"
+
+    def test_int_keys_sort_numerically(self):
+        keys = [
+            0, 1, 2, 10, 255, 256, -1, -2, -10, -256,
+            2 ** 63 - 1, 2 ** 63, 2 ** 64, -(2 ** 63), -(2 ** 63) - 1,
+            10 ** 30, -(10 ** 30),
+        ]
+        test_dict = Rdict(self.path, Options())
+        for k in keys:
+            test_dict[k] = k
+        test_dict.flush()
+        self.assertEqual(list(test_dict.keys()), sorted(keys))
+        self.assertEqual(list(test_dict.keys(backwards=True)), sorted(keys, reverse=True))
+        self.assertEqual(list(test_dict.keys(from_key=-2)), [k for k in sorted(keys) if k >= -2])
+        test_dict.close()
+        # the ordering is kept after reopening
+        test_dict = Rdict(self.path)
+        self.assertEqual(list(test_dict.keys()), sorted(keys))
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+class TestEncodingVersion(unittest.TestCase):
+    path = "./temp_encoding_version"
+
+    def _rewrite_config(self, update):
+        config_path = os.path.join(self.path, "rocksdict-config.json")
+        with open(config_path) as f:
+            config = loads(f.read())
+        update(config)
+        with open(config_path, "w") as f:
+            f.write(dumps(config))
+
+    def test_encoding_version(self):
+        test_dict = Rdict(self.path)
+        test_dict[1] = 1
+        test_dict.close()
+        with open(os.path.join(self.path, "rocksdict-config.json")) as f:
+            self.assertEqual(loads(f.read())["encoding_version"], 2)
+        # configs written before the version existed
+        self._rewrite_config(lambda c: c.pop("encoding_version"))
+        test_dict = Rdict(self.path)
+        self.assertEqual(test_dict[1], 1)
+        test_dict.close()
+        # older encodings are readable, and upgraded on open
+        self._rewrite_config(lambda c: c.update(encoding_version=1))
+        test_dict = Rdict(self.path)
+        self.assertEqual(test_dict[1], 1)
+        test_dict.close()
+        with open(os.path.join(self.path, "rocksdict-config.json")) as f:
+            self.assertEqual(loads(f.read())["encoding_version"], 2)
+        # databases written by a newer encoding are rejected
+        self._rewrite_config(lambda c: c.update(encoding_version=3))
+        with self.assertRaisesRegex(Exception, "encoding version 3"):
+            Rdict(self.path)
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+def json_dumps(obj):
+    return dumps(obj).encode("utf-8")
+
+
+def json_loads(data):
+    return loads(data)
+
+
+class TestSerializer(unittest.TestCase):
+    path = "./temp_serializer"
+
+    def test_serializer(self):
+        opt = Options()
+        opt.set_serializer(json_dumps, json_loads)
+        test_dict = Rdict(self.path, opt)
+        test_dict["list"] = [1, "a", None]
+        test_dict["dict"] = {"a": 1}
+        test_dict.close()
+        # the serializer is restored when reopening without options
+        test_dict = Rdict(self.path)
+        self.assertEqual(test_dict["list"], [1, "a", None])
+        self.assertEqual(test_dict["dict"], {"a": 1})
+        test_dict.close()
+        # opening with another serializer is an error
+        with self.assertRaisesRegex(Exception, "serializer"):
+            Rdict(self.path, Options())
+
+    def test_lambda_rejected(self):
+        with self.assertRaises(ValueError):
+            Options().set_serializer(lambda x: x, json_loads)
+
+    def test_batches_and_merge_operators(self):
+        def append(key, existing_value, operands):
+            return (existing_value or []) + operands
+
+        opt = Options()
+        opt.set_serializer(json_dumps, json_loads)
+        opt.set_merge_operator_associative("append", append)
+        # merge operators capture the serializer when set
+        with self.assertRaisesRegex(Exception, "set_serializer"):
+            opt.set_serializer(json_dumps, json_loads)
+        test_dict = Rdict(self.path, opt)
+        test_dict.merge("merged", ["a"])
+        test_dict.merge("merged", ["b"])
+        self.assertEqual(test_dict["merged"], [["a"], ["b"]])
+        # batches must use the serializer of the database
+        for wb in (WriteBatch(), WriteBatchWithIndex()):
+            wb["batch"] = [1]
+            with self.assertRaisesRegex(Exception, "serializer"):
+                test_dict.write(wb)
+        for wb in (WriteBatch(), WriteBatchWithIndex()):
+            wb.set_dumps(json_dumps)
+            wb.set_loads(json_loads)
+            wb["batch"] = [1]
+            test_dict.write(wb)
+            self.assertEqual(test_dict["batch"], [1])
+        test_dict.close()
+        Rdict.destroy(self.path)
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+class TestDateTime(unittest.TestCase):
+    path = "./temp_datetime"
+
+    def test_round_trip(self):
+        test_dict = Rdict(self.path)
+        values = [
+            datetime(2024, 1, 1, 12, 30, 15, 123456, tzinfo=timezone.utc),
+            datetime(1900, 1, 1, tzinfo=timezone(timedelta(hours=8))),
+            datetime(2024, 1, 1, 12, 30),
+            date(2024, 2, 29),
+            date(1, 1, 1),
+            timedelta(days=-3, seconds=5, microseconds=7),
+            timedelta(0),
+        ]
+        for i, v in enumerate(values):
+            test_dict[i] = v
+            if not (isinstance(v, datetime) and v.tzinfo is None):
+                test_dict[v] = i
+        for i, v in enumerate(values):
+            self.assertEqual(test_dict[i], v)
+            self.assertEqual(type(test_dict[i]), type(v))
+        self.assertEqual(test_dict[values[0]], 0)
+        # naive datetimes cannot be keys
+        with self.assertRaises(Exception):
+            test_dict[datetime(2024, 1, 1)] = 1
+        test_dict.close()
+        Rdict.destroy(self.path)
+
+    def test_ordering(self):
+        try:
+            from zoneinfo import ZoneInfo
+            new_york = ZoneInfo("America/New_York")
+        except Exception:
+            self.skipTest("time zone database not available")
+        test_dict = Rdict(self.path)
+        # DST ends at 2023-11-05 02:00 local time: 01:30 happens twice
+        start = datetime(2023, 11, 5, 0, 0, tzinfo=timezone.utc)
+        instants = [start + timedelta(minutes=30 * i) for i in range(-5, 20)]
+        local_times = [t.astimezone(new_york) for t in instants]
+        for t in reversed(local_times):
+            test_dict[t] = t
+        self.assertEqual(list(test_dict.keys()), instants)
+        # read back in UTC (ambiguous local times never compare equal across zones)
+        self.assertEqual(list(test_dict.values()), instants)
+        deltas = [timedelta(days=d, seconds=s) for d in [-2, -1, 0, 1] for s in [0, 1, 86399]]
+        for d in deltas:
+            test_dict[d] = d
+        self.assertEqual([k for k in test_dict.keys() if isinstance(k, timedelta)], sorted(deltas))
+        test_dict.close()
+        Rdict.destroy(self.path)
+
+
+class TestUuidDecimal(unittest.TestCase):
+    path = "./temp_uuid_decimal"
+
+    def test_uuid(self):
+        test_dict = Rdict(self.path)
+        uuids = [uuid4() for _ in range(100)]
+        for u in uuids:
+            test_dict[u] = u
+        self.assertEqual(list(test_dict.keys()), sorted(uuids))
+        self.assertEqual(list(test_dict.values()), sorted(uuids))
+        test_dict["u"] = uuids[0]
+        self.assertEqual(test_dict.get_raw(b"\x02u"), b"\x0b" + uuids[0].bytes)
+        self.assertEqual(len(test_dict.get_raw(b"\x02u")), 17)
+        test_dict.close()
+        Rdict.destroy(self.path)
+
+    def test_decimal(self):
+        test_dict = Rdict(self.path)
+        values = [Decimal("1.10"), Decimal("-0"), Decimal("1E+3"), Decimal("NaN"), Decimal("3.14159265358979323846")]
+        for i, v in enumerate(values):
+            test_dict[i] = v
+        for i, v in enumerate(values):
+            self.assertEqual(type(test_dict[i]), Decimal)
+            self.assertEqual(str(test_dict[i]), str(v))
+        test_dict.close()
+        Rdict.destroy(self.path)
+
+    def test_decimal_keys(self):
+        test_dict = Rdict(self.path)
+        keys = [
+            Decimal("-Infinity"),
+            Decimal("-1E+10"),
+            Decimal("-12.5"),
+            Decimal("-12.25"),
+            Decimal("-0.001"),
+            Decimal("0"),
+            Decimal("0.001"),
+            Decimal("0.0010001"),
+            Decimal("1.1"),
+            Decimal("12.25"),
+            Decimal("12.5"),
+            Decimal("1E+10"),
+            Decimal("Infinity"),
+        ]
+        for i, k in enumerate(reversed(keys)):
+            test_dict[k] = i
+        self.assertEqual(list(test_dict.keys()), keys)
+        for k in test_dict.keys():
+            self.assertEqual(type(k), Decimal)
+        # equal numbers are the same key
+        test_dict[Decimal("1.10")] = "a"
+        self.assertEqual(test_dict[Decimal("1.1000")], "a")
+        self.assertEqual(test_dict[Decimal("-0.00")], test_dict[Decimal("0")])
+        self.assertEqual(len(list(test_dict.keys())), len(keys))
+        with self.assertRaises(ValueError):
+            test_dict[Decimal("NaN")] = 1
+        test_dict.close()
+        Rdict.destroy(self.path)
+
+
+class TestDeleteRangeColumnFamilies(unittest.TestCase):
+    path = "./temp_delete_range_cfs"
+
+    def test_same_semantics(self):
+        test_dict = Rdict(self.path)
+        cf = test_dict.create_column_family("cf")
+        for target in [test_dict, cf]:
+            for i in range(-500, 500):
+                target[i] = i
+            target["str"] = "str"
+            target.delete_range(-100, 200)
+        # str keys (type byte 2) sort before int keys (type byte 3)
+        expected = ["str"] + [i for i in range(-500, 500) if not -100 <= i < 200]
+        self.assertEqual(list(test_dict.keys()), expected)
+        self.assertEqual(list(cf.keys()), expected)
+        del cf
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
+class TestIterPrefix(unittest.TestCase):
+    path = "./temp_iter_prefix"
+
+    def _fill(self, test_dict):
+        for k in ["us", "user:1", "user:2", "user:10", "users", "video:1"]:
+            test_dict[k] = k
+        for i in range(3):
+            test_dict[("user", i)] = i
+            test_dict[("video", i)] = i
+
+    def _check(self, test_dict):
+        self.assertEqual([k for k, _ in test_dict.iter_prefix("user:")], ["user:1", "user:10", "user:2"])
+        self.assertEqual([k for k, _ in test_dict.iter_prefix("user")], ["user:1", "user:10", "user:2", "users"])
+        self.assertEqual(list(test_dict.iter_prefix("none")), [])
+        self.assertEqual([v for _, v in test_dict.iter_prefix(("user",))], [0, 1, 2])
+        with self.assertRaises(ValueError):
+            test_dict.iter_prefix(1)
+
+    def test_total_order(self):
+        test_dict = Rdict(self.path)
+        self._fill(test_dict)
+        self._check(test_dict)
+        test_dict.close()
+        Rdict.destroy(self.path)
+
+    def test_prefix_extractor(self):
+        opt = Options()
+        opt.set_prefix_extractor(SliceTransform.create_fixed_prefix(5))
+        test_dict = Rdict(self.path, opt)
+        self._fill(test_dict)
+        test_dict.flush()
+        # "user:" is encoded as 6 bytes, covering the 5-byte prefixes
+        self._check(test_dict)
+        test_dict.close()
+        Rdict.destroy(self.path)
+
+    def test_raw_mode_bounds(self):
+        opt = Options(raw_mode=True)
+        test_dict = Rdict(self.path, opt)
+        keys = [b"a", b"ab", b"ab\x00", b"ab\xff", b"ab\xff\xff", b"ac",
+                b"\xff", b"\xff\xff", b"\xff\xff\x01"]
+        for k in keys:
+            test_dict[k] = k
+        self.assertEqual([k for k, _ in test_dict.iter_prefix(b"ab")],
+                         [b"ab", b"ab\x00", b"ab\xff", b"ab\xff\xff"])
+        # the successor of b"ab\xff" is b"ac"
+        self.assertEqual([k for k, _ in test_dict.iter_prefix(b"ab\xff")],
+                         [b"ab\xff", b"ab\xff\xff"])
+        # no upper bound for all 0xff prefixes
+        self.assertEqual([k for k, _ in test_dict.iter_prefix(b"\xff\xff")],
+                         [b"\xff\xff", b"\xff\xff\x01"])
+        self.assertEqual([k for k, _ in test_dict.iter_prefix(b"")], keys)
+        test_dict.close()
+        Rdict.destroy(self.path, opt)
+
+    def test_raw_mode_non_bytewise_comparators(self):
+        # the prefix successor is not an upper bound in reverse order
+        for set_comparator in (
+            lambda opt: opt.set_builtin_comparator("reverse_bytewise"),
+            lambda opt: opt.set_comparator("reverse", lambda a, b: (a < b) - (a > b)),
+        ):
+            opt = Options(raw_mode=True)
+            set_comparator(opt)
+            test_dict = Rdict(self.path, opt)
+            for k in [b"aa", b"ab", b"b"]:
+                test_dict[k] = k
+            self.assertEqual([k for k, _ in test_dict.iter_prefix(b"ab")], [b"ab"])
+            test_dict.close()
+            Rdict.destroy(self.path, opt)
+
+
+class TestBackupEngine(unittest.TestCase):
+    path = "./temp_backup_db"
+    backup_path = "./temp_backup_dir"
+
+    def test_backup_restore(self):
+        test_dict = Rdict(self.path)
+        for i in range(1000):
+            test_dict[i] = str(i)
+        engine = BackupEngine(self.backup_path)
+        engine.create_new_backup(test_dict)
+        test_dict["after"] = "first backup"
+        engine.create_new_backup(test_dict)
+        self.assertEqual([b["backup_id"] for b in engine.get_backup_info()], [1, 2])
+        engine.purge_old_backups(1)
+        self.assertEqual([b["backup_id"] for b in engine.get_backup_info()], [2])
+        test_dict.close()
+        Rdict.destroy(self.path)
+
+        engine.restore_from_latest_backup(self.path)
+        test_dict = Rdict(self.path)
+        self.assertEqual(test_dict["after"], "first backup")
+        self.assertEqual([test_dict[i] for i in range(1000)], [str(i) for i in range(1000)])
+        test_dict.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+        shutil.rmtree(cls.backup_path, ignore_errors=True)
+
+
+class TestSstFileWriter(unittest.TestCase):
+    path = "./temp_sst_file_writer"
+    sst_path = "./temp_sst_file_writer.sst"
+
+    def test_write_and_ingest(self):
+        for raw_mode in (False, True):
+            opt = Options(raw_mode=raw_mode)
+            writer = SstFileWriter(opt)
+            writer.open(self.sst_path)
+            for i in range(10):
+                key = f"{i:02}".encode() if raw_mode else i
+                writer.put(key, key)
+            writer.delete(b"10" if raw_mode else 10)
+            writer.finish()
+            self.assertGreater(writer.file_size(), 0)
+
+            db = Rdict(self.path, opt)
+            db[b"10" if raw_mode else 10] = "deleted"
+            db.ingest_external_file([self.sst_path])
+            if raw_mode:
+                self.assertEqual(db[b"03"], b"03")
+                self.assertNotIn(b"10", db)
+            else:
+                self.assertEqual(list(db.items()), [(i, i) for i in range(10)])
+            db.close()
+            Rdict.destroy(self.path, opt)
+            os.remove(self.sst_path)
+
+    def test_keys_out_of_order(self):
+        writer = SstFileWriter()
+        writer.open(self.sst_path)
+        writer[2] = "b"
+        with self.assertRaises(RocksDBError) as ctx:
+            writer[1] = "a"
+        self.assertIn("same order", str(ctx.exception))
+        del writer
+        os.remove(self.sst_path)
+
+
+class TestOptimisticTransactionDB(unittest.TestCase):
+    path = "./temp_optimistic_transaction_db"
+
+    def setUp(self) -> None:
+        self.db = OptimisticTransactionDB(self.path)
+        self.db["a"] = 1
+        self.db["b"] = 2
+
+    def tearDown(self) -> None:
+        self.db.close()
+        gc.collect()
+        Rdict.destroy(self.path)
+
+    def test_commit_and_rollback(self):
+        txn = self.db.transaction()
+        txn["a"] = 10
+        del txn["b"]
+        self.assertEqual(txn["a"], 10)
+        self.assertIsNone(txn.get("b"))
+        # not visible outside of the transaction before commit
+        self.assertEqual(self.db["a"], 1)
+        txn.commit()
+        self.assertEqual(self.db["a"], 10)
+        self.assertIsNone(self.db.get("b"))
+        self.assertRaises(Exception, txn.commit)
+
+        txn = self.db.transaction()
+        txn["a"] = 100
+        txn.rollback()
+        self.assertEqual(self.db["a"], 10)
+
+    def test_conflict(self):
+        txn1 = self.db.transaction()
+        txn2 = self.db.transaction()
+        txn1["a"] = txn1.get_for_update("a") + 1
+        txn2["a"] = txn2.get_for_update("a") + 1
+        txn1.commit()
+        with self.assertRaises(Exception) as ctx:
+            txn2.commit()
+        self.assertIn("Resource busy", str(ctx.exception))
+        self.assertEqual(self.db["a"], 2)
+
+        # retry after the conflict
+        txn2 = self.db.transaction()
+        txn2["a"] = txn2.get_for_update("a") + 1
+        txn2.commit()
+        self.assertEqual(self.db["a"], 3)
+
+    def test_savepoint(self):
+        txn = self.db.transaction(set_snapshot=True)
+        txn["a"] = 10
+        txn.set_savepoint()
+        txn["b"] = 20
+        txn.rollback_to_savepoint()
+        txn.commit()
+        self.assertEqual(self.db["a"], 10)
+        self.assertEqual(self.db["b"], 2)
+
+    def test_reopen_with_rdict(self):
+        self.db.close()
+        gc.collect()
+        db = Rdict(self.path)
+        self.assertEqual(db["a"], 1)
+        db.close()
+        self.db = OptimisticTransactionDB(self.path)
+
+
+class TestGetPinned(unittest.TestCase):
+    path = "./temp_get_pinned"
+
+    def test_get_pinned(self):
+        opt = Options(raw_mode=True)
+        db = Rdict(self.path, opt)
+        value = b"0123456789" * 1000
+        db[b"key"] = value
+        pinned = db.get_pinned(b"key")
+        self.assertEqual(len(pinned), len(value))
+        with memoryview(pinned) as view:
+            self.assertTrue(view.readonly)
+            self.assertEqual(view[:10], b"0123456789")
+            self.assertEqual(view.tobytes(), value)
+        self.assertEqual(bytes(pinned), value)
+        self.assertIsNone(db.get_pinned(b"missing"))
+        del pinned
+        db.close()
+        Rdict.destroy(self.path, opt)
+
+    def test_not_raw_mode(self):
+        db = Rdict(self.path)
+        db["key"] = "value"
+        self.assertRaises(ValueError, db.get_pinned, "key")
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestWriteBatchWithIndex(unittest.TestCase):
+    path = "./temp_write_batch_with_index"
+
+    def setUp(self) -> None:
+        self.db = Rdict(self.path)
+        self.db["a"] = 1
+        self.db["b"] = 2
+
+    def tearDown(self) -> None:
+        self.db.close()
+        Rdict.destroy(self.path)
+
+    def test_read_your_own_writes(self):
+        wb = WriteBatchWithIndex()
+        wb["b"] = 20
+        wb["c"] = 30
+        wb["c"] = 300
+        del wb["a"]
+        self.assertEqual(len(wb), 4)
+        self.assertEqual(wb.get_from_batch("c"), 300)
+        self.assertIsNone(wb.get_from_batch("a"))
+        self.assertEqual(wb.get_from_batch("missing", "default"), "default")
+        self.assertIsNone(wb.get_from_batch_and_db(self.db, "a"))
+        self.assertEqual(wb.get_from_batch_and_db(self.db, "b"), 20)
+        self.assertEqual(wb.get_from_batch_and_db(self.db, "c"), 300)
+        self.assertEqual(
+            wb.staged_entries(),
+            [("put", "b", 20), ("put", "c", 30), ("put", "c", 300), ("delete", "a", None)],
+        )
+        # nothing is written to the db before `write`
+        self.assertEqual(self.db["a"], 1)
+        self.db.write(wb)
+        self.assertEqual(list(self.db.items()), [("b", 20), ("c", 300)])
+        wb.clear()
+        self.assertEqual(len(wb), 0)
+
+    def test_raw_mode_mismatch(self):
+        wb = WriteBatchWithIndex(raw_mode=True)
+        wb[b"a"] = b"1"
+        self.assertRaises(Exception, wb.get_from_batch_and_db, self.db, b"a")
+        self.assertRaises(Exception, self.db.write, wb)
+        self.assertRaises(TypeError, self.db.write, {"a": 1})
+
+    def test_column_family_rejected(self):
+        cf = self.db.create_column_family("cf")
+        wb = WriteBatchWithIndex()
+        wb["c"] = 3
+        self.assertRaises(Exception, cf.write, wb)
+        self.assertNotIn("c", cf)
+        self.assertNotIn("c", self.db)
+        self.db.write(wb)
+        self.assertEqual(self.db["c"], 3)
+        del cf
+
+
+class TestSchema(unittest.TestCase):
+    path = "./temp_schema"
+
+    def test_schema(self):
+        db = Rdict(self.path)
+        db.set_schema(key_type=int, value_type=(str, bytes))
+        db[1] = "a"
+        db.put(2, b"b")
+        self.assertEqual(db[1], "a")
+        with self.assertRaises(TypeError) as ctx:
+            db["oops"] = "a"
+        self.assertIn("expected key of type int, got str", str(ctx.exception))
+        with self.assertRaises(TypeError) as ctx:
+            db[3] = 3
+        self.assertIn("got int", str(ctx.exception))
+        self.assertRaises(TypeError, db.__getitem__, "oops")
+        self.assertRaises(TypeError, db.__delitem__, "oops")
+        self.assertRaises(TypeError, db.get_batch, [1, "oops"])
+        self.assertRaises(TypeError, db.get, [1, "oops"])
+        self.assertRaises(TypeError, db.merge, "oops", "a")
+        self.assertRaises(TypeError, db.merge, 1, 1)
+        self.assertRaises(TypeError, db.put_entity, "oops", ["a"], ["a"])
+        self.assertRaises(TypeError, db.put_entity, 4, ["a"], [1])
+        self.assertRaises(TypeError, db.update, {"oops": "a"})
+        self.assertRaises(TypeError, db.update, {5: 5})
+        self.assertRaises(TypeError, db.delete_range, "a", "z")
+        self.assertRaises(TypeError, db.key_may_exist, "oops")
+        self.assertNotIn(3, db)
+        self.assertNotIn(4, db)
+        self.assertNotIn(5, db)
+
+        # the schema can be removed
+        db.set_schema()
+        db["any"] = 3
+        self.assertEqual(db["any"], 3)
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestIterReleasesGil(unittest.TestCase):
+    path = "./temp_iter_releases_gil"
+
+    def test_concurrent_scans(self):
+        import threading
+
+        db = Rdict(self.path)
+        for i in range(10000):
+            db[i] = i
+        results = []
+
+        def scan():
+            results.append(sum(v for v in db.values()))
+
+        threads = [threading.Thread(target=scan) for _ in range(4)]
+        for t in threads:
+            t.start()
+        for t in threads:
+            t.join()
+        self.assertEqual(results, [sum(range(10000))] * 4)
+
+        it = db.iter()
+        it.seek(5000)
+        self.assertEqual(it.key(), 5000)
+        it.prev()
+        self.assertEqual(it.key(), 4999)
+        del it
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestSeekReturnsValid(unittest.TestCase):
+    path = "./temp_seek_returns_valid"
+
+    def test_seek_returns_valid(self):
+        db = Rdict(self.path)
+        it = db.iter()
+        # empty db
+        self.assertFalse(it.seek_to_first())
+        self.assertFalse(it.seek_to_last())
+        self.assertFalse(it.seek(1))
+        del it
+
+        for i in range(0, 10, 2):
+            db[i] = i
+        it = db.iter()
+        # exact hit
+        self.assertTrue(it.seek(4))
+        self.assertEqual(it.key(), 4)
+        # between keys
+        self.assertTrue(it.seek(5))
+        self.assertEqual(it.key(), 6)
+        self.assertTrue(it.seek_for_prev(5))
+        self.assertEqual(it.key(), 4)
+        # past the last key
+        self.assertFalse(it.seek(9))
+        self.assertFalse(it.seek_for_prev(-1))
+
+        self.assertTrue(it.seek_to_last())
+        self.assertFalse(it.next())
+        self.assertTrue(it.seek_to_first())
+        keys = [it.key()]
+        while it.next():
+            keys.append(it.key())
+        self.assertEqual(keys, [0, 2, 4, 6, 8])
+        del it
+        self.assertEqual(list(db.keys(backwards=True)), [8, 6, 4, 2, 0])
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestIterCorruption(unittest.TestCase):
+    path = "./temp_iter_corruption"
+
+    def test_iteration_raises_on_corruption(self):
+        opt = Options(raw_mode=True)
+        opt.set_compression_type(DBCompressionType.none())
+        db = Rdict(self.path, opt)
+        for i in range(1000):
+            db[f"{i:04}".encode()] = b"v" * 100
+        db.flush()
+        db.close()
+
+        sst_files = [f for f in os.listdir(self.path) if f.endswith(".sst")]
+        self.assertEqual(len(sst_files), 1)
+        # flip bytes of the first data block, at the start of the file
+        with open(os.path.join(self.path, sst_files[0]), "r+b") as f:
+            f.seek(100)
+            data = f.read(100)
+            f.seek(100)
+            f.write(bytes(b ^ 0xFF for b in data))
+
+        db = Rdict(self.path, opt)
+        with self.assertRaises(Exception) as ctx:
+            list(db.keys())
+        self.assertIn("Corruption", str(ctx.exception))
+        db.close()
+        Rdict.destroy(self.path, opt)
+
+
+class TestToKey(unittest.TestCase):
+    path = "./temp_to_key"
+
+    def test_to_key(self):
+        db = Rdict(self.path)
+        for i in range(10):
+            db[i] = i * i
+        self.assertEqual(list(db.keys(from_key=2, to_key=5)), [2, 3, 4])
+        self.assertEqual(list(db.values(to_key=3)), [0, 1, 4])
+        self.assertEqual(
+            list(db.items(backwards=True, from_key=7, to_key=5)),
+            [(7, 49), (6, 36), (5, 25)],
+        )
+        self.assertEqual(list(db.keys(backwards=True, to_key=8)), [9, 8])
+        self.assertEqual(list(db.keys(to_key=-1)), [])
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestColumnFamiliesListing(unittest.TestCase):
+    path = "./column_families_listing"
+
+    def test_column_families(self):
+        db = Rdict(self.path)
+        db.create_column_family("a")["x"] = 1
+        db.create_column_family("b")["y"] = 2
+        db.create_column_family("c")
+        db.drop_column_family("c")
+        self.assertEqual(sorted(db.cf_names()), ["a", "b", "default"])
+        cfs = db.column_families()
+        self.assertEqual(sorted(cfs), ["a", "b", "default"])
+        self.assertEqual(cfs["a"]["x"], 1)
+        self.assertEqual(cfs["b"]["y"], 2)
+        cfs["default"]["z"] = 3
+        self.assertEqual(db["z"], 3)
+        del cfs
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestFlushCfs(unittest.TestCase):
+    path = "./flush_cfs"
+
+    def test_atomic_flush(self):
+        opt = Options()
+        opt.create_if_missing(True)
+        opt.set_atomic_flush(True)
+        db = Rdict(self.path, opt)
+        cf_a = db.create_column_family("a", opt)
+        cf_b = db.create_column_family("b", opt)
+        cf_a["x"] = 1
+        cf_b["y"] = 2
+        db.flush_cfs(["a", "b"])
+        flushed = {lf["column_family_name"] for lf in db.live_files()}
+        self.assertEqual(flushed, {"a", "b"})
+        with self.assertRaises(Exception):
+            db.flush_cfs(["a", "missing"])
+        del cf_a, cf_b
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestPropertyAllCf(unittest.TestCase):
+    path = "./property_all_cf"
+
+    def test_property_int_value_all_cf(self):
+        db = Rdict(self.path)
+        cf = db.create_column_family("cf")
+        for i in range(10):
+            cf[i] = i
+        db["a"] = 1
+        values = db.property_int_value_all_cf("rocksdb.estimate-num-keys")
+        self.assertEqual(values, {"default": 1, "cf": 10})
+        values = db.property_int_value_all_cf("rocksdb.no-such-property")
+        self.assertEqual(values, {"default": None, "cf": None})
+        del cf
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestStatisticsAccessors(unittest.TestCase):
+    path = "./statistics_accessors"
+
+    def test_ticker_and_histogram(self):
+        opt = Options()
+        opt.enable_statistics()
+        db = Rdict(self.path, opt)
+        for i in range(100):
+            db[i] = i
+        for i in range(100):
+            self.assertEqual(db[i], i)
+        written = opt.get_ticker_count("rocksdb.number.keys.written")
+        self.assertEqual(written, 100)
+        self.assertEqual(opt.get_ticker_count("NUMBER_KEYS_WRITTEN"), written)
+        self.assertGreater(opt.get_ticker_count("BYTES_WRITTEN"), 0)
+        data = opt.get_histogram_data("rocksdb.db.get.micros")
+        self.assertEqual(data["count"], 100)
+        self.assertEqual(opt.get_histogram_data("DB_GET")["count"], 100)
+        with self.assertRaises(ValueError):
+            opt.get_ticker_count("rocksdb.no.such.ticker")
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestSharedWriteBufferManager(unittest.TestCase):
+    path1 = "./write_buffer_manager_1"
+    path2 = "./write_buffer_manager_2"
+
+    def test_shared_budget(self):
+        manager = WriteBufferManager(64 * 1024 * 1024, False)
+        self.assertTrue(manager.enabled())
+        self.assertEqual(manager.get_buffer_size(), 64 * 1024 * 1024)
+        opt = Options()
+        opt.set_write_buffer_manager(manager)
+        db1 = Rdict(self.path1, opt)
+        db2 = Rdict(self.path2, opt)
+        db1["a"] = b"a" * 1024
+        usage = manager.get_usage()
+        self.assertGreater(usage, 0)
+        db2["b"] = b"b" * 1024
+        self.assertGreater(manager.get_usage(), usage)
+        self.assertLessEqual(manager.get_usage(), 64 * 1024 * 1024)
+        db1.close()
+        db2.close()
+        Rdict.destroy(self.path1)
+        Rdict.destroy(self.path2)
+
+
+class TestPutWithTtl(unittest.TestCase):
+    path = "./put_with_ttl"
+
+    def test_put_with_ttl(self):
+        opt = Options()
+        opt.set_per_key_ttl_filter()
+        db = Rdict(self.path, opt)
+        db.put_with_ttl("expired", {"a": 1}, 0)
+        db.put_with_ttl("alive", "value", 3600)
+        db["plain"] = 1
+        # expired values are only removed by compactions
+        self.assertEqual(db["expired"], {"a": 1})
+        db.compact_range(None, None)
+        self.assertNotIn("expired", db)
+        self.assertEqual(db["alive"], "value")
+        self.assertEqual(db["plain"], 1)
+        self.assertEqual(list(db.keys()), ["alive", "plain"])
+        db.close()
+        Rdict.destroy(self.path)
+
+    def test_raw_mode(self):
+        opt = Options(raw_mode=True)
+        with self.assertRaises(ValueError):
+            opt.set_per_key_ttl_filter()
+
+
+class TestMultiGetCf(unittest.TestCase):
+    path = "./multi_get_cf"
+    other_path = "./multi_get_cf_other"
+
+    def test_multi_get_cf(self):
+        db = Rdict(self.path)
+        users = db.create_column_family("users")
+        orders = db.create_column_family("orders")
+        users[42] = "alice"
+        orders[1001] = {"user": 42}
+        db["config"] = True
+        users_handle = db.get_column_family_handle("users")
+        orders_handle = db.get_column_family_handle("orders")
+        default_handle = db.get_column_family_handle("default")
+        self.assertEqual(
+            db.multi_get_cf(
+                [
+                    (users_handle, 42),
+                    (orders_handle, 1001),
+                    (default_handle, "config"),
+                    (orders_handle, 42),
+                ]
+            ),
+            ["alice", {"user": 42}, True, None],
+        )
+        self.assertEqual(db.multi_get_cf([(users_handle, 0)], default=-1), [-1])
+        other = Rdict(self.other_path)
+        with self.assertRaises(ValueError):
+            other.multi_get_cf([(users_handle, 42)])
+        other.close()
+        del users, orders, users_handle, orders_handle, default_handle
+        db.close()
+        Rdict.destroy(self.path)
+        Rdict.destroy(self.other_path)
+
+
+class TestSnapshotReads(unittest.TestCase):
+    path = "./snapshot_reads"
+
+    def test_snapshot_batch_and_iteration(self):
+        db = Rdict(self.path)
+        for i in range(100):
+            db[i] = i
+        snapshot = db.snapshot()
+        for i in range(90):
+            del db[i]
+        for i in range(100, 200):
+            db[i] = i
+        db[95] = "changed"
+        self.assertEqual(list(snapshot.keys()), list(range(100)))
+        self.assertEqual(list(snapshot.values(backwards=True)), list(range(99, -1, -1)))
+        self.assertEqual(dict(snapshot.items(from_key=90)), {i: i for i in range(90, 100)})
+        self.assertEqual(snapshot.get_batch([0, 95, 150], default=-1), [0, 95, -1])
+        self.assertEqual(db.get([0, 95, 150]), [None, "changed", 150])
+        del snapshot
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestGetUpdatesSince(unittest.TestCase):
+    path = "./get_updates_since"
+
+    def test_get_updates_since(self):
+        db = Rdict(self.path)
+        cf = db.create_column_family("cf")
+        db["before"] = 0
+        seq = db.latest_sequence_number()
+        db["a"] = [1, 2]
+        cf["b"] = "b"
+        wb = WriteBatch()
+        wb.put(1, 1.5)
+        wb.delete("a")
+        db.write(wb)
+        cf_id = db.get_column_family_handle("cf").id()
+        self.assertEqual(db.get_column_family_handle("default").id(), 0)
+        updates = list(db.get_updates_since(seq))
+        self.assertEqual([s for s, _ in updates], [seq + 1, seq + 2, seq + 3])
+        self.assertEqual(
+            [op for _, batch in updates for op in batch.operations()],
+            [
+                ("put", 0, "a", [1, 2]),
+                ("put", cf_id, "b", "b"),
+                ("put", 0, 1, 1.5),
+                ("delete", 0, "a", None),
+            ],
+        )
+        with self.assertRaises(Exception):
+            db.get_updates_since(db.latest_sequence_number() + 100)
+        del cf
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestWriteBatchIterate(unittest.TestCase):
+    def test_iterate(self):
+        class Recorder:
+            def __init__(self):
+                self.calls = []
+
+            def put(self, cf_id, key, value):
+                self.calls.append(("put", cf_id, key, value))
+
+            def delete(self, cf_id, key):
+                self.calls.append(("delete", cf_id, key))
+
+        wb = WriteBatch()
+        wb["a"] = {"x": 1}
+        wb.merge("b", 2)
+        del wb[3]
+        self.assertEqual(len(wb), 3)
+        self.assertGreater(wb.size_in_bytes(), 0)
+        recorder = Recorder()
+        wb.iterate(recorder)
+        # merges are skipped, since the handler has no `merge` method
+        self.assertEqual(recorder.calls, [("put", 0, "a", {"x": 1}), ("delete", 0, 3)])
+        self.assertEqual(
+            wb.operations(),
+            [("put", 0, "a", {"x": 1}), ("merge", 0, "b", 2), ("delete", 0, 3, None)],
+        )
+
+    def test_raw_mode(self):
+        wb = WriteBatch(raw_mode=True)
+        wb[b"k"] = b"v"
+        self.assertEqual(wb.operations(), [("put", 0, b"k", b"v")])
+
+
+class TestWriteBatchSavePoint(unittest.TestCase):
+    path = "./write_batch_save_point"
+
+    def test_save_points(self):
+        wb = WriteBatch()
+        wb["a"] = 1
+        wb.set_save_point()
+        wb["b"] = 2
+        wb.set_save_point()
+        del wb["a"]
+        wb.rollback_to_save_point()
+        self.assertEqual(len(wb), 2)
+        wb.pop_save_point()
+        with self.assertRaises(Exception):
+            wb.rollback_to_save_point()
+        wb.set_save_point()
+        wb["c"] = 3
+        wb.rollback_to_save_point()
+        self.assertEqual(
+            wb.operations(), [("put", 0, "a", 1), ("put", 0, "b", 2)]
+        )
+        db = Rdict(self.path)
+        db.write(wb)
+        self.assertEqual(dict(db.items()), {"a": 1, "b": 2})
+        db.close()
+        Rdict.destroy(self.path)
+
+    def test_clear(self):
+        wb = WriteBatch(raw_mode=True)
+        wb[b"a"] = b"1"
+        wb.set_save_point()
+        wb.clear()
+        self.assertEqual(len(wb), 0)
+        with self.assertRaises(Exception):
+            wb.pop_save_point()
+        wb[b"b"] = b"2"
+        self.assertEqual(wb.operations(), [("put", 0, b"b", b"2")])
+
+
+class TestDeleteBatch(unittest.TestCase):
+    path = "./delete_batch"
+
+    def test_delete_batch(self):
+        db = Rdict(self.path)
+        db.update({i: i for i in range(10)})
+        db.delete_batch([1, 5, 8, 100])
+        self.assertEqual(list(db.keys()), [0, 2, 3, 4, 6, 7, 9])
+        # a key that cannot be encoded fails the whole batch
+        with self.assertRaises(Exception):
+            db.delete_batch([0, [1, 2], 9])
+        self.assertEqual(list(db.keys()), [0, 2, 3, 4, 6, 7, 9])
+        cf = db.create_column_family("cf")
+        cf.update({i: i for i in range(3)})
+        cf.delete_batch(k for k in (0, 2))
+        self.assertEqual(list(cf.keys()), [1])
+        self.assertEqual(list(db.keys()), [0, 2, 3, 4, 6, 7, 9])
+        del cf
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestGetSet(unittest.TestCase):
+    path = "./getset"
+
+    def test_getset(self):
+        db = Rdict(self.path)
+        self.assertIsNone(db.getset("key", [1]))
+        self.assertEqual(db.getset("key", {"a": 2}), [1])
+        self.assertEqual(db["key"], {"a": 2})
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestCompact(unittest.TestCase):
+    path = "./compact"
+
+    def test_compact(self):
+        opt = Options()
+        opt.set_disable_auto_compactions(True)
+        db = Rdict(self.path, opt)
+        for i in range(5):
+            for j in range(10):
+                db[i * 10 + j] = j
+            db.flush()
+        files = db.live_files()
+        self.assertEqual(len(files), 5)
+        self.assertTrue(all(f["level"] == 0 for f in files))
+        db.compact()
+        files = db.live_files()
+        self.assertEqual(len(files), 1)
+        self.assertGreater(files[0]["level"], 1)
+        db.compact(target_level=1)
+        self.assertEqual([f["level"] for f in db.live_files()], [1])
+        self.assertEqual(len(list(db.keys())), 50)
+        db.close()
+        Rdict.destroy(self.path)
+
+    def test_compact_keeps_compact_options(self):
+        opt = Options()
+        opt.set_disable_auto_compactions(True)
+        db = Rdict(self.path, opt)
+        db[0] = 0
+        db.flush()
+        compact_opt = CompactOptions()
+        db.compact(target_level=1, compact_opt=compact_opt)
+        self.assertEqual([f["level"] for f in db.live_files()], [1])
+        db.close()
+        Rdict.destroy(self.path)
+        # the target level is not left set on the caller's options
+        db = Rdict(self.path, opt)
+        db[0] = 0
+        db.flush()
+        db.compact_range(None, None, compact_opt)
+        [file] = db.live_files()
+        self.assertGreater(file["level"], 1)
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestForceBottommostCompaction(unittest.TestCase):
+    path = "./force_bottommost_compaction"
+
+    def test_force_rewrites_bottom_level(self):
+        db = Rdict(self.path)
+        for i in range(100):
+            db[i] = i
+        db.flush()
+        db.compact()
+        [bottom_file] = db.live_files()
+        # the bottom level file is left untouched by default
+        db.compact()
+        self.assertEqual([f["name"] for f in db.live_files()], [bottom_file["name"]])
+        compact_opt = CompactOptions()
+        compact_opt.set_exclusive_manual_compaction(True)
+        compact_opt.set_bottommost_level_compaction(BottommostLevelCompaction.force())
+        db.compact_range(None, None, compact_opt)
+        [rewritten] = db.live_files()
+        self.assertNotEqual(rewritten["name"], bottom_file["name"])
+        self.assertEqual(rewritten["level"], bottom_file["level"])
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestWaitForCompact(unittest.TestCase):
+    path = "./wait_for_compact"
+
+    def test_wait_for_compact(self):
+        opt = Options()
+        opt.set_level_zero_file_num_compaction_trigger(2)
+        db = Rdict(self.path, opt)
+        for i in range(4):
+            for j in range(10):
+                db[i * 10 + j] = j
+            db.flush()
+        db[100] = 100
+        db.wait_for_compact()
+        self.assertEqual(db.property_int_value("rocksdb.compaction-pending"), 0)
+        self.assertEqual(db.property_int_value("rocksdb.num-running-compactions"), 0)
+        # the memtable was flushed before waiting
+        self.assertEqual(db.property_int_value("rocksdb.num-entries-active-mem-table"), 0)
+        self.assertEqual(len(list(db.keys())), 41)
+        db.wait_for_compact(flush=False, timeout=10.0)
+        with self.assertRaises(ValueError):
+            db.wait_for_compact(timeout=0)
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestDbPaths(unittest.TestCase):
+    path = "./db_paths"
+
+    def test_db_paths_round_trip(self):
+        fast = DBPath(self.path + "_fast", 1024 * 1024)
+        slow = DBPath(self.path + "_slow", 1024 * 1024 * 1024)
+        self.assertEqual(fast.path, self.path + "_fast")
+        self.assertEqual(fast.target_size, 1024 * 1024)
+        opt = Options()
+        self.assertEqual(opt.get_db_paths(), [])
+        opt.set_db_paths([fast, slow])
+        self.assertEqual(
+            opt.get_db_paths(),
+            [(self.path + "_fast", 1024 * 1024), (self.path + "_slow", 1024 * 1024 * 1024)],
+        )
+        db = Rdict(self.path, opt)
+        db[0] = b"x" * 100
+        db.flush()
+        # sst files go to the first path with room
+        self.assertTrue(all(f.endswith(".sst") for f in os.listdir(self.path + "_fast")))
+        self.assertEqual(len(os.listdir(self.path + "_fast")), 1)
+        db.close()
+        Rdict.destroy(self.path, opt)
+        shutil.rmtree(self.path + "_fast", ignore_errors=True)
+        shutil.rmtree(self.path + "_slow", ignore_errors=True)
+
+
+class TestOptionsFileRoundTrip(unittest.TestCase):
+    path = "./options_file_round_trip"
+
+    def test_reload_tuned_options(self):
+        opt = Options()
+        opt.create_missing_column_families(True)
+        opt.set_write_buffer_size(8 * 1024 * 1024)
+        cf_opt = Options()
+        cf_opt.set_max_write_buffer_number(5)
+        db = Rdict(self.path, opt, column_families={"tuned": cf_opt})
+        options_file = db.latest_options_file()
+        self.assertTrue(os.path.basename(options_file).startswith("OPTIONS-"))
+        with open(options_file) as f:
+            content = f.read()
+        self.assertIn('[CFOptions "tuned"]', content)
+        self.assertIn("max_write_buffer_number=5", content)
+        db.close()
+        loaded, cf_opts = Options.load_latest(self.path)
+        self.assertEqual(set(cf_opts), {"default", "tuned"})
+        db = Rdict(self.path, loaded, column_families=cf_opts)
+        with open(db.latest_options_file()) as f:
+            self.assertIn("max_write_buffer_number=5", f.read())
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestReadOptionsSnapshot(unittest.TestCase):
+    path = "./read_options_snapshot"
+
+    def test_snapshot_read_options(self):
+        db = Rdict(self.path)
+        for i in range(10):
+            db[i] = i
+        read_opt = ReadOptions()
+        read_opt.set_snapshot(db.snapshot())
+        for i in range(10):
+            db[i] = -i
+        db[10] = 10
+        self.assertEqual(db.get(1, read_opt=read_opt), 1)
+        self.assertEqual(db.get(10, read_opt=read_opt), None)
+        self.assertEqual(db.get([1, 2], read_opt=read_opt), [1, 2])
+        self.assertEqual(list(db.values(read_opt=read_opt)), list(range(10)))
+        # iterators keep the snapshot alive
+        it = db.items(read_opt=read_opt)
+        read_opt.set_snapshot(None)
+        del read_opt
+        gc.collect()
+        self.assertEqual(list(it), [(i, i) for i in range(10)])
+        del it
+        db.close()
+        Rdict.destroy(self.path)
+
+    def test_snapshot_of_other_db(self):
+        db = Rdict(self.path)
+        other = Rdict(self.path + "_other")
+        read_opt = ReadOptions()
+        read_opt.set_snapshot(other.snapshot())
+        with self.assertRaises(ValueError):
+            db.get(0, read_opt=read_opt)
+        with self.assertRaises(ValueError):
+            db.items(read_opt=read_opt)
+        del read_opt
+        other.close()
+        db.close()
+        Rdict.destroy(self.path)
+        Rdict.destroy(self.path + "_other")
+
+
+class TestUserTimestamp(unittest.TestCase):
+    path = "./user_timestamp"
+
+    def test_versions(self):
+        opt = Options(raw_mode=True)
+        opt.enable_user_timestamp()
+        db = Rdict(self.path, opt)
+        db.put_with_ts(b"a", 1, b"a1")
+        db.put_with_ts(b"a", 3, b"a3")
+        db.put_with_ts(b"b", 2, b"b2")
+        all_versions = ReadOptions(raw_mode=True)
+        all_versions.set_timestamp(2 ** 64 - 1)
+        all_versions.set_iter_start_ts(0)
+        self.assertEqual(list(db.values(read_opt=all_versions)), [b"a3", b"a1", b"b2"])
+        db.delete_with_ts(b"b", 4)
+        read_opt = ReadOptions(raw_mode=True)
+        for ts, expected in [(0, [None, None]), (1, [b"a1", None]),
+                             (2, [b"a1", b"b2"]), (3, [b"a3", b"b2"]),
+                             (4, [b"a3", None])]:
+            read_opt.set_timestamp(ts)
+            self.assertEqual(db.get([b"a", b"b"], read_opt=read_opt), expected)
+        read_opt.set_timestamp(2)
+        self.assertEqual(list(db.items(read_opt=read_opt)), [(b"a", b"a1"), (b"b", b"b2")])
+        db.close()
+        Rdict.destroy(self.path, opt)
+
+    def test_raw_mode_only(self):
+        with self.assertRaises(ValueError):
+            Options().enable_user_timestamp()
+
+
+class TestScanReadahead(unittest.TestCase):
+    path = "./scan_readahead"
+
+    def test_async_io_scan(self):
+        db = Rdict(self.path)
+        for i in range(1000):
+            db[i] = i
+        db.flush()
+        read_opt = ReadOptions()
+        read_opt.set_async_io(True)
+        read_opt.set_auto_readahead_size(True)
+        read_opt.set_iterate_upper_bound(500)
+        self.assertEqual(list(db.keys(read_opt=read_opt)), list(range(500)))
+        read_opt.set_auto_readahead_size(False)
+        self.assertEqual(list(db.values(read_opt=read_opt, backwards=True)), list(range(499, -1, -1)))
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestDeleteFileInRange(unittest.TestCase):
+    path = "./delete_file_in_range"
+
+    def test_delete_whole_files(self):
+        opt = Options()
+        opt.set_disable_auto_compactions(True)
+        db = Rdict(self.path, opt)
+        for i in range(100):
+            db[i] = i
+        db.flush()
+        db.compact()
+        for i in range(100, 200):
+            db[i] = i
+        db.flush()
+        db.compact_range(100, 199)
+        self.assertEqual(len(db.live_files()), 2)
+        db[150] = 150
+        db.delete_file_in_range(100, 199)
+        self.assertEqual(len(db.live_files()), 1)
+        # the memtable is not affected
+        self.assertEqual(list(db.keys()), list(range(100)) + [150])
+        # files partially in the range are kept
+        db.delete_file_in_range(50, 199)
+        self.assertEqual(len(list(db.keys())), 101)
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestApproximateSizes(unittest.TestCase):
+    path = "./approximate_sizes"
+
+    def test_approximate_sizes(self):
+        opt = Options()
+        opt.set_compression_type(DBCompressionType.none())
+        db = Rdict(self.path, opt)
+        for i in range(10000):
+            db[i] = randbytes(100)
+        db.flush()
+        sizes = db.get_approximate_sizes([(0, 5000), (5000, 10000), (20000, 30000)])
+        self.assertEqual(len(sizes), 3)
+        self.assertGreater(sizes[0], 0)
+        self.assertLess(abs(sizes[0] - sizes[1]), sizes[0] // 2)
+        self.assertEqual(sizes[2], 0)
+        self.assertEqual(db.get_approximate_sizes([]), [])
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestApproximateMemtableSize(unittest.TestCase):
+    path = "./approximate_memtable_size"
+
+    def test_memtable_size(self):
+        db = Rdict(self.path)
+        for i in range(1000):
+            db[i] = randbytes(100)
+        self.assertGreater(db.get_approximate_memtable_size(0, 1000), 0)
+        self.assertEqual(db.get_approximate_memtable_size(2000, 3000), 0)
+        db.flush()
+        self.assertEqual(db.get_approximate_memtable_size(0, 1000), 0)
+        db.close()
+        Rdict.destroy(self.path)
+
+
+class TestBuiltinComparator(unittest.TestCase):
+    path = "./builtin_comparator"
+
+    def test_reverse_bytewise(self):
+        opt = Options(raw_mode=True)
+        opt.set_builtin_comparator("reverse_bytewise")
+        db = Rdict(self.path, opt)
+        for k in [b"a", b"c", b"b", b"ab"]:
+            db[k] = k
+        self.assertEqual(list(db.keys()), [b"c", b"b", b"ab", b"a"])
+        db.close()
+        # the comparator is restored when reopening without options
+        db = Rdict(self.path)
+        self.assertEqual(list(db.keys()), [b"c", b"b", b"ab", b"a"])
+        db.close()
+        Rdict.destroy(self.path, opt)
+
+    def test_invalid(self):
+        with self.assertRaises(ValueError):
+            Options().set_builtin_comparator("reverse_bytewise")
+        with self.assertRaises(ValueError):
+            Options(raw_mode=True).set_builtin_comparator("numeric")
+
+
+class TestReopenConfigMismatch(unittest.TestCase):
+    path = "./reopen_config_mismatch"
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+
+    def test_raw_mode_mismatch(self):
+        Rdict(self.path, Options(raw_mode=True)).close()
+        with self.assertRaisesRegex(Exception, "raw_mode=True"):
+            Rdict(self.path, Options())
+
+    def test_normalize_str_bytes_keys_mismatch(self):
+        opt = Options()
+        opt.set_normalize_str_bytes_keys(True)
+        Rdict(self.path, opt).close()
+        with self.assertRaisesRegex(Exception, "normalize_str_bytes_keys=True"):
+            Rdict(self.path, Options())
+        Rdict(self.path, opt).close()
+
+    def test_comparator_mismatch(self):
+        opt = Options(raw_mode=True)
+        opt.set_builtin_comparator("reverse_bytewise")
+        Rdict(self.path, opt).close()
+        with self.assertRaisesRegex(Exception, "reverse_bytewise"):
+            Rdict(self.path, Options(raw_mode=True))
+        # the config is kept after the failed open
+        Rdict(self.path).close()
+
+
+class TestReadOnlyWrites(unittest.TestCase):
+    path = "./read_only_writes"
+
+    @classmethod
+    def setUpClass(cls):
+        db = Rdict(cls.path)
+        db["a"] = 1
+        db.close()
+        cls.db = Rdict(cls.path, access_type=AccessType.read_only())
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.db.close()
+        Rdict.destroy(cls.path)
+
+    def test_setitem(self):
+        with self.assertRaisesRegex(RocksDBError, "read-only"):
+            self.db["b"] = 2
+
+    def test_delitem(self):
+        with self.assertRaisesRegex(RocksDBError, "read-only"):
+            del self.db["a"]
+        self.assertEqual(self.db["a"], 1)
+
+    def test_write(self):
+        wb = WriteBatch()
+        wb.put("b", 2)
+        with self.assertRaisesRegex(RocksDBError, "read-only"):
+            self.db.write(wb)
+
+    def test_merge(self):
+        with self.assertRaisesRegex(RocksDBError, "read-only"):
+            self.db.merge("a", 1)
+
+    def test_put_raw(self):
+        with self.assertRaisesRegex(RocksDBError, "read-only"):
+            self.db.put_raw(b"b", b"2")
+
+    def test_delete_range(self):
+        with self.assertRaisesRegex(RocksDBError, "read-only"):
+            self.db.delete_range("a", "z")
+        self.assertEqual(self.db["a"], 1)
+
+
+class TestExceptionTypes(unittest.TestCase):
+    path = "./exception_types"
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+
+    def test_key_not_found(self):
+        db = Rdict(self.path)
+        with self.assertRaises(KeyNotFoundError):
+            db["missing"]
+        # still catchable as a plain KeyError
+        with self.assertRaises(KeyError):
+            db["missing"]
+        db.close()
+
+    def test_missing_key_is_key_error(self):
+        db = Rdict(self.path)
+        db["a"] = 1
+        try:
+            db["b"]
+        except Exception as exc:
+            self.assertIsInstance(exc, KeyError)
+        else:
+            self.fail("missing key did not raise")
+        snapshot = db.snapshot()
+        with self.assertRaises(KeyError):
+            snapshot["b"]
+        del snapshot
+        # a missing key is not confused with a RocksDB error
+        with self.assertRaises(KeyError) as ctx:
+            db["b"]
+        self.assertNotIsInstance(ctx.exception, RocksDBError)
+        db.close()
+
+    def test_db_closed(self):
+        db = Rdict(self.path)
+        db.close()
+        with self.assertRaises(RocksDBError):
+            db.get(1)
+        self.assertTrue(issubclass(DbClosedError, RocksDBError))
+
+    def test_rocksdb_error(self):
+        Rdict(self.path).close()
+        opt = Options()
+        opt.set_error_if_exists(True)
+        with self.assertRaises(RocksDBError):
+            Rdict(self.path, opt)
+
+
+class TestRepairSummary(unittest.TestCase):
+    path = "./repair_summary"
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+
+    def test_repair_summary(self):
+        db = Rdict(self.path)
+        cf = db.create_column_family("cf")
+        for i in range(100):
+            db[i] = i
+            cf[i] = i
+        del cf
+        db.close()
+        summary = Rdict.repair(self.path)
+        self.assertEqual(set(summary["column_families"]), {"default", "cf"})
+        self.assertIsNotNone(summary["log_path"])
+        self.assertGreaterEqual(summary["recovered_files"], 1)
+        self.assertGreater(summary["recovered_bytes"], 0)
+        # the old MANIFEST is archived
+        self.assertTrue(any("MANIFEST-" in f for f in summary["archived_files"]))
+        self.assertIsInstance(summary["warnings"], list)
+        db = Rdict(self.path)
+        self.assertEqual(db[99], 99)
+        db.close()
+
+
+class TestWriteParallel(unittest.TestCase):
+    path = "./write_parallel"
+
+    @classmethod
+    def setUpClass(cls):
+        cls.db = Rdict(cls.path)
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.db.close()
+        Rdict.destroy(cls.path)
+
+    def test_write_parallel(self):
+        self.db.write_parallel(((i, str(i)) for i in range(10000)), threads=4)
+        self.assertEqual(self.db[0], "0")
+        self.assertEqual(self.db[9999], "9999")
+        self.assertEqual(len(list(self.db.keys())), 10000)
+
+    def test_last_value_wins(self):
+        self.db.write_parallel([("dup", 1), ("other", 2), ("dup", 3)], threads=3)
+        self.assertEqual(self.db["dup"], 3)
+
+    def test_dict_and_column_family(self):
+        cf = self.db.create_column_family("parallel_cf")
+        cf.write_parallel({"a": 1, "b": 2})
+        self.assertEqual(cf["b"], 2)
+        self.assertNotIn("a", self.db)
+        del cf
+        self.db.drop_column_family("parallel_cf")
+
+    def test_invalid_threads(self):
+        with self.assertRaises(ValueError):
+            self.db.write_parallel([(1, 1)], threads=0)
+
+
+class TestCacheCapacity(unittest.TestCase):
+    def test_get_capacity(self):
+        cache = Cache(1024 * 1024)
+        self.assertEqual(cache.get_capacity(), 1024 * 1024)
+        cache.set_capacity(2 * 1024 * 1024)
+        self.assertEqual(cache.get_capacity(), 2 * 1024 * 1024)
+        cache = Cache.new_hyper_clock_cache(4 * 1024 * 1024, 4096)
+        self.assertEqual(cache.get_capacity(), 4 * 1024 * 1024)
+
+    def test_block_cache_hit_ratio(self):
+        path = "./cache_hit_ratio"
+        cache = Cache(8 * 1024 * 1024)
+        table_options = BlockBasedOptions()
+        table_options.set_block_cache(cache)
+        opt = Options()
+        opt.enable_statistics()
+        opt.set_block_based_table_factory(table_options)
+        db = Rdict(path, opt)
+        for i in range(1000):
+            db[i] = i
+        db.flush()
+        for _ in range(2):
+            for i in range(1000):
+                self.assertEqual(db[i], i)
+        self.assertGreater(opt.get_ticker_count("rocksdb.block.cache.hit"), 0)
+        self.assertGreater(cache.get_usage(), 0)
+        db.close()
+        Rdict.destroy(path)
+
+
+class TestRibbonFilter(unittest.TestCase):
+    path = "./ribbon_filter"
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+
+    def check_filter(self, table_options):
+        opt = Options()
+        opt.set_block_based_table_factory(table_options)
+        db = Rdict(self.path, opt)
+        for i in range(1000):
+            db[i] = i
+        db.flush()
+        for i in range(1000):
+            self.assertEqual(db[i], i)
+        self.assertNotIn(1000, db)
+        db.close()
+
+    def test_ribbon_filter(self):
+        table_options = BlockBasedOptions()
+        table_options.set_ribbon_filter(10.0)
+        self.check_filter(table_options)
+
+    def test_ribbon_filter_with_bloom_fallback(self):
+        table_options = BlockBasedOptions()
+        table_options.set_ribbon_filter_with_bloom_fallback(10.0, 1)
+        self.check_filter(table_options)
+
+    def test_optimize_filters_for_memory(self):
+        table_options = BlockBasedOptions()
+        table_options.set_ribbon_filter(10.0)
+        table_options.set_optimize_filters_for_memory(True)
+        table_options.set_whole_key_filtering(True)
+        self.check_filter(table_options)
+
+
+class TestConfigurePrefixLookup(unittest.TestCase):
+    path = "./configure_prefix_lookup"
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+
+    def test_configure_prefix_lookup(self):
+        opt = Options(raw_mode=True)
+        opt.configure_prefix_lookup(4)
+        db = Rdict(self.path, opt)
+        for i in range(100):
+            db[b"user%04d" % i] = b"u"
+            db[b"item%04d" % i] = b"i"
+        db.flush()
+        self.assertEqual(db[b"user0042"], b"u")
+        self.assertNotIn(b"user0100", db)
+        self.assertEqual(len(list(db.iter_prefix(b"item"))), 100)
+        db.close()
+        # the prefix extractor is persisted
+        db = Rdict(self.path)
+        self.assertEqual(len(list(db.iter_prefix(b"user"))), 100)
+        db.close()
+
+    def test_table_options(self):
+        cache = Cache(1024 * 1024)
+        table_options = BlockBasedOptions()
+        table_options.set_block_cache(cache)
+        opt = Options(raw_mode=True)
+        opt.configure_prefix_lookup(4, bloom_bits=8.0, table_options=table_options)
+        db = Rdict(self.path, opt)
+        db[b"abcd1"] = b"1"
+        db.flush()
+        self.assertEqual(db[b"abcd1"], b"1")
+        self.assertGreater(cache.get_usage(), 0)
+        db.close()
+
+    def test_invalid_prefix_len(self):
+        with self.assertRaises(ValueError):
+            Options().configure_prefix_lookup(0)
+
+
+class TestEffectiveOptions(unittest.TestCase):
+    path = "./effective_options"
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+
+    def test_get_use_fsync(self):
+        opt = Options()
+        self.assertFalse(opt.get_use_fsync())
+        opt.set_use_fsync(True)
+        self.assertTrue(opt.get_use_fsync())
+
+    def test_options_getters(self):
+        opt = Options()
+        opt.set_num_levels(5)
+        opt.set_write_buffer_size(32 * 1024 * 1024)
+        opt.set_max_write_buffer_number(4)
+        opt.set_target_file_size_base(16 * 1024 * 1024)
+        opt.set_max_bytes_for_level_multiplier(8.0)
+        opt.set_level_zero_file_num_compaction_trigger(6)
+        opt.set_disable_auto_compactions(True)
+        opt.set_max_background_jobs(3)
+        opt.set_max_subcompactions(2)
+        opt.set_max_open_files(100)
+        self.assertEqual(opt.get_num_levels(), 5)
+        self.assertEqual(opt.get_write_buffer_size(), 32 * 1024 * 1024)
+        self.assertEqual(opt.get_max_write_buffer_number(), 4)
+        self.assertEqual(opt.get_target_file_size_base(), 16 * 1024 * 1024)
+        self.assertEqual(opt.get_max_bytes_for_level_multiplier(), 8.0)
+        self.assertEqual(opt.get_level_zero_file_num_compaction_trigger(), 6)
+        self.assertTrue(opt.get_disable_auto_compactions())
+        self.assertEqual(opt.get_max_background_jobs(), 3)
+        self.assertEqual(opt.get_max_subcompactions(), 2)
+        self.assertEqual(opt.get_max_open_files(), 100)
+
+    def test_get_effective_options(self):
+        opt = Options()
+        opt.set_num_levels(5)
+        opt.set_write_buffer_size(32 * 1024 * 1024)
+        opt.set_target_file_size_base(16 * 1024 * 1024)
+        opt.set_max_background_jobs(3)
+        db = Rdict(self.path, opt)
+        effective = db.get_effective_options()
+        self.assertEqual(effective["num_levels"], 5)
+        self.assertEqual(effective["write_buffer_size"], 32 * 1024 * 1024)
+        self.assertEqual(effective["target_file_size_base"], 16 * 1024 * 1024)
+        self.assertEqual(effective["max_background_jobs"], 3)
+        db.set_options({"target_file_size_base": str(8 * 1024 * 1024)})
+        self.assertEqual(
+            db.get_effective_options()["target_file_size_base"], 8 * 1024 * 1024
+        )
+        db.close()
+
+
+class TestBlobFiles(unittest.TestCase):
+    path = "./blob_files"
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+
+    def test_large_values_go_to_blob_files(self):
+        opt = Options(raw_mode=True)
+        opt.set_enable_blob_files(True)
+        opt.set_min_blob_size(4096)
+        opt.set_blob_file_size(1024 * 1024)
+        opt.set_blob_compression_type(DBCompressionType.lz4())
+        opt.set_enable_blob_gc(True)
+        db = Rdict(self.path, opt)
+        value = os.urandom(50 * 1024)
+        for i in range(20):
+            db[b"key%d" % i] = value
+        db.flush()
+        blob_files = [f for f in os.listdir(self.path) if f.endswith(".blob")]
+        self.assertGreater(len(blob_files), 0)
+        self.assertEqual(db[b"key7"], value)
+        db.close()
+
+    def test_force_blob_gc(self):
+        opt = Options(raw_mode=True)
+        opt.set_enable_blob_files(True)
+        opt.set_min_blob_size(1024)
+        opt.set_blob_gc_age_cutoff(0.25)
+        opt.set_blob_gc_force_threshold(0.5)
+        db = Rdict(self.path, opt)
+        for i in range(20):
+            db[b"key%d" % i] = b"a" * 4096
+        db.flush()
+        # overwrite half of the values, and drop the old ones without collecting
+        for i in range(10):
+            db[b"key%d" % i] = b"b" * 4096
+        db.flush()
+        db.compact_range(None, None)
+        garbage = "rocksdb.live-blob-file-garbage-size"
+        self.assertGreater(db.property_int_value(garbage), 0)
+        db.force_blob_gc()
+        self.assertEqual(db.property_int_value(garbage), 0)
+        self.assertEqual(db[b"key0"], b"b" * 4096)
+        self.assertEqual(db[b"key19"], b"a" * 4096)
+        db.close()
+
+
+class TestCompactionPri(unittest.TestCase):
+    path = "./compaction_pri"
+
+    def tearDown(self):
+        Rdict.destroy(self.path)
+
+    def test_set_compaction_pri(self):
+        for pri in [
+            CompactionPri.by_compensated_size(),
+            CompactionPri.oldest_largest_seq_first(),
+            CompactionPri.oldest_smallest_seq_first(),
+            CompactionPri.min_overlapping_ratio(),
+            CompactionPri.round_robin(),
+        ]:
+            opt = Options()
+            opt.set_compaction_pri(pri)
+            db = Rdict(self.path, opt)
+            db["key"] = "value"
+            db.compact_range(None, None)
+            self.assertEqual(db["key"], "value")
+            db.close()
+
+
+class TestCorruptedValues(unittest.TestCase):
+    path = "./temp_corrupted_values"
+
+    def test_short_payloads(self):
+        db = Rdict(self.path)
+        # keys are the encodings of the ints 1 to 5
+        db.put_raw(b"\x03\x01", b"\x04\x00")
+        db.put_raw(b"\x03\x02", b"\x08\x00\x00")
+        db.put_raw(b"\x03\x03", b"\x09\x00")
+        db.put_raw(b"\x03\x04", b"\x0a" + bytes(11))
+        db.put_raw(b"\x03\x05", b"\x0d" + bytes(4))
+        for key in range(1, 6):
+            with self.assertRaisesRegex(RocksDBError, "corrupted value"):
+                db[key]
+        db.close()
+
+    @classmethod
+    def tearDownClass(cls):
+        gc.collect()
+        Rdict.destroy(cls.path)
+
+
 class TestBigInt(unittest.TestCase):
     test_dict = None
     opt = None
@@ -584,6 +3219,8 @@ class TestWideColumnsRaw(unittest.TestCase):
         self.assertEqual(self.test_dict.get_entity(b"Guangdong"), [(b"city", b"Shenzhen"), (b"language", b"Cantonese")])
         self.assertEqual(self.test_dict[b"Sichuan"], b"")
         self.assertEqual(self.test_dict.get_entity(b"Sichuan"), [(b"city", b"Chengdu"), (b"language", b"Sichuanhua")])
+        self.assertIsNone(self.test_dict.get_entity(b"Tibet"))
+        self.assertEqual(self.test_dict.get_entity(b"Tibet", []), [])
 
         # iterator
         it = self.test_dict.iter()
@@ -1166,6 +3803,19 @@ class TestCheckpoint(unittest.TestCase):
 
         checkpoint_dict.close()
 
+    def test_rdict_create_checkpoint(self):
+        assert self.test_dict is not None
+        path = "./temp_checkpoint_from_rdict"
+        self.test_dict["unflushed"] = "value"
+        self.test_dict.create_checkpoint(path, log_size_for_flush=0)
+        # the path must not exist
+        self.assertRaises(Exception, self.test_dict.create_checkpoint, path)
+
+        checkpoint_dict = Rdict(path)
+        self.assertEqual(checkpoint_dict["unflushed"], "value")
+        checkpoint_dict.close()
+        Rdict.destroy(path)
+
     @classmethod
     def tearDownClass(cls):
         assert cls.test_dict is not None